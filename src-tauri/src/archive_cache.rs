@@ -0,0 +1,233 @@
+//! Global cache of downloaded mod archives, shared by all profiles.
+//!
+//! Archives live in `<app_cache>/archives/<Author-Name-Version>.zip`. A hit is
+//! only served when its size matches what Thunderstore reports for that
+//! version. `index.json` next to them tracks size and last use so the cache can
+//! be pruned least-recently-used first and kept under the configured quota.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{atomic::{AtomicU64, Ordering}, Mutex},
+};
+use tauri::{AppHandle, Manager};
+
+use crate::storage;
+
+/// Serializes index.json updates across concurrent installs
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Keys of archives that are being fetched or installed, with their pin counts
+static PINNED: Mutex<Vec<(String, usize)>> = Mutex::new(Vec::new());
+
+/// Keeps an archive out of `prune` until dropped
+pub struct Pin {
+    key: String,
+}
+
+/// Pin `key` so concurrent prunes leave its archive alone
+pub fn pin(key: &str) -> Pin {
+    let key = sanitize_key(key);
+    let mut pinned = PINNED.lock().unwrap_or_else(|e| e.into_inner());
+    match pinned.iter_mut().find(|(k, _)| *k == key) {
+        Some((_, count)) => *count += 1,
+        None => pinned.push((key.clone(), 1)),
+    }
+    Pin { key }
+}
+
+impl Drop for Pin {
+    fn drop(&mut self) {
+        let mut pinned = PINNED.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(pos) = pinned.iter().position(|(k, _)| *k == self.key) {
+            pinned[pos].1 -= 1;
+            if pinned[pos].1 == 0 {
+                pinned.swap_remove(pos);
+            }
+        }
+    }
+}
+
+/// How many pins `key` (sanitized) has
+fn pins(key: &str) -> usize {
+    PINNED.lock().unwrap_or_else(|e| e.into_inner()).iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, count)| *count)
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    size: u64,
+    /// Seconds since the Unix epoch
+    downloaded: u64,
+    last_used: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CacheStats {
+    pub path: String,
+    pub entry_count: usize,
+    pub total_bytes: u64,
+    pub quota_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PruneResult {
+    pub removed: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+pub struct ArchiveCache {
+    dir: PathBuf,
+}
+
+/// Keys become file names, so keep only characters Thunderstore names use
+fn sanitize_key(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+        .collect()
+}
+
+impl ArchiveCache {
+    pub fn open(app: &AppHandle) -> Result<Self, String> {
+        let dir = app.path().app_cache_dir().map_err(|e| e.to_string())?.join("archives");
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        Ok(Self { dir })
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("index.json")
+    }
+
+    fn archive_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.zip", sanitize_key(key)))
+    }
+
+    fn load_index(&self) -> HashMap<String, CacheEntry> {
+        fs::read_to_string(self.index_path()).ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_index(&self, index: &HashMap<String, CacheEntry>) {
+        if let Ok(data) = serde_json::to_string_pretty(index) {
+            if let Err(e) = storage::atomic_write(&self.index_path(), data.as_bytes()) {
+                eprintln!("[archive_cache] Failed to save index: {}", e);
+            }
+        }
+    }
+
+    /// Path of a cached archive for `key` ("Author-Name-Version"), if present and the
+    /// size matches `expected_size`. Marks the entry as used. The caller holds
+    /// a pin on `key`; a mismatching archive is only discarded if nobody else does.
+    pub fn get(&self, key: &str, expected_size: Option<u64>) -> Option<PathBuf> {
+        let path = self.archive_path(key);
+        let size = fs::metadata(&path).ok()?.len();
+        if let Some(expected) = expected_size {
+            if expected > 0 && expected != size {
+                if pins(&sanitize_key(key)) > 1 {
+                    eprintln!("[archive_cache] Size mismatch for {} ({} != {}), in use elsewhere, keeping it", key, size, expected);
+                } else {
+                    eprintln!("[archive_cache] Size mismatch for {} ({} != {}), discarding", key, size, expected);
+                    let _ = fs::remove_file(&path);
+                }
+                return None;
+            }
+        }
+
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.load_index();
+        let now = storage::unix_now();
+        let entry = index.entry(sanitize_key(key)).or_insert(CacheEntry { size, downloaded: now, last_used: now });
+        entry.last_used = now;
+        self.save_index(&index);
+
+        Some(path)
+    }
+
+    /// Unique temp path for an in-progress download of `key`, on the same
+    /// filesystem as the cache so finished downloads can be moved in
+    pub fn partial_path(&self, key: &str) -> PathBuf {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+        self.dir.join(".partial").join(format!("{}-{}-{}.part", sanitize_key(key), std::process::id(), n))
+    }
+
+    /// Move a downloaded archive into the cache and return its new path.
+    /// Archives whose size doesn't match `expected_size` are not cached.
+    pub fn insert_file(&self, key: &str, file: &Path, expected_size: Option<u64>) -> Result<Option<PathBuf>, String> {
+        let size = fs::metadata(file).map_err(|e| e.to_string())?.len();
+        if let Some(expected) = expected_size {
+            if expected > 0 && expected != size {
+                eprintln!("[archive_cache] Not caching {}: got {} bytes, Thunderstore reports {}", key, size, expected);
+                return Ok(None);
+            }
+        }
+        let path = self.archive_path(key);
+        fs::rename(file, &path).map_err(|e| format!("Failed to cache {}: {}", key, e))?;
+
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.load_index();
+        let now = storage::unix_now();
+        index.insert(sanitize_key(key), CacheEntry { size, downloaded: now, last_used: now });
+        self.save_index(&index);
+
+        Ok(Some(path))
+    }
+
+    /// Index entries for archives that actually exist, dropping stale ones
+    fn entries(&self) -> Vec<(String, CacheEntry)> {
+        let index = self.load_index();
+        index.into_iter()
+            .filter(|(key, _)| self.dir.join(format!("{}.zip", key)).exists())
+            .collect()
+    }
+
+    pub fn stats(&self, quota_bytes: Option<u64>) -> CacheStats {
+        let entries = self.entries();
+        CacheStats {
+            path: self.dir.to_string_lossy().to_string(),
+            entry_count: entries.len(),
+            total_bytes: entries.iter().map(|(_, e)| e.size).sum(),
+            quota_bytes,
+        }
+    }
+
+    /// Remove archives not used for `unused_for_days`, then least recently used
+    /// archives until the cache fits in `max_bytes`. Pinned archives are never removed.
+    pub fn prune(&self, unused_for_days: Option<u64>, max_bytes: Option<u64>) -> PruneResult {
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries = self.entries();
+        entries.sort_by_key(|(_, e)| e.last_used);
+
+        let now = storage::unix_now();
+        let mut total: u64 = entries.iter().map(|(_, e)| e.size).sum();
+        let mut result = PruneResult::default();
+        let mut index: HashMap<String, CacheEntry> = HashMap::new();
+
+        for (key, entry) in entries {
+            let expired = unused_for_days
+                .map(|days| now.saturating_sub(entry.last_used) >= days * 24 * 60 * 60)
+                .unwrap_or(false);
+            let over_quota = max_bytes.map(|max| total > max).unwrap_or(false);
+
+            let removable = (expired || over_quota) && pins(&key) == 0;
+            if removable && fs::remove_file(self.dir.join(format!("{}.zip", key))).is_ok() {
+                total = total.saturating_sub(entry.size);
+                result.bytes_freed += entry.size;
+                result.removed.push(key);
+                continue;
+            }
+            index.insert(key, entry);
+        }
+
+        self.save_index(&index);
+        if !result.removed.is_empty() {
+            eprintln!("[archive_cache] Pruned {} archives, freed {} bytes", result.removed.len(), result.bytes_freed);
+        }
+        result
+    }
+}
@@ -0,0 +1,281 @@
+//! BepInEx `.cfg` files.
+//!
+//! Every plugin config is an INI-like file whose entries carry a comment
+//! header written by BepInEx:
+//!
+//! ```text
+//! [General]
+//!
+//! ## Whether the mod is active
+//! # Setting type: Boolean
+//! # Default value: true
+//! Enabled = true
+//! ```
+//!
+//! Files are kept as their original lines; edits replace only the value part
+//! of an entry's line so comments, ordering, spacing and line endings survive.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::storage;
+
+/// Config folder relative to a profile or game root
+pub const CONFIG_DIR: &str = "BepInEx/config";
+
+/// Whose config folder to use: `{"profile": id}` or `{"game": identifier}`
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigLocation {
+    Profile(String),
+    Game(String),
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ConfigFileInfo {
+    /// Relative to the config folder, with forward slashes
+    pub path: String,
+    pub size: u64,
+    pub modified: u64,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: String,
+    pub description: String,
+    /// "Boolean", "Int32", "String", an enum type name, ...
+    pub value_type: Option<String>,
+    pub default_value: Option<String>,
+    /// Allowed values of an enum or a value list
+    pub acceptable_values: Vec<String>,
+    /// Inclusive (min, max) of a numeric range
+    pub acceptable_range: Option<(String, String)>,
+    /// Enum flags: several values can be combined with ", "
+    pub is_flags: bool,
+    /// 0-based line of `key = value` in the file
+    pub line: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ConfigSection {
+    pub name: String,
+    pub entries: Vec<ConfigEntry>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ConfigFile {
+    pub path: String,
+    /// From the `## Settings file was created by plugin X vY` header
+    pub plugin_name: Option<String>,
+    pub plugin_version: Option<String>,
+    /// From the `## Plugin GUID: ...` header
+    pub plugin_guid: Option<String>,
+    pub sections: Vec<ConfigSection>,
+    #[serde(skip)]
+    lines: Vec<String>,
+}
+
+impl ConfigFile {
+    pub fn parse(path: &str, text: &str) -> Self {
+        let lines: Vec<String> = text.split_inclusive('\n').map(str::to_string).collect();
+        let mut file = ConfigFile { path: path.to_string(), plugin_name: None, plugin_version: None, plugin_guid: None, sections: Vec::new(), lines: Vec::new() };
+        let mut pending = ConfigEntry::default();
+
+        for (i, raw) in lines.iter().enumerate() {
+            let line = raw.trim();
+            if let Some(comment) = line.strip_prefix("##") {
+                let comment = comment.trim();
+                if let Some(rest) = comment.strip_prefix("Settings file was created by plugin ") {
+                    match rest.rsplit_once(" v") {
+                        Some((name, version)) => {
+                            file.plugin_name = Some(name.to_string());
+                            file.plugin_version = Some(version.to_string());
+                        }
+                        None => file.plugin_name = Some(rest.to_string()),
+                    }
+                } else if let Some(guid) = comment.strip_prefix("Plugin GUID:") {
+                    file.plugin_guid = Some(guid.trim().to_string());
+                } else {
+                    if !pending.description.is_empty() {
+                        pending.description.push('\n');
+                    }
+                    pending.description.push_str(comment);
+                }
+            } else if let Some(comment) = line.strip_prefix('#') {
+                let comment = comment.trim();
+                if let Some(t) = comment.strip_prefix("Setting type:") {
+                    pending.value_type = Some(t.trim().to_string());
+                } else if let Some(d) = comment.strip_prefix("Default value:") {
+                    pending.default_value = Some(d.trim().to_string());
+                } else if let Some(values) = comment.strip_prefix("Acceptable values:") {
+                    pending.acceptable_values = values.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect();
+                } else if let Some(range) = comment.strip_prefix("Acceptable value range: From ") {
+                    pending.acceptable_range = range.split_once(" to ").map(|(min, max)| (min.trim().to_string(), max.trim().to_string()));
+                } else if comment.starts_with("Multiple values can be set at the same time") {
+                    pending.is_flags = true;
+                }
+            } else if line.starts_with('[') && line.ends_with(']') {
+                file.sections.push(ConfigSection { name: line[1..line.len() - 1].trim().to_string(), entries: Vec::new() });
+                pending = ConfigEntry::default();
+            } else if let Some((key, value)) = line.split_once('=') {
+                let mut entry = std::mem::take(&mut pending);
+                entry.key = key.trim().to_string();
+                entry.value = value.trim().to_string();
+                entry.line = i;
+                if file.sections.is_empty() {
+                    file.sections.push(ConfigSection { name: String::new(), entries: Vec::new() });
+                }
+                file.sections.last_mut().unwrap().entries.push(entry);
+            } else if line.is_empty() && pending.value_type.is_none() {
+                // Loose comments not followed by an entry
+                pending.description.clear();
+            }
+        }
+        file.lines = lines;
+        file
+    }
+
+    pub fn to_text(&self) -> String {
+        self.lines.concat()
+    }
+
+    fn entry_mut(&mut self, section: &str, key: &str) -> Result<&mut ConfigEntry, String> {
+        self.sections.iter_mut()
+            .find(|s| s.name == section)
+            .and_then(|s| s.entries.iter_mut().find(|e| e.key == key))
+            .ok_or_else(|| format!("No entry '{}' in section [{}] of {}", key, section, self.path))
+    }
+
+    /// Set an entry's value, rewriting only the value part of its line
+    pub fn set_value(&mut self, section: &str, key: &str, value: &str) -> Result<ConfigEntry, String> {
+        if value.contains('\n') || value.contains('\r') {
+            return Err("Config values can't span several lines".to_string());
+        }
+        let entry = self.entry_mut(section, key)?;
+        validate(entry, value)?;
+        entry.value = value.to_string();
+        let entry = entry.clone();
+
+        let raw = &self.lines[entry.line];
+        let body_len = raw.trim_end_matches(['\r', '\n']).len();
+        let (body, ending) = raw.split_at(body_len);
+        // Keep the key and whatever spacing follows the `=`
+        let after_eq = body.find('=').map(|i| i + 1).unwrap_or(body.len());
+        let value_start = body.len() - body[after_eq..].trim_start().len();
+        let prefix = &body[..value_start];
+        self.lines[entry.line] = format!("{}{}{}", prefix, value, ending);
+        Ok(entry)
+    }
+
+    /// Set an entry back to its default value
+    pub fn reset_value(&mut self, section: &str, key: &str) -> Result<ConfigEntry, String> {
+        let default = self.entry_mut(section, key)?.default_value.clone()
+            .ok_or_else(|| format!("Entry '{}' in section [{}] has no default value", key, section))?;
+        self.set_value(section, key, &default)
+    }
+
+    /// Set every entry that has a default back to it; returns how many changed
+    pub fn reset_all(&mut self) -> usize {
+        let defaults: Vec<(String, String, String)> = self.sections.iter()
+            .flat_map(|s| s.entries.iter().map(move |e| (s.name.clone(), e)))
+            .filter_map(|(section, e)| {
+                let default = e.default_value.as_ref()?;
+                (*default != e.value).then(|| (section, e.key.clone(), default.clone()))
+            })
+            .collect();
+        defaults.iter()
+            .filter(|(section, key, default)| match self.set_value(section, key, default) {
+                Ok(_) => true,
+                Err(e) => {
+                    eprintln!("[bepinex_config] Could not reset {}: {}", key, e);
+                    false
+                }
+            })
+            .count()
+    }
+}
+
+/// Check a value against the entry's type, allowed values and range
+fn validate(entry: &ConfigEntry, value: &str) -> Result<(), String> {
+    let bad = |why: String| Err(format!("Invalid value for '{}': {}", entry.key, why));
+    match entry.value_type.as_deref() {
+        Some("Boolean") if !value.eq_ignore_ascii_case("true") && !value.eq_ignore_ascii_case("false") => {
+            return bad("expected true or false".to_string());
+        }
+        Some("Byte" | "SByte" | "Int16" | "UInt16" | "Int32" | "UInt32" | "Int64" | "UInt64") if value.parse::<i128>().is_err() => {
+            return bad("expected a whole number".to_string());
+        }
+        Some("Single" | "Double" | "Decimal") if value.parse::<f64>().is_err() => {
+            return bad("expected a number".to_string());
+        }
+        _ => {}
+    }
+
+    if !entry.acceptable_values.is_empty() {
+        let allowed = |v: &str| entry.acceptable_values.iter().any(|a| a.eq_ignore_ascii_case(v));
+        let ok = if entry.is_flags {
+            value.split(',').map(str::trim).all(allowed)
+        } else {
+            allowed(value)
+        };
+        if !ok {
+            return bad(format!("expected one of {}", entry.acceptable_values.join(", ")));
+        }
+    }
+
+    if let Some((min, max)) = &entry.acceptable_range {
+        if let (Ok(v), Ok(min), Ok(max)) = (value.parse::<f64>(), min.parse::<f64>(), max.parse::<f64>()) {
+            if v < min || v > max {
+                return bad(format!("expected a value from {} to {}", min, max));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `relative` inside `config_dir`, refusing anything that would leave it
+fn resolve(config_dir: &Path, relative: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(relative);
+    if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(format!("Invalid config file path: {}", relative.display()));
+    }
+    Ok(config_dir.join(relative))
+}
+
+/// `.cfg` files under `root`'s config folder, sorted by path
+pub fn list_files(root: &Path) -> Vec<ConfigFileInfo> {
+    let config_dir = root.join(CONFIG_DIR);
+    let mut files: Vec<ConfigFileInfo> = walkdir::WalkDir::new(&config_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path().extension().map(|x| x.eq_ignore_ascii_case("cfg")).unwrap_or(false))
+        .filter_map(|e| {
+            let metadata = e.metadata().ok()?;
+            let path = e.path().strip_prefix(&config_dir).ok()?.to_string_lossy().replace('\\', "/");
+            let modified = metadata.modified().ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            Some(ConfigFileInfo { path, size: metadata.len(), modified })
+        })
+        .collect();
+    files.sort_by_key(|f| f.path.to_lowercase());
+    files
+}
+
+pub fn read_file(root: &Path, relative: &str) -> Result<ConfigFile, String> {
+    let path = resolve(&root.join(CONFIG_DIR), relative)?;
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(ConfigFile::parse(relative, &String::from_utf8_lossy(&bytes)))
+}
+
+pub fn write_file(root: &Path, file: &ConfigFile) -> Result<(), String> {
+    let path = resolve(&root.join(CONFIG_DIR), &file.path)?;
+    storage::atomic_write(&path, file.to_text().as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
@@ -0,0 +1,508 @@
+//! BepInEx's `LogOutput.log`: tailing, parsing and per-plugin attribution.
+//!
+//! Lines look like `[Error  : MoreCompany] message`, with stack traces
+//! continuing on unprefixed lines. Each entry is attributed to a plugin by its
+//! log source (the plugin's name), by the `Loading [Name 1.0.0]` style
+//! chainloader messages, or by the first stack frame that points into a
+//! plugin assembly. Plugins are found by reading the `[BepInPlugin]`
+//! attribute out of every DLL under `BepInEx/plugins`, and mapped to their
+//! package through the install records (or the package folder they sit in).
+//!
+//! The log is rewritten on every game start, so the file on disk is always the
+//! last session.
+
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tauri::{AppHandle, Emitter};
+
+use crate::install_rules;
+
+pub const LOG_FILE: &str = "BepInEx/LogOutput.log";
+const PLUGINS_DIR: &str = "BepInEx/plugins";
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Polls without new output before the last entry is taken as complete
+const IDLE_POLLS_BEFORE_FLUSH: u32 = 4;
+/// Bytes compared to tell a rewritten log from one that grew
+const HEAD_LEN: usize = 256;
+/// How deep plugin DLLs are looked for under `BepInEx/plugins`
+const MAX_PLUGIN_DEPTH: usize = 8;
+
+/// Log sources that are BepInEx or Unity themselves, never a plugin
+const CORE_SOURCES: &[&str] = &["BepInEx", "Unity Log", "Unity", "Preloader", "HarmonyX", "Harmony", "Console", "Detour"];
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Fatal,
+    Error,
+    Warning,
+    Message,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    fn parse(s: &str) -> Self {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "fatal" => LogLevel::Fatal,
+            "error" => LogLevel::Error,
+            "warning" => LogLevel::Warning,
+            "message" => LogLevel::Message,
+            "debug" => LogLevel::Debug,
+            _ => LogLevel::Info,
+        }
+    }
+
+    fn is_error(self) -> bool {
+        matches!(self, LogLevel::Fatal | LogLevel::Error)
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryKind {
+    /// Chainloader `Loading [Name 1.0.0]`
+    Loaded,
+    /// Chainloader `Error loading [...]` / `Could not load [...]`
+    LoadFailed,
+    Exception,
+    Message,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LogEntry {
+    pub level: LogLevel,
+    pub source: String,
+    /// First line plus any continuation lines (stack traces)
+    pub message: String,
+    pub kind: EntryKind,
+    /// Plugin the entry is about, by name if its GUID is unknown
+    pub plugin: Option<String>,
+    pub plugin_guid: Option<String>,
+    pub package: Option<String>,
+}
+
+/// A plugin found in a DLL's `[BepInPlugin(guid, name, version)]`
+#[derive(Debug, Serialize, Clone)]
+pub struct PluginInfo {
+    pub guid: String,
+    pub name: String,
+    pub version: String,
+    /// DLL relative to the game root
+    pub dll: String,
+    pub package: Option<String>,
+}
+
+/// Read a length-prefixed (ECMA-335 compressed length) UTF-8 string
+fn read_ser_string(bytes: &[u8], pos: &mut usize) -> Option<String> {
+    let first = *bytes.get(*pos)?;
+    let (len, header) = if first & 0x80 == 0 {
+        (first as usize, 1)
+    } else if first & 0xC0 == 0x80 {
+        ((((first & 0x3F) as usize) << 8) | *bytes.get(*pos + 1)? as usize, 2)
+    } else {
+        return None;
+    };
+    let start = *pos + header;
+    let s = std::str::from_utf8(bytes.get(start..start + len)?).ok()?;
+    *pos = start + len;
+    Some(s.to_string())
+}
+
+fn is_version(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('.').collect();
+    (2..=4).contains(&parts.len()) && parts.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// `[BepInPlugin]` attributes in an assembly: a custom attribute blob of
+/// prolog 0x0001, three strings (guid, name, version) and no named arguments
+fn scan_bepin_plugins(bytes: &[u8]) -> Vec<(String, String, String)> {
+    let printable = |s: &str| !s.is_empty() && s.len() <= 256 && s.chars().all(|c| !c.is_control());
+    let mut found: Vec<(String, String, String)> = Vec::new();
+    for i in 0..bytes.len().saturating_sub(2) {
+        if bytes[i] != 0x01 || bytes[i + 1] != 0x00 {
+            continue;
+        }
+        let mut pos = i + 2;
+        let Some(guid) = read_ser_string(bytes, &mut pos).filter(|s| printable(s)) else { continue };
+        let Some(name) = read_ser_string(bytes, &mut pos).filter(|s| printable(s)) else { continue };
+        let Some(version) = read_ser_string(bytes, &mut pos).filter(|s| is_version(s)) else { continue };
+        if bytes.get(pos..pos + 2) != Some(&[0, 0]) {
+            continue;
+        }
+        if !found.iter().any(|(g, _, _)| *g == guid) {
+            found.push((guid, name, version));
+        }
+    }
+    found
+}
+
+fn normalize(s: &str) -> String {
+    s.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+/// Plugins installed in a game folder
+pub struct PluginIndex {
+    plugins: Vec<PluginInfo>,
+    /// Normalized DLL file stem -> index into `plugins`
+    by_assembly: HashMap<String, usize>,
+}
+
+impl PluginIndex {
+    /// Scan `BepInEx/plugins`; `profile_dir` supplies install records for
+    /// mapping files to packages
+    pub fn build(game_root: &Path, profile_dir: Option<&Path>) -> Self {
+        let owners: HashMap<String, String> = profile_dir
+            .map(install_rules::list_records)
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|record| {
+                let package = record.package;
+                record.files.into_iter().map(move |f| (f.path.to_lowercase(), package.clone()))
+            })
+            .collect();
+
+        let plugins_dir = game_root.join(PLUGINS_DIR);
+        let mut index = PluginIndex { plugins: Vec::new(), by_assembly: HashMap::new() };
+        // Plugin folders are symlinks into the profile unless installed directly
+        for entry in walkdir::WalkDir::new(&plugins_dir).follow_links(true).max_depth(MAX_PLUGIN_DEPTH).into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !entry.file_type().is_file() || !path.extension().map(|e| e.eq_ignore_ascii_case("dll")).unwrap_or(false) {
+                continue;
+            }
+            let Ok(bytes) = fs::read(path) else { continue };
+            let relative = path.strip_prefix(game_root).unwrap_or(path).to_string_lossy().replace('\\', "/");
+            // Records first; otherwise the package folder the DLL sits in
+            let package = owners.get(&relative.to_lowercase()).cloned().or_else(|| {
+                let inside = path.strip_prefix(&plugins_dir).ok()?;
+                let mut components = inside.components();
+                let first = components.next()?;
+                components.next().map(|_| first.as_os_str().to_string_lossy().to_string())
+            });
+
+            for (guid, name, version) in scan_bepin_plugins(&bytes) {
+                if let Some(stem) = path.file_stem() {
+                    index.by_assembly.entry(normalize(&stem.to_string_lossy())).or_insert(index.plugins.len());
+                }
+                index.plugins.push(PluginInfo { guid, name, version, dll: relative.clone(), package: package.clone() });
+            }
+        }
+        eprintln!("[bepinex_log] Indexed {} plugins in {:?}", index.plugins.len(), plugins_dir);
+        index
+    }
+
+    fn by_name(&self, name: &str) -> Option<&PluginInfo> {
+        self.plugins.iter().find(|p| p.name.eq_ignore_ascii_case(name) || p.guid.eq_ignore_ascii_case(name))
+            .or_else(|| {
+                let name = normalize(name);
+                self.plugins.iter().find(|p| normalize(&p.name) == name)
+            })
+    }
+
+    /// Plugin owning the first stack frame (`at Namespace.Type.Method`) that
+    /// matches a plugin assembly or name
+    fn by_stack(&self, message: &str) -> Option<&PluginInfo> {
+        message.lines()
+            .filter_map(|line| line.trim_start().strip_prefix("at "))
+            .filter_map(|frame| frame.split(['.', ':', '(']).next())
+            .find_map(|root| {
+                let root = normalize(root);
+                self.by_assembly.get(&root).map(|&i| &self.plugins[i])
+                    .or_else(|| self.plugins.iter().find(|p| normalize(&p.name) == root))
+            })
+    }
+}
+
+/// Turns log lines into entries; an entry is complete once the next one starts
+pub struct LogParser {
+    header: Regex,
+    chainloader: Regex,
+    pending: Option<LogEntry>,
+}
+
+impl Default for LogParser {
+    fn default() -> Self {
+        Self {
+            header: Regex::new(r"^\[(\w+)\s*:\s*(.*?)\s*\]\s?(.*)$").unwrap(),
+            chainloader: Regex::new(r"^(Loading|Error loading|Could not load) \[(.+?) [^ \]]+\]").unwrap(),
+            pending: None,
+        }
+    }
+}
+
+impl LogParser {
+    /// Feed one line; returns the entry it completed, if any
+    pub fn push_line(&mut self, line: &str, index: &PluginIndex) -> Option<LogEntry> {
+        let line = line.trim_end_matches('\r');
+        let Some(caps) = self.header.captures(line) else {
+            // Continuation of the previous entry (stack trace, multi-line message)
+            if let Some(entry) = &mut self.pending {
+                entry.message.push('\n');
+                entry.message.push_str(line);
+            }
+            return None;
+        };
+
+        let source = caps[2].to_string();
+        let message = caps[3].to_string();
+        let (kind, plugin) = match self.chainloader.captures(&message) {
+            Some(c) if &c[1] == "Loading" => (EntryKind::Loaded, Some(c[2].to_string())),
+            Some(c) => (EntryKind::LoadFailed, Some(c[2].to_string())),
+            None if CORE_SOURCES.iter().any(|s| s.eq_ignore_ascii_case(&source)) => (EntryKind::Message, None),
+            None => (EntryKind::Message, Some(source.clone())),
+        };
+        let next = LogEntry { level: LogLevel::parse(&caps[1]), source, message, kind, plugin, plugin_guid: None, package: None };
+        self.pending.replace(next).map(|entry| finish(entry, index))
+    }
+
+    /// The last entry, once no more lines are coming for now
+    pub fn flush(&mut self, index: &PluginIndex) -> Option<LogEntry> {
+        self.pending.take().map(|entry| finish(entry, index))
+    }
+}
+
+fn finish(mut entry: LogEntry, index: &PluginIndex) -> LogEntry {
+    let has_exception = entry.message.lines().next().map(|l| l.contains("Exception")).unwrap_or(false)
+        || entry.message.lines().skip(1).any(|l| l.trim_start().starts_with("at "));
+    if has_exception && entry.kind == EntryKind::Message {
+        entry.kind = EntryKind::Exception;
+    }
+
+    let plugin = match &entry.plugin {
+        Some(name) => index.by_name(name),
+        None if entry.kind == EntryKind::Exception => index.by_stack(&entry.message),
+        None => None,
+    };
+    if let Some(plugin) = plugin {
+        entry.plugin = Some(plugin.name.clone());
+        entry.plugin_guid = Some(plugin.guid.clone());
+        entry.package = plugin.package.clone();
+    }
+    entry
+}
+
+pub fn parse_log(text: &str, index: &PluginIndex) -> Vec<LogEntry> {
+    let mut parser = LogParser::default();
+    let mut entries: Vec<LogEntry> = text.lines().filter_map(|line| parser.push_line(line, index)).collect();
+    entries.extend(parser.flush(index));
+    entries
+}
+
+/// Errors of one plugin during a session
+#[derive(Debug, Serialize, Clone)]
+pub struct PluginReport {
+    pub plugin: String,
+    pub plugin_guid: Option<String>,
+    pub package: Option<String>,
+    pub errors: usize,
+    pub exceptions: usize,
+    pub warnings: usize,
+    pub load_failed: bool,
+    pub first_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SessionSummary {
+    pub log_path: String,
+    /// Unix time the log was last written
+    pub modified: u64,
+    pub plugins_loaded: usize,
+    /// Plugins with errors, exceptions or a failed load, worst first
+    pub reports: Vec<PluginReport>,
+    /// Errors that couldn't be tied to a plugin
+    pub unattributed_errors: usize,
+}
+
+pub fn summarize(entries: &[LogEntry]) -> (usize, Vec<PluginReport>, usize) {
+    let mut reports: Vec<PluginReport> = Vec::new();
+    let mut plugins_loaded = 0;
+    let mut unattributed = 0;
+
+    for entry in entries {
+        if entry.kind == EntryKind::Loaded {
+            plugins_loaded += 1;
+            continue;
+        }
+        let is_problem = entry.level.is_error() || entry.level == LogLevel::Warning
+            || matches!(entry.kind, EntryKind::Exception | EntryKind::LoadFailed);
+        if !is_problem {
+            continue;
+        }
+        let Some(plugin) = &entry.plugin else {
+            if entry.level.is_error() || entry.kind == EntryKind::Exception {
+                unattributed += 1;
+            }
+            continue;
+        };
+
+        let report = match reports.iter().position(|r| r.plugin == *plugin) {
+            Some(i) => &mut reports[i],
+            None => {
+                reports.push(PluginReport {
+                    plugin: plugin.clone(),
+                    plugin_guid: entry.plugin_guid.clone(),
+                    package: entry.package.clone(),
+                    errors: 0,
+                    exceptions: 0,
+                    warnings: 0,
+                    load_failed: false,
+                    first_error: None,
+                });
+                reports.last_mut().unwrap()
+            }
+        };
+        match entry.kind {
+            EntryKind::LoadFailed => report.load_failed = true,
+            EntryKind::Exception => report.exceptions += 1,
+            _ => {}
+        }
+        if entry.level.is_error() {
+            report.errors += 1;
+        } else if entry.level == LogLevel::Warning {
+            report.warnings += 1;
+        }
+        if report.first_error.is_none() && (entry.level.is_error() || entry.kind != EntryKind::Message) {
+            report.first_error = entry.message.lines().next().map(str::to_string);
+        }
+    }
+
+    reports.retain(|r| r.errors > 0 || r.exceptions > 0 || r.load_failed);
+    reports.sort_by_key(|r| std::cmp::Reverse((r.load_failed, r.errors + r.exceptions)));
+    (plugins_loaded, reports, unattributed)
+}
+
+/// Summary of the last session's log in `game_root`
+pub fn last_session(game_root: &Path, profile_dir: Option<&Path>) -> Result<SessionSummary, String> {
+    let log_path = game_root.join(LOG_FILE);
+    let text = fs::read(&log_path).map_err(|e| format!("No BepInEx log at {}: {}", log_path.display(), e))?;
+    let index = PluginIndex::build(game_root, profile_dir);
+    let entries = parse_log(&String::from_utf8_lossy(&text), &index);
+    let (plugins_loaded, reports, unattributed_errors) = summarize(&entries);
+    let modified = fs::metadata(&log_path).ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok(SessionSummary { log_path: log_path.to_string_lossy().to_string(), modified, plugins_loaded, reports, unattributed_errors })
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct GameLogEvent {
+    pub game_identifier: String,
+    pub entries: Vec<LogEntry>,
+}
+
+/// The first bytes of a file, empty if it can't be read
+fn read_head(path: &Path) -> Vec<u8> {
+    let mut head = Vec::with_capacity(HEAD_LEN);
+    if let Ok(file) = fs::File::open(path) {
+        let _ = file.take(HEAD_LEN as u64).read_to_end(&mut head);
+    }
+    head
+}
+
+/// Running log tails by game, managed as Tauri state
+#[derive(Default)]
+pub struct LogTailers {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl LogTailers {
+    /// Stop the game's tail; it reads what's left before it ends
+    pub fn stop(&self, game_identifier: &str) {
+        if let Some(flag) = self.running.lock().unwrap_or_else(|e| e.into_inner()).remove(game_identifier) {
+            flag.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Tail `game_root`'s log, emitting `game-log` events, until stopped.
+    /// Returns the stop flag.
+    pub fn start(&self, app: &AppHandle, game_identifier: &str, game_root: PathBuf, profile_dir: Option<PathBuf>) -> Arc<AtomicBool> {
+        self.stop(game_identifier);
+        let stop = Arc::new(AtomicBool::new(false));
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).insert(game_identifier.to_string(), stop.clone());
+
+        let app = app.clone();
+        let game_identifier = game_identifier.to_string();
+        let flag = stop.clone();
+        tokio::spawn(async move {
+            let index = PluginIndex::build(&game_root, profile_dir.as_deref());
+            let log_path = game_root.join(LOG_FILE);
+            // Whatever is there now belongs to the previous session
+            let mut offset = fs::metadata(&log_path).map(|m| m.len()).unwrap_or(0);
+            let mut head = read_head(&log_path);
+            let mut parser = LogParser::default();
+            // Bytes after the last newline; decoded only once the line is complete
+            // so multi-byte characters split across reads survive
+            let mut partial: Vec<u8> = Vec::new();
+            let mut idle_polls = 0;
+            eprintln!("[bepinex_log] Tailing {:?}", log_path);
+
+            loop {
+                let stopping = flag.load(Ordering::SeqCst);
+                let mut entries = Vec::new();
+                let len = fs::metadata(&log_path).map(|m| m.len()).unwrap_or(0);
+                // A new game start rewrites the log; it may already be longer
+                // than the old one, but its first line (with the start time) differs
+                let current = read_head(&log_path);
+                let shared = head.len().min(current.len());
+                if len < offset || current[..shared] != head[..shared] {
+                    offset = 0;
+                    partial.clear();
+                    idle_polls = 0;
+                    parser = LogParser::default();
+                    head = current;
+                } else if current.len() > head.len() {
+                    head = current;
+                }
+                if len > offset {
+                    if let Ok(mut file) = fs::File::open(&log_path) {
+                        let mut chunk = Vec::new();
+                        if file.seek(SeekFrom::Start(offset)).is_ok() && file.read_to_end(&mut chunk).is_ok() {
+                            offset += chunk.len() as u64;
+                            idle_polls = 0;
+                            partial.extend_from_slice(&chunk);
+                            while let Some(newline) = partial.iter().position(|b| *b == b'\n') {
+                                let line: Vec<u8> = partial.drain(..=newline).collect();
+                                entries.extend(parser.push_line(String::from_utf8_lossy(&line).trim_end_matches('\n'), &index));
+                            }
+                        }
+                    }
+                } else {
+                    idle_polls += 1;
+                    // Quiet for a while: the pending entry has all its lines (stack
+                    // traces can arrive a poll or two after their message)
+                    if idle_polls == IDLE_POLLS_BEFORE_FLUSH {
+                        entries.extend(parser.flush(&index));
+                    }
+                }
+                if stopping {
+                    if !partial.is_empty() {
+                        entries.extend(parser.push_line(&String::from_utf8_lossy(&partial), &index));
+                    }
+                    entries.extend(parser.flush(&index));
+                }
+                if !entries.is_empty() {
+                    let _ = app.emit("game-log", GameLogEvent { game_identifier: game_identifier.clone(), entries });
+                }
+                if stopping {
+                    break;
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            eprintln!("[bepinex_log] Stopped tailing {:?}", log_path);
+        });
+        stop
+    }
+}
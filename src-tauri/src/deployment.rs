@@ -0,0 +1,453 @@
+//! Which profile is deployed to each game, and which files in the game folder
+//! the manager put there.
+//!
+//! Syncing a profile to a game records the profile and its enabled mods in
+//! `deployments.json` (keyed by community identifier), so launching can check
+//! the game folder still holds what the profile expects.
+//!
+//! Every file and symlink the manager places in a game folder is also entered
+//! in that game's ledger (`ledgers/<game>.json`) with its hash, the package
+//! that placed it and the profile it was deployed for. Cleanup only ever
+//! deletes ledger entries that are still as they were placed; anything else
+//! found along the way is reported as foreign and left alone.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+use tauri::{AppHandle, Manager};
+
+use crate::{install_rules, mod_loader, profile_store::{self, Profile}, storage};
+
+const LEDGERS_DIR: &str = "ledgers";
+
+/// Installs run concurrently; read-modify-write of a ledger must not
+static LEDGER_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Deployment {
+    pub profile_id: String,
+    pub game_path: String,
+    pub deployed_at: u64,
+    /// Full names ("Author-Name-1.0.0") of the enabled mods, sorted
+    pub mods: Vec<String>,
+}
+
+fn deployments_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join("deployments.json"))
+}
+
+fn load_all(app: &AppHandle) -> HashMap<String, Deployment> {
+    deployments_path(app).ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+fn save_all(app: &AppHandle, deployments: &HashMap<String, Deployment>) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(deployments).map_err(|e| e.to_string())?;
+    storage::atomic_write(&deployments_path(app)?, data.as_bytes())
+        .map_err(|e| format!("Failed to save deployments: {}", e))
+}
+
+pub fn enabled_mods(profile: &Profile) -> Vec<String> {
+    let mut mods: Vec<String> = profile.mods.iter().filter(|m| m.enabled).map(|m| m.full_name.clone()).collect();
+    mods.sort();
+    mods
+}
+
+pub fn get(app: &AppHandle, game_identifier: &str) -> Option<Deployment> {
+    load_all(app).remove(game_identifier)
+}
+
+/// Record `profile` as deployed to the game at `game_path`, and what it
+/// placed there in the game's ledger
+pub fn record(app: &AppHandle, game_identifier: &str, profile: &Profile, game_path: &str) -> Result<(), String> {
+    let mut deployments = load_all(app);
+    deployments.insert(game_identifier.to_string(), Deployment {
+        profile_id: profile.id.clone(),
+        game_path: game_path.to_string(),
+        deployed_at: storage::unix_now(),
+        mods: enabled_mods(profile),
+    });
+    save_all(app, &deployments)?;
+    update_ledger(app, game_identifier, profile, Path::new(game_path))
+}
+
+/// Forget the deployment of `profile_id`, e.g. once it's removed from the game
+pub fn forget(app: &AppHandle, game_identifier: &str, profile_id: &str) -> Result<(), String> {
+    let mut deployments = load_all(app);
+    if deployments.get(game_identifier).map(|d| d.profile_id == profile_id) != Some(true) {
+        return Ok(());
+    }
+    deployments.remove(game_identifier);
+    save_all(app, &deployments)
+}
+
+/// Track a single mod toggled straight in the game folder
+pub fn set_mod_enabled(app: &AppHandle, game_identifier: &str, profile_id: &str, full_name: &str, enabled: bool) -> Result<(), String> {
+    let mut deployments = load_all(app);
+    let Some(deployment) = deployments.get_mut(game_identifier).filter(|d| d.profile_id == profile_id) else {
+        return Ok(());
+    };
+    deployment.mods.retain(|m| m != full_name);
+    if enabled {
+        deployment.mods.push(full_name.to_string());
+        deployment.mods.sort();
+    }
+    save_all(app, &deployments)
+}
+
+/// Why the game folder doesn't hold `profile` as it is now, if it doesn't
+pub fn check(app: &AppHandle, game_identifier: &str, profile: &Profile) -> Result<(), String> {
+    let Some(deployment) = get(app, game_identifier) else {
+        return Err(format!("Profile '{}' has not been applied to the game yet", profile.name));
+    };
+    if deployment.profile_id != profile.id {
+        return Err(format!("Another profile is applied to the game. Apply '{}' first", profile.name));
+    }
+
+    let current = enabled_mods(profile);
+    let missing = current.iter().filter(|m| !deployment.mods.contains(m)).count();
+    let extra = deployment.mods.iter().filter(|m| !current.contains(m)).count();
+    if missing > 0 || extra > 0 {
+        return Err(format!(
+            "Profile '{}' changed since it was applied ({} to install, {} to remove). Apply it to the game first",
+            profile.name, missing, extra
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    File,
+    Symlink,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    pub kind: EntryKind,
+    /// SHA-256 (hex) of a file's contents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Size and modification time (unix seconds) when hashed, to skip rehashing unchanged files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<u64>,
+    /// Where a symlink points
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Full name of the package that placed it; None for profile configs and
+    /// other files copied from the profile
+    pub package: Option<String>,
+    pub profile_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Ledger {
+    pub game_path: String,
+    pub updated_at: u64,
+    /// Path relative to the game folder (forward slashes) -> entry
+    pub entries: BTreeMap<String, LedgerEntry>,
+}
+
+impl Ledger {
+    /// Whether `relative` is an entry or lies inside a symlinked entry
+    fn owns(&self, relative: &str) -> bool {
+        let mut prefix = relative;
+        loop {
+            match self.entries.get(prefix) {
+                Some(entry) if prefix == relative || entry.kind == EntryKind::Symlink => return true,
+                _ => {}
+            }
+            match prefix.rsplit_once('/') {
+                Some((parent, _)) => prefix = parent,
+                None => return false,
+            }
+        }
+    }
+}
+
+/// What a cleanup did in a game folder
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct CleanupReport {
+    /// Ledger entries deleted
+    pub removed: Vec<String>,
+    /// Ledger entries changed since they were placed, left alone
+    pub modified: Vec<String>,
+    /// Files the manager didn't place, left alone
+    pub foreign: Vec<String>,
+}
+
+impl CleanupReport {
+    pub fn merge(&mut self, other: CleanupReport) {
+        self.removed.extend(other.removed);
+        self.modified.extend(other.modified);
+        self.foreign.extend(other.foreign);
+    }
+}
+
+fn ledger_path(app: &AppHandle, game_identifier: &str) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join(LEDGERS_DIR).join(format!("{}.json", game_identifier)))
+}
+
+/// The game's ledger, empty if it was written for another game folder
+pub fn load_ledger(app: &AppHandle, game_identifier: &str, game_root: &Path) -> Ledger {
+    let game_path = game_root.to_string_lossy().to_string();
+    let Some(data) = ledger_path(app, game_identifier).ok().and_then(|path| fs::read_to_string(path).ok()) else {
+        return adopt_existing(app, game_identifier, game_root);
+    };
+    serde_json::from_str::<Ledger>(&data).ok()
+        .filter(|ledger| ledger.game_path == game_path)
+        .unwrap_or(Ledger { game_path, ..Default::default() })
+}
+
+/// Ledger for a game deployed before ledgers were kept: what the deployed
+/// profile's install records and cache say the manager placed
+fn adopt_existing(app: &AppHandle, game_identifier: &str, game_root: &Path) -> Ledger {
+    let mut ledger = Ledger { game_path: game_root.to_string_lossy().to_string(), ..Default::default() };
+    let Some(deployed) = get(app, game_identifier).filter(|d| Path::new(&d.game_path) == game_root) else { return ledger };
+    let Ok((placed, owners)) = placed_by(app, game_identifier, &deployed.profile_id, None) else { return ledger };
+    for relative in &placed {
+        enter(&mut ledger, &BTreeMap::new(), game_root, relative, owners.get(relative).map(String::as_str), &deployed.profile_id);
+    }
+    eprintln!("[deployment] Adopted {} existing entries of profile {} into the ledger of {}", ledger.entries.len(), deployed.profile_id, game_identifier);
+    ledger
+}
+
+fn save_ledger(app: &AppHandle, game_identifier: &str, ledger: &mut Ledger) -> Result<(), String> {
+    ledger.updated_at = storage::unix_now();
+    let data = serde_json::to_string_pretty(ledger).map_err(|e| e.to_string())?;
+    storage::atomic_write(&ledger_path(app, game_identifier)?, data.as_bytes())
+        .map_err(|e| format!("Failed to save deployment ledger: {}", e))
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn modified_secs(metadata: &fs::Metadata) -> Option<u64> {
+    metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+/// The entry for what is at `relative` now; None for folders and missing paths.
+/// `known` is reused instead of rehashing a file whose size and time match.
+fn observe(game_root: &Path, relative: &str, package: Option<&str>, profile_id: &str, known: Option<&LedgerEntry>) -> Option<LedgerEntry> {
+    let path = game_root.join(relative);
+    let metadata = fs::symlink_metadata(&path).ok()?;
+    let mut entry = LedgerEntry {
+        kind: EntryKind::File,
+        sha256: None,
+        size: None,
+        modified: None,
+        target: None,
+        package: package.map(str::to_string),
+        profile_id: profile_id.to_string(),
+    };
+    if metadata.file_type().is_symlink() {
+        entry.kind = EntryKind::Symlink;
+        entry.target = Some(fs::read_link(&path).ok()?.to_string_lossy().to_string());
+    } else if metadata.is_file() {
+        entry.size = Some(metadata.len());
+        entry.modified = modified_secs(&metadata);
+        entry.sha256 = match known {
+            Some(k) if k.kind == EntryKind::File && k.size == entry.size && k.modified == entry.modified && entry.modified.is_some() => k.sha256.clone(),
+            _ => Some(sha256_file(&path).ok()?),
+        };
+    } else {
+        return None;
+    }
+    Some(entry)
+}
+
+/// Whether an entry is still exactly what was placed. `quick` trusts the
+/// recorded hash of a file whose size and time didn't change.
+fn is_intact(game_root: &Path, relative: &str, entry: &LedgerEntry, quick: bool) -> bool {
+    let Some(current) = observe(game_root, relative, None, "", quick.then_some(entry)) else { return false };
+    current.kind == entry.kind && current.sha256 == entry.sha256 && current.target == entry.target
+}
+
+/// `relative`, or the ancestor of it that is a symlink
+fn placed_path(game_root: &Path, relative: &str) -> String {
+    let mut prefix = String::new();
+    for part in relative.split('/') {
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix.push_str(part);
+        let is_link = fs::symlink_metadata(game_root.join(&prefix)).map(|m| m.file_type().is_symlink()).unwrap_or(false);
+        if is_link {
+            return prefix;
+        }
+    }
+    prefix
+}
+
+fn enter(ledger: &mut Ledger, previous: &BTreeMap<String, LedgerEntry>, game_root: &Path, relative: &str, package: Option<&str>, profile_id: &str) {
+    let relative = placed_path(game_root, relative);
+    if let Some(entry) = observe(game_root, &relative, package, profile_id, previous.get(&relative)) {
+        ledger.entries.insert(relative, entry);
+    }
+}
+
+/// Record files `package` just placed in the game folder for `profile_id`
+pub fn claim<'a>(app: &AppHandle, game_identifier: &str, profile_id: &str, game_root: &Path, package: Option<&str>, paths: impl IntoIterator<Item = &'a str>) -> Result<(), String> {
+    let _guard = LEDGER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ledger = load_ledger(app, game_identifier, game_root);
+    let previous = ledger.entries.clone();
+    for relative in paths {
+        enter(&mut ledger, &previous, game_root, relative, package, profile_id);
+    }
+    save_ledger(app, game_identifier, &mut ledger)
+}
+
+/// Drop the entries of files that were moved out of the game folder
+pub fn disown<'a>(app: &AppHandle, game_identifier: &str, game_root: &Path, paths: impl IntoIterator<Item = &'a str>) -> Result<(), String> {
+    let _guard = LEDGER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ledger = load_ledger(app, game_identifier, game_root);
+    for relative in paths {
+        ledger.entries.remove(relative);
+    }
+    save_ledger(app, game_identifier, &mut ledger)
+}
+
+/// Put back a ledger from `load_ledger`, e.g. when the files were put back
+pub fn restore_ledger(app: &AppHandle, game_identifier: &str, mut ledger: Ledger) -> Result<(), String> {
+    let _guard = LEDGER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    save_ledger(app, game_identifier, &mut ledger)
+}
+
+/// Relative paths (forward slashes) of the files under `root/dir`
+fn files_under(root: &Path, dir: &str) -> Vec<String> {
+    walkdir::WalkDir::new(root.join(dir))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| !e.file_type().is_dir())
+        .filter_map(|e| Some(e.path().strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/")))
+        .collect()
+}
+
+/// What a profile places in the game folder: the files of its install
+/// records (of the `only` mods if given) and what its cached loader folders,
+/// root files and configs hold. Also returns the owning package of each file
+/// that has one.
+fn placed_by(app: &AppHandle, game_identifier: &str, profile_id: &str, only: Option<&[String]>) -> Result<(Vec<String>, HashMap<String, String>), String> {
+    let profile_dir = profile_store::profile_dir(app, profile_id)?;
+    let loader = mod_loader::for_game(game_identifier);
+
+    let mut owners: HashMap<String, String> = HashMap::new();
+    for record in install_rules::list_records(&profile_dir).into_iter().filter(|r| only.map(|o| o.contains(&r.package)).unwrap_or(true)) {
+        for file in record.files {
+            owners.insert(file.path, record.package.clone());
+        }
+    }
+    let mut placed: Vec<String> = owners.keys().cloned().collect();
+    for dir in loader.managed_dirs() {
+        placed.extend(files_under(&profile_dir, dir));
+    }
+    placed.extend(loader.root_files().iter().filter(|f| profile_dir.join(f).is_file()).map(|f| f.to_string()));
+    Ok((placed, owners))
+}
+
+/// Enter everything `profile` places in the game folder for its enabled mods.
+/// Earlier entries that are still intact are kept so a later cleanup can
+/// remove them.
+fn update_ledger(app: &AppHandle, game_identifier: &str, profile: &Profile, game_root: &Path) -> Result<(), String> {
+    let (placed, owners) = placed_by(app, game_identifier, &profile.id, Some(&enabled_mods(profile)))?;
+
+    let _guard = LEDGER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ledger = load_ledger(app, game_identifier, game_root);
+    let previous = std::mem::take(&mut ledger.entries);
+    ledger.entries = previous.iter()
+        .filter(|(relative, entry)| is_intact(game_root, relative, entry, true))
+        .map(|(relative, entry)| (relative.clone(), entry.clone()))
+        .collect();
+    for relative in &placed {
+        enter(&mut ledger, &previous, game_root, relative, owners.get(relative).map(String::as_str), &profile.id);
+    }
+    eprintln!("[deployment] Ledger of {} holds {} entries", game_identifier, ledger.entries.len());
+    save_ledger(app, game_identifier, &mut ledger)
+}
+
+/// Remove now-empty folders from `dir` up, staying inside `scope`
+fn prune_empty_dirs(game_root: &Path, scope: &Path, dir: Option<&Path>) {
+    let mut dir = dir;
+    while let Some(d) = dir {
+        if d == game_root || !d.starts_with(scope) || fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+}
+
+fn remove_entries(app: &AppHandle, game_identifier: &str, game_root: &Path, scope: &str, select: impl Fn(&str, &LedgerEntry) -> bool) -> Result<(Ledger, CleanupReport), String> {
+    let _guard = LEDGER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ledger = load_ledger(app, game_identifier, game_root);
+    let mut report = CleanupReport::default();
+    let scope_dir = game_root.join(scope);
+
+    let selected: Vec<String> = ledger.entries.iter()
+        .filter(|(relative, entry)| select(relative, entry))
+        .map(|(relative, _)| relative.clone())
+        .collect();
+    for relative in selected {
+        let Some(entry) = ledger.entries.remove(&relative) else { continue };
+        let path = game_root.join(&relative);
+        if fs::symlink_metadata(&path).is_err() {
+            continue;
+        }
+        if !is_intact(game_root, &relative, &entry, false) {
+            eprintln!("[deployment] Leaving {}: changed since it was placed", relative);
+            report.modified.push(relative);
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                prune_empty_dirs(game_root, &scope_dir, path.parent());
+                report.removed.push(relative);
+            }
+            Err(e) => eprintln!("[deployment] Failed to remove {}: {}", relative, e),
+        }
+    }
+    save_ledger(app, game_identifier, &mut ledger)?;
+    Ok((ledger, report))
+}
+
+/// Delete the ledger entries `select` picks wherever they are
+pub fn remove_owned(app: &AppHandle, game_identifier: &str, game_root: &Path, select: impl Fn(&LedgerEntry) -> bool) -> Result<CleanupReport, String> {
+    remove_entries(app, game_identifier, game_root, "", |_, entry| select(entry)).map(|(_, report)| report)
+}
+
+/// Delete the ledger entries at or under `scope` (relative to the game folder)
+/// that `select` picks, and report the files under it the manager didn't place
+pub fn clean(app: &AppHandle, game_identifier: &str, game_root: &Path, scope: &str, select: impl Fn(&LedgerEntry) -> bool) -> Result<CleanupReport, String> {
+    let under = |relative: &str| scope.is_empty() || relative == scope || relative.strip_prefix(scope).map(|rest| rest.starts_with('/')).unwrap_or(false);
+    let (ledger, mut report) = remove_entries(app, game_identifier, game_root, scope, |relative, entry| under(relative) && select(entry))?;
+
+    let scope_dir = game_root.join(scope);
+    let remaining: Vec<String> = if fs::symlink_metadata(&scope_dir).map(|m| m.is_dir()).unwrap_or(false) {
+        files_under(game_root, scope)
+    } else if fs::symlink_metadata(&scope_dir).is_ok() {
+        vec![scope.to_string()]
+    } else {
+        Vec::new()
+    };
+    report.foreign = remaining.into_iter().filter(|relative| !ledger.owns(relative)).collect();
+    if !report.foreign.is_empty() {
+        eprintln!("[deployment] Left {} files in {} the manager didn't place", report.foreign.len(), scope);
+    }
+    Ok(report)
+}
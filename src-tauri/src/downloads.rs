@@ -0,0 +1,450 @@
+//! Download queue for installing many mods at once.
+//!
+//! A batch runs its items with bounded concurrency. Archives are served from
+//! the shared archive cache when possible, otherwise streamed from
+//! Thunderstore with retries on transient failures (stalled connections time
+//! out and count as such). Progress is reported per item and for the whole
+//! batch through `download-progress` events, and the frontend can cancel a
+//! single item or the whole batch by id, even mid-stall.
+
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc, Mutex, OnceLock},
+    time::Duration,
+};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{Notify, Semaphore};
+
+use crate::archive_cache::{self, ArchiveCache};
+
+pub const DEFAULT_CONCURRENCY: usize = 4;
+const MAX_ATTEMPTS: u32 = 4;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Don't flood the frontend with an event per network chunk
+const PROGRESS_STEP: u64 = 256 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// A connection that sends nothing for this long is treated as dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadItem {
+    /// "Author-Name-Version", also the archive cache key
+    pub mod_name: String,
+    pub download_url: String,
+    /// Archive size reported by Thunderstore
+    #[serde(default)]
+    pub expected_size: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemState {
+    Queued,
+    Downloading,
+    Retrying,
+    Installing,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DownloadProgress {
+    pub batch_id: String,
+    pub mod_name: String,
+    pub state: ItemState,
+    pub downloaded: u64,
+    pub total: u64,
+    pub overall_downloaded: u64,
+    pub overall_total: u64,
+    pub attempt: u32,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ItemResult {
+    pub mod_name: String,
+    pub state: ItemState,
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum DownloadError {
+    Cancelled,
+    /// Worth retrying (connection errors, timeouts, 5xx, 429)
+    Transient(String),
+    Failed(String),
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadError::Cancelled => write!(f, "Cancelled"),
+            DownloadError::Transient(e) | DownloadError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+struct Batch {
+    cancelled: Arc<AtomicBool>,
+    items: HashMap<String, Arc<AtomicBool>>,
+    /// Wakes the batch's tasks when it or one of its items is cancelled
+    notify: Arc<Notify>,
+}
+
+/// Cancellation handles of the running batches, managed as Tauri state
+#[derive(Default)]
+pub struct DownloadManager {
+    batches: Mutex<HashMap<String, Batch>>,
+}
+
+impl DownloadManager {
+    fn register(&self, batch_id: &str, items: &[DownloadItem]) -> Result<(Arc<AtomicBool>, Arc<Notify>), String> {
+        let mut batches = self.batches.lock().unwrap_or_else(|e| e.into_inner());
+        if batches.contains_key(batch_id) {
+            return Err(format!("Batch {} is already running", batch_id));
+        }
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let notify = Arc::new(Notify::new());
+        batches.insert(batch_id.to_string(), Batch {
+            cancelled: cancelled.clone(),
+            items: items.iter().map(|i| (i.mod_name.clone(), Arc::new(AtomicBool::new(false)))).collect(),
+            notify: notify.clone(),
+        });
+        Ok((cancelled, notify))
+    }
+
+    fn item_flag(&self, batch_id: &str, mod_name: &str) -> Arc<AtomicBool> {
+        let batches = self.batches.lock().unwrap_or_else(|e| e.into_inner());
+        batches.get(batch_id)
+            .and_then(|b| b.items.get(mod_name).cloned())
+            .unwrap_or_default()
+    }
+
+    fn finish(&self, batch_id: &str) {
+        self.batches.lock().unwrap_or_else(|e| e.into_inner()).remove(batch_id);
+    }
+
+    /// Cancel one item of a batch, or the whole batch when `mod_name` is None.
+    /// Returns false if there is nothing running to cancel.
+    pub fn cancel(&self, batch_id: &str, mod_name: Option<&str>) -> bool {
+        let batches = self.batches.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(batch) = batches.get(batch_id) else { return false };
+        let cancelled = match mod_name {
+            Some(name) => match batch.items.get(name) {
+                Some(flag) => {
+                    flag.store(true, Ordering::SeqCst);
+                    true
+                }
+                None => false,
+            },
+            None => {
+                batch.cancelled.store(true, Ordering::SeqCst);
+                true
+            }
+        };
+        if cancelled {
+            batch.notify.notify_waiters();
+        }
+        cancelled
+    }
+}
+
+/// Per-item view of a batch: emits progress and answers "was I cancelled?"
+pub struct ItemProgress {
+    app: AppHandle,
+    batch_id: String,
+    mod_name: String,
+    batch_cancelled: Arc<AtomicBool>,
+    item_cancelled: Arc<AtomicBool>,
+    cancel_notify: Arc<Notify>,
+    overall_downloaded: Arc<AtomicU64>,
+    overall_total: Arc<AtomicU64>,
+    downloaded: AtomicU64,
+    total: AtomicU64,
+}
+
+impl ItemProgress {
+    fn is_cancelled(&self) -> bool {
+        self.batch_cancelled.load(Ordering::SeqCst) || self.item_cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once this item or its batch is cancelled
+    async fn cancelled(&self) {
+        loop {
+            // Created before the check so a cancel in between still wakes it
+            let notified = self.cancel_notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    fn emit(&self, state: ItemState, attempt: u32, error: Option<String>) {
+        let _ = self.app.emit("download-progress", DownloadProgress {
+            batch_id: self.batch_id.clone(),
+            mod_name: self.mod_name.clone(),
+            state,
+            downloaded: self.downloaded.load(Ordering::SeqCst),
+            total: self.total.load(Ordering::SeqCst),
+            overall_downloaded: self.overall_downloaded.load(Ordering::SeqCst),
+            overall_total: self.overall_total.load(Ordering::SeqCst),
+            attempt,
+            error,
+        });
+    }
+
+    /// Replace this item's expected size in the batch total once the server tells us
+    fn set_total(&self, total: u64) {
+        let previous = self.total.swap(total, Ordering::SeqCst);
+        self.overall_total.fetch_add(total, Ordering::SeqCst);
+        self.overall_total.fetch_sub(previous, Ordering::SeqCst);
+    }
+
+    fn add_downloaded(&self, bytes: u64) {
+        self.downloaded.fetch_add(bytes, Ordering::SeqCst);
+        self.overall_downloaded.fetch_add(bytes, Ordering::SeqCst);
+    }
+
+    /// Forget a failed attempt's bytes before retrying
+    fn reset_downloaded(&self) {
+        let previous = self.downloaded.swap(0, Ordering::SeqCst);
+        self.overall_downloaded.fetch_sub(previous, Ordering::SeqCst);
+    }
+}
+
+/// A fetched archive on disk, pinned in the cache while held. Downloads that
+/// couldn't be cached are deleted on drop.
+pub struct FetchedArchive {
+    path: PathBuf,
+    temporary: bool,
+    _pin: archive_cache::Pin,
+}
+
+impl FetchedArchive {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for FetchedArchive {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// Run `future` unless `progress` is cancelled first
+async fn or_cancelled<T>(progress: Option<&ItemProgress>, future: impl std::future::Future<Output = T>) -> Result<T, DownloadError> {
+    match progress {
+        Some(progress) => tokio::select! {
+            output = future => Ok(output),
+            _ = progress.cancelled() => Err(DownloadError::Cancelled),
+        },
+        None => Ok(future.await),
+    }
+}
+
+/// Stream `item` into `dest`, returning the number of bytes written
+async fn download_once(item: &DownloadItem, dest: &Path, progress: Option<&ItemProgress>) -> Result<u64, DownloadError> {
+    let response = or_cancelled(progress, tokio::time::timeout(READ_TIMEOUT, client().get(&item.download_url).send())).await?
+        .map_err(|_| DownloadError::Transient(format!("Download of {} timed out waiting for the server", item.mod_name)))?
+        .map_err(|e| DownloadError::Transient(e.to_string()))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let message = format!("Download of {} failed: HTTP {}", item.mod_name, status);
+        return Err(if status.is_server_error() || status.as_u16() == 429 {
+            DownloadError::Transient(message)
+        } else {
+            DownloadError::Failed(message)
+        });
+    }
+
+    let total = response.content_length().or(item.expected_size).unwrap_or(0);
+    if let Some(progress) = progress {
+        progress.set_total(total);
+        progress.emit(ItemState::Downloading, 0, None);
+    }
+
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| DownloadError::Failed(e.to_string()))?;
+    }
+    let file = std::fs::File::create(dest).map_err(|e| DownloadError::Failed(e.to_string()))?;
+    let mut writer = BufWriter::new(file);
+
+    let mut written = 0u64;
+    let mut since_emit = 0u64;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = or_cancelled(progress, tokio::time::timeout(READ_TIMEOUT, stream.next())).await?
+        .map_err(|_| DownloadError::Transient(format!("Download of {} stalled", item.mod_name)))?
+    {
+        let chunk = chunk.map_err(|e| DownloadError::Transient(e.to_string()))?;
+        writer.write_all(&chunk).map_err(|e| DownloadError::Failed(e.to_string()))?;
+        written += chunk.len() as u64;
+
+        if let Some(progress) = progress {
+            if progress.is_cancelled() {
+                return Err(DownloadError::Cancelled);
+            }
+            progress.add_downloaded(chunk.len() as u64);
+            since_emit += chunk.len() as u64;
+            if since_emit >= PROGRESS_STEP {
+                since_emit = 0;
+                progress.emit(ItemState::Downloading, 0, None);
+            }
+        }
+    }
+    writer.flush().map_err(|e| DownloadError::Failed(e.to_string()))?;
+    Ok(written)
+}
+
+/// Archive for `item`, from the shared cache or downloaded with retries
+pub async fn fetch_archive(app: &AppHandle, item: &DownloadItem, progress: Option<&ItemProgress>) -> Result<FetchedArchive, DownloadError> {
+    let cache = ArchiveCache::open(app).map_err(DownloadError::Failed)?;
+    // Pinned before the lookup so another task's prune can't remove it until installed
+    let pin = archive_cache::pin(&item.mod_name);
+    if let Some(cached) = cache.get(&item.mod_name, item.expected_size) {
+        eprintln!("[downloads] Using cached archive: {:?}", cached);
+        if let Some(progress) = progress {
+            let size = std::fs::metadata(&cached).map(|m| m.len()).unwrap_or(0);
+            progress.set_total(size);
+            progress.add_downloaded(size);
+        }
+        return Ok(FetchedArchive { path: cached, temporary: false, _pin: pin });
+    }
+
+    // Removes the partial file on every early return below
+    let mut partial = FetchedArchive { path: cache.partial_path(&item.mod_name), temporary: true, _pin: pin };
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match download_once(item, partial.path(), progress).await {
+            Ok(_) => break,
+            Err(DownloadError::Transient(e)) if attempt < MAX_ATTEMPTS => {
+                eprintln!("[downloads] {} attempt {} failed: {}, retrying in {:?}", item.mod_name, attempt, e, backoff);
+                if let Some(progress) = progress {
+                    progress.reset_downloaded();
+                    progress.emit(ItemState::Retrying, attempt, Some(e));
+                }
+                or_cancelled(progress, tokio::time::sleep(backoff)).await?;
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    match cache.insert_file(&item.mod_name, partial.path(), item.expected_size).map_err(DownloadError::Failed)? {
+        Some(cached) => {
+            partial.path = cached;
+            partial.temporary = false;
+            if let Some(quota_mb) = crate::load_settings_impl(app).archive_cache_quota_mb {
+                cache.prune(None, Some(quota_mb * 1024 * 1024));
+            }
+            Ok(partial)
+        }
+        None => Ok(partial),
+    }
+}
+
+/// Download and install `items`, at most `concurrency` at a time. `install`
+/// runs on a blocking thread with the archive file of each item.
+pub async fn run_batch<F>(
+    app: AppHandle,
+    manager: &DownloadManager,
+    batch_id: String,
+    items: Vec<DownloadItem>,
+    concurrency: usize,
+    install: F,
+) -> Result<Vec<ItemResult>, String>
+where
+    F: Fn(&DownloadItem, &Path) -> Result<(), String> + Send + Sync + 'static,
+{
+    let (batch_cancelled, cancel_notify) = manager.register(&batch_id, &items)?;
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let install = Arc::new(install);
+    let overall_downloaded = Arc::new(AtomicU64::new(0));
+    let overall_total = Arc::new(AtomicU64::new(items.iter().filter_map(|i| i.expected_size).sum()));
+
+    let mut tasks = Vec::new();
+    for item in items {
+        let progress = ItemProgress {
+            app: app.clone(),
+            batch_id: batch_id.clone(),
+            mod_name: item.mod_name.clone(),
+            batch_cancelled: batch_cancelled.clone(),
+            item_cancelled: manager.item_flag(&batch_id, &item.mod_name),
+            cancel_notify: cancel_notify.clone(),
+            overall_downloaded: overall_downloaded.clone(),
+            overall_total: overall_total.clone(),
+            downloaded: AtomicU64::new(0),
+            total: AtomicU64::new(item.expected_size.unwrap_or(0)),
+        };
+        progress.emit(ItemState::Queued, 0, None);
+
+        let semaphore = semaphore.clone();
+        let install = install.clone();
+        let app = app.clone();
+        let mod_name = item.mod_name.clone();
+        tasks.push((mod_name, tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let result = async {
+                if progress.is_cancelled() {
+                    return Err(DownloadError::Cancelled);
+                }
+                let archive = fetch_archive(&app, &item, Some(&progress)).await?;
+                if progress.is_cancelled() {
+                    return Err(DownloadError::Cancelled);
+                }
+
+                progress.emit(ItemState::Installing, 0, None);
+                let install = install.clone();
+                let install_item = item.clone();
+                tokio::task::spawn_blocking(move || install(&install_item, archive.path()))
+                    .await
+                    .map_err(|e| DownloadError::Failed(e.to_string()))?
+                    .map_err(DownloadError::Failed)
+            }.await;
+
+            let (state, error) = match result {
+                Ok(()) => (ItemState::Done, None),
+                Err(DownloadError::Cancelled) => (ItemState::Cancelled, None),
+                Err(e) => (ItemState::Failed, Some(e.to_string())),
+            };
+            progress.emit(state, 0, error.clone());
+            ItemResult { mod_name: item.mod_name, state, error }
+        })));
+    }
+
+    let mut results = Vec::new();
+    for (mod_name, task) in tasks {
+        match task.await {
+            Ok(result) => results.push(result),
+            Err(e) => {
+                eprintln!("[downloads] Task for {} panicked: {}", mod_name, e);
+                results.push(ItemResult { mod_name, state: ItemState::Failed, error: Some(format!("Install task failed: {}", e)) });
+            }
+        }
+    }
+    manager.finish(&batch_id);
+    Ok(results)
+}
@@ -0,0 +1,192 @@
+//! Single-pass extraction of mod archives.
+//!
+//! Archives are read from disk, never buffered whole. Everything that only
+//! needs the entry names (like spotting a BepInExPack) is answered from the
+//! zip central directory without decompressing anything.
+//!
+//! All package archives go through `extract`, which refuses the whole archive
+//! before writing anything if an entry is a symlink or would land outside the
+//! target (absolute paths, `..`, drive prefixes), and caps the file count and
+//! total uncompressed size.
+
+use std::{
+    fmt, fs,
+    io::{BufReader, Read, Seek},
+    path::{Component, Path, PathBuf},
+};
+use zip::ZipArchive;
+
+/// Largest total uncompressed size accepted for one archive
+pub const MAX_TOTAL_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+/// Most entries accepted in one archive
+pub const MAX_FILE_COUNT: usize = 50_000;
+
+const S_IFMT: u32 = 0o170000;
+const S_IFLNK: u32 = 0o120000;
+
+#[derive(Debug)]
+pub enum ExtractError {
+    /// Entry name is absolute, contains `..` or otherwise escapes the target
+    UnsafePath { entry: String, reason: &'static str },
+    Symlink { entry: String },
+    TooManyFiles { count: usize, limit: usize },
+    /// Total uncompressed size crossed the limit at `entry`
+    TooLarge { entry: String, limit: u64 },
+    Io { entry: String, message: String },
+    Archive(String),
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::UnsafePath { entry, reason } => write!(f, "Refusing archive entry '{}': {}", entry, reason),
+            ExtractError::Symlink { entry } => write!(f, "Refusing archive entry '{}': symlinks are not allowed", entry),
+            ExtractError::TooManyFiles { count, limit } => write!(f, "Archive has {} entries, the limit is {}", count, limit),
+            ExtractError::TooLarge { entry, limit } => write!(f, "Archive exceeds {} MB uncompressed at entry '{}'", limit / 1024 / 1024, entry),
+            ExtractError::Io { entry, message } => write!(f, "Failed to extract '{}': {}", entry, message),
+            ExtractError::Archive(message) => write!(f, "Invalid archive: {}", message),
+        }
+    }
+}
+
+impl From<ExtractError> for String {
+    fn from(e: ExtractError) -> Self {
+        e.to_string()
+    }
+}
+
+pub fn open_archive(path: &Path) -> Result<ZipArchive<BufReader<fs::File>>, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    ZipArchive::new(BufReader::new(file)).map_err(|e| format!("Invalid archive {:?}: {}", path, e))
+}
+
+/// Relative path for an entry name, or why it can't be extracted safely.
+/// Both `/` and `\` count as separators so Windows-made archives can't sneak
+/// `..\` past a unix check (or the other way round).
+fn sanitize_entry_name(name: &str) -> Result<PathBuf, &'static str> {
+    if name.contains('\0') {
+        return Err("contains a NUL byte");
+    }
+    if name.starts_with('/') || name.starts_with('\\') {
+        return Err("absolute path");
+    }
+    let bytes = name.as_bytes();
+    if bytes.len() >= 2 && bytes[1] == b':' && bytes[0].is_ascii_alphabetic() {
+        return Err("drive-qualified path");
+    }
+
+    let mut path = PathBuf::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => continue,
+            ".." => return Err("parent directory component"),
+            _ => path.push(part),
+        }
+    }
+    // Belt and braces: nothing but plain names may remain
+    if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err("escapes the target directory");
+    }
+    Ok(path)
+}
+
+/// Mapping for `extract` that keeps only entries under `prefix`, with the prefix stripped
+pub fn strip_prefix(prefix: &str) -> impl Fn(&Path) -> Option<PathBuf> + '_ {
+    move |path| match path.strip_prefix(prefix.trim_end_matches('/')) {
+        Ok(rest) if !rest.as_os_str().is_empty() => Some(rest.to_path_buf()),
+        _ => None,
+    }
+}
+
+/// A file written by `extract`
+#[derive(Debug, Clone)]
+pub struct ExtractedFile {
+    /// Sanitized path inside the archive
+    pub entry: PathBuf,
+    /// Where it was written, relative to the destination
+    pub target: PathBuf,
+}
+
+/// Check every entry from the central directory before anything is written
+fn validate<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<(), ExtractError> {
+    if archive.len() > MAX_FILE_COUNT {
+        return Err(ExtractError::TooManyFiles { count: archive.len(), limit: MAX_FILE_COUNT });
+    }
+
+    let mut total: u64 = 0;
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i).map_err(|e| ExtractError::Archive(e.to_string()))?;
+        let entry = file.name().to_string();
+
+        sanitize_entry_name(&entry).map_err(|reason| ExtractError::UnsafePath { entry: entry.clone(), reason })?;
+        if file.unix_mode().map(|mode| mode & S_IFMT == S_IFLNK).unwrap_or(false) {
+            return Err(ExtractError::Symlink { entry });
+        }
+        total = total.saturating_add(file.size());
+        if total > MAX_TOTAL_SIZE {
+            return Err(ExtractError::TooLarge { entry, limit: MAX_TOTAL_SIZE });
+        }
+    }
+    Ok(())
+}
+
+/// Extract the archive into `dest`. `map` gives each entry's path relative to
+/// `dest` (None skips the entry); it only ever sees sanitized entry paths.
+pub fn extract<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    dest: &Path,
+    map: impl Fn(&Path) -> Option<PathBuf>,
+) -> Result<Vec<ExtractedFile>, ExtractError> {
+    validate(archive)?;
+
+    let io_error = |entry: &str, e: std::io::Error| ExtractError::Io { entry: entry.to_string(), message: e.to_string() };
+    fs::create_dir_all(dest).map_err(|e| io_error("", e))?;
+
+    let mut written = Vec::new();
+    // Declared sizes can lie, so count what is actually decompressed too
+    let mut remaining = MAX_TOTAL_SIZE;
+    for i in 0..archive.len() {
+        let file = archive.by_index(i).map_err(|e| ExtractError::Archive(e.to_string()))?;
+        let entry = file.name().to_string();
+        let path = sanitize_entry_name(&entry).map_err(|reason| ExtractError::UnsafePath { entry: entry.clone(), reason })?;
+        if path.as_os_str().is_empty() {
+            continue;
+        }
+        let Some(relative) = map(&path) else { continue };
+        let outpath = dest.join(&relative);
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) || !outpath.starts_with(dest) {
+            return Err(ExtractError::UnsafePath { entry, reason: "escapes the target directory" });
+        }
+
+        if file.is_dir() {
+            fs::create_dir_all(&outpath).map_err(|e| io_error(&entry, e))?;
+            continue;
+        }
+        if let Some(parent) = outpath.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error(&entry, e))?;
+        }
+        let mut outfile = fs::File::create(&outpath).map_err(|e| io_error(&entry, e))?;
+        let copied = std::io::copy(&mut file.take(remaining + 1), &mut outfile).map_err(|e| io_error(&entry, e))?;
+        if copied > remaining {
+            drop(outfile);
+            let _ = fs::remove_file(&outpath);
+            return Err(ExtractError::TooLarge { entry, limit: MAX_TOTAL_SIZE });
+        }
+        remaining -= copied;
+        written.push(ExtractedFile { entry: path, target: relative });
+    }
+
+    Ok(written)
+}
+
+/// Copy already-extracted files from `src` to `dst`
+pub fn copy_files(src: &Path, dst: &Path, files: &[ExtractedFile]) -> std::io::Result<()> {
+    for file in files {
+        let target = dst.join(&file.target);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(src.join(&file.target), &target)?;
+    }
+    Ok(())
+}
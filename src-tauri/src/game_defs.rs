@@ -0,0 +1,183 @@
+//! Per-community game definitions.
+//!
+//! A table keyed by Thunderstore community identifier gives each game's Steam
+//! app id, install folder names, executables, mod loader and platform quirks,
+//! so games are found by what they are rather than by fuzzy-matching the
+//! community slug against folder names. The table ships with the app
+//! (`resources/game_definitions.json`); a `game_definitions.json` in the app
+//! data folder with a higher `version` adds or replaces entries without an
+//! app update.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+use tauri::{AppHandle, Manager};
+
+use crate::vdf;
+
+const BUNDLED: &str = include_str!("../resources/game_definitions.json");
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LoaderKind {
+    #[default]
+    Bepinex,
+    Melonloader,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GameQuirks {
+    /// Only ships a Windows build, so it runs through Proton/Wine off Windows
+    #[serde(default)]
+    pub windows_only: bool,
+    /// Arguments the game always needs on launch
+    #[serde(default)]
+    pub launch_args: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GameDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub steam_app_id: Option<u32>,
+    /// Legendary/Heroic app name of the Epic release
+    #[serde(default)]
+    pub epic_app_name: Option<String>,
+    /// GOG product id
+    #[serde(default)]
+    pub gog_id: Option<String>,
+    /// Folder names under `steamapps/common`, most likely first
+    #[serde(default)]
+    pub install_folders: Vec<String>,
+    /// Executables relative to the game folder, preferred first
+    #[serde(default)]
+    pub executables: Vec<String>,
+    /// Unity `<Game>_Data` folder
+    #[serde(default)]
+    pub data_folder: Option<String>,
+    #[serde(default)]
+    pub loader: LoaderKind,
+    #[serde(default)]
+    pub quirks: GameQuirks,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DefinitionsFile {
+    version: u32,
+    games: HashMap<String, GameDefinition>,
+}
+
+struct Table {
+    version: u32,
+    games: HashMap<String, GameDefinition>,
+}
+
+static TABLE: RwLock<Option<Table>> = RwLock::new(None);
+
+fn overrides_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join("game_definitions.json"))
+}
+
+fn parse(data: &str) -> Result<DefinitionsFile, String> {
+    serde_json::from_str(data).map_err(|e| format!("Invalid game definitions: {}", e))
+}
+
+fn bundled() -> Table {
+    let file = parse(BUNDLED).expect("bundled game definitions are valid");
+    Table { version: file.version, games: file.games }
+}
+
+fn merge(table: &mut Table, file: DefinitionsFile) {
+    table.version = table.version.max(file.version);
+    table.games.extend(file.games);
+}
+
+/// (Re)build the table from the bundled definitions plus the app data
+/// override, if that one is newer
+pub fn load(app: &AppHandle) {
+    let mut table = bundled();
+    if let Ok(path) = overrides_path(app) {
+        if let Ok(data) = fs::read_to_string(&path) {
+            match parse(&data) {
+                Ok(file) if file.version > table.version => {
+                    eprintln!("[game_defs] Using game definitions v{} ({} entries) from {:?}", file.version, file.games.len(), path);
+                    merge(&mut table, file);
+                }
+                Ok(file) => eprintln!("[game_defs] Ignoring game definitions v{}, bundled is v{}", file.version, table.version),
+                Err(e) => eprintln!("[game_defs] {}", e),
+            }
+        }
+    }
+    *TABLE.write().unwrap() = Some(table);
+}
+
+/// Definition for a community identifier (case-insensitive)
+pub fn get(game_identifier: &str) -> Option<GameDefinition> {
+    if TABLE.read().unwrap().is_none() {
+        TABLE.write().unwrap().get_or_insert_with(bundled);
+    }
+    let table = TABLE.read().unwrap();
+    let games = &table.as_ref()?.games;
+    games.get(game_identifier)
+        .or_else(|| games.iter().find(|(id, _)| id.eq_ignore_ascii_case(game_identifier)).map(|(_, def)| def))
+        .cloned()
+}
+
+/// Install a newer definitions file into the app data folder and reload.
+/// Returns the number of games it defines.
+pub fn update(app: &AppHandle, data: &str) -> Result<usize, String> {
+    let file = parse(data)?;
+    let current = TABLE.read().unwrap().as_ref().map(|t| t.version).unwrap_or(0);
+    if file.version <= current {
+        return Err(format!("Game definitions v{} are not newer than v{}", file.version, current));
+    }
+    let count = file.games.len();
+    crate::storage::atomic_write(&overrides_path(app)?, data.as_bytes())
+        .map_err(|e| format!("Failed to save game definitions: {}", e))?;
+    load(app);
+    Ok(count)
+}
+
+fn find_dir_case_insensitive(parent: &Path, name: &str) -> Option<PathBuf> {
+    let exact = parent.join(name);
+    if exact.is_dir() {
+        return Some(exact);
+    }
+    fs::read_dir(parent).ok()?
+        .filter_map(|e| e.ok())
+        .find(|e| e.file_name().to_string_lossy().eq_ignore_ascii_case(name) && e.path().is_dir())
+        .map(|e| e.path())
+}
+
+/// Where the game is installed in one of the given Steam libraries: the app
+/// manifest for its app id wins, then the known folder names
+pub fn find_in_libraries(def: &GameDefinition, libraries: &[vdf::LibraryFolder]) -> Option<PathBuf> {
+    if let Some((lib, manifest)) = def.steam_app_id.and_then(|id| vdf::find_app(libraries, id)) {
+        let path = lib.steamapps().join("common").join(&manifest.install_dir);
+        if path.is_dir() {
+            return Some(path);
+        }
+    }
+    for lib in libraries {
+        let common = lib.steamapps().join("common");
+        for folder in &def.install_folders {
+            if let Some(path) = find_dir_case_insensitive(&common, folder) {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+/// First of the game's executables present in `game_path`
+pub fn find_executable(def: &GameDefinition, game_path: &Path) -> Option<PathBuf> {
+    def.executables.iter()
+        .map(|exe| game_path.join(exe))
+        .find(|path| path.exists())
+}
@@ -0,0 +1,248 @@
+//! Games installed outside Steam.
+//!
+//! Each `GameScanner` reads one launcher's own records of what it installed:
+//! Legendary/Heroic's `installed.json` for Epic, Heroic's GOG store list (with
+//! the `goggame-<id>.info` manifest GOG puts in every game folder) and Lutris'
+//! per-game YAML files. Native, Flatpak and macOS config locations are all
+//! checked. An install matches a game by its store id from the game
+//! definition, or else by exact (normalized) title or folder name, never by
+//! substring.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::game_defs::GameDefinition;
+
+/// Where a game path came from
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GameSource {
+    Manual,
+    Steam,
+    Epic,
+    Gog,
+    Lutris,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct GameLocation {
+    pub path: String,
+    pub source: GameSource,
+}
+
+/// One game a launcher says it installed
+#[derive(Debug, Clone)]
+pub struct InstalledGame {
+    /// Launcher-specific id (Epic app name, GOG product id, Lutris slug)
+    pub store_id: String,
+    pub title: String,
+    pub path: PathBuf,
+}
+
+pub trait GameScanner: Sync {
+    fn source(&self) -> GameSource;
+
+    fn installed_games(&self, home: &Path) -> Vec<InstalledGame>;
+
+    /// Store id of the game in this launcher, if the definition knows it
+    fn store_id<'a>(&self, _def: &'a GameDefinition) -> Option<&'a str> {
+        None
+    }
+}
+
+pub struct Legendary;
+pub struct HeroicGog;
+pub struct Lutris;
+
+pub const SCANNERS: &[&dyn GameScanner] = &[&Legendary, &HeroicGog, &Lutris];
+
+/// Heroic's config folder for a native, Flatpak or macOS install
+fn heroic_dirs(home: &Path) -> [PathBuf; 3] {
+    [
+        home.join(".config/heroic"),
+        home.join(".var/app/com.heroicgameslauncher.hgl/config/heroic"),
+        home.join("Library/Application Support/heroic"),
+    ]
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Option<T> {
+    let data = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&data) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            eprintln!("[game_sources] Failed to parse {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LegendaryInstall {
+    app_name: String,
+    title: String,
+    install_path: String,
+}
+
+impl GameScanner for Legendary {
+    fn source(&self) -> GameSource {
+        GameSource::Epic
+    }
+
+    fn installed_games(&self, home: &Path) -> Vec<InstalledGame> {
+        let mut files = vec![home.join(".config/legendary/installed.json")];
+        files.extend(heroic_dirs(home).iter().map(|dir| dir.join("legendaryConfig/legendary/installed.json")));
+
+        files.iter()
+            .filter_map(|file| read_json::<HashMap<String, LegendaryInstall>>(file))
+            .flat_map(|installs| installs.into_values())
+            .map(|install| InstalledGame { store_id: install.app_name, title: install.title, path: PathBuf::from(install.install_path) })
+            .collect()
+    }
+
+    fn store_id<'a>(&self, def: &'a GameDefinition) -> Option<&'a str> {
+        def.epic_app_name.as_deref()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HeroicGogInstall {
+    app_name: String,
+    install_path: String,
+    #[serde(default)]
+    is_dlc: bool,
+}
+
+#[derive(Deserialize)]
+struct HeroicGogInstalled {
+    installed: Vec<HeroicGogInstall>,
+}
+
+/// Title from the `goggame-<id>.info` manifest in a GOG game folder
+fn gog_info_title(install_path: &Path, product_id: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct GogInfo {
+        name: String,
+    }
+    read_json::<GogInfo>(&install_path.join(format!("goggame-{}.info", product_id))).map(|info| info.name)
+}
+
+impl GameScanner for HeroicGog {
+    fn source(&self) -> GameSource {
+        GameSource::Gog
+    }
+
+    fn installed_games(&self, home: &Path) -> Vec<InstalledGame> {
+        heroic_dirs(home).iter()
+            .filter_map(|dir| read_json::<HeroicGogInstalled>(&dir.join("gog_store/installed.json")))
+            .flat_map(|list| list.installed)
+            .filter(|install| !install.is_dlc)
+            .map(|install| {
+                let path = PathBuf::from(&install.install_path);
+                let title = gog_info_title(&path, &install.app_name)
+                    .or_else(|| path.file_name().map(|n| n.to_string_lossy().to_string()))
+                    .unwrap_or_default();
+                InstalledGame { store_id: install.app_name, title, path }
+            })
+            .collect()
+    }
+
+    fn store_id<'a>(&self, def: &'a GameDefinition) -> Option<&'a str> {
+        def.gog_id.as_deref()
+    }
+}
+
+#[derive(Deserialize)]
+struct LutrisGame {
+    exe: Option<String>,
+    working_dir: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LutrisConfig {
+    game: Option<LutrisGame>,
+}
+
+/// "lethal-company-1700000000.yml" -> "lethal-company"
+fn lutris_slug(file_stem: &str) -> &str {
+    match file_stem.rsplit_once('-') {
+        Some((slug, ts)) if !ts.is_empty() && ts.chars().all(|c| c.is_ascii_digit()) => slug,
+        _ => file_stem,
+    }
+}
+
+impl GameScanner for Lutris {
+    fn source(&self) -> GameSource {
+        GameSource::Lutris
+    }
+
+    fn installed_games(&self, home: &Path) -> Vec<InstalledGame> {
+        let dirs = [
+            home.join(".local/share/lutris/games"),
+            home.join(".config/lutris/games"),
+            home.join(".var/app/net.lutris.Lutris/data/lutris/games"),
+        ];
+
+        let mut games = Vec::new();
+        for entry in dirs.iter().filter_map(|dir| fs::read_dir(dir).ok()).flatten().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !path.extension().map(|ext| ext == "yml" || ext == "yaml").unwrap_or(false) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(&path) else { continue };
+            let config: LutrisConfig = match serde_yaml::from_str(&content) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("[game_sources] Failed to parse {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            // Steam-runner entries have no exe; the Steam lookup covers those
+            let Some(game) = config.game else { continue };
+            let game_dir = game.working_dir.map(PathBuf::from)
+                .or_else(|| game.exe.as_deref().and_then(|exe| Path::new(exe).parent().map(Path::to_path_buf)));
+            let Some(game_dir) = game_dir else { continue };
+
+            let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            let slug = lutris_slug(&stem).to_string();
+            games.push(InstalledGame { store_id: slug.clone(), title: slug, path: game_dir });
+        }
+        games
+    }
+}
+
+fn normalize(s: &str) -> String {
+    s.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+fn matches(scanner: &dyn GameScanner, install: &InstalledGame, game_identifier: &str, def: Option<&GameDefinition>) -> bool {
+    if let Some(id) = def.and_then(|def| scanner.store_id(def)) {
+        return install.store_id == id;
+    }
+
+    let mut names = vec![normalize(game_identifier)];
+    if let Some(def) = def {
+        names.push(normalize(&def.name));
+        names.extend(def.install_folders.iter().map(|f| normalize(f)));
+    }
+    let folder = install.path.file_name().map(|n| normalize(&n.to_string_lossy())).unwrap_or_default();
+    [normalize(&install.title), folder].iter().any(|n| !n.is_empty() && names.contains(n))
+}
+
+/// First non-Steam install of the game that still exists on disk
+pub fn find_game(game_identifier: &str, def: Option<&GameDefinition>) -> Option<GameLocation> {
+    let home = dirs::home_dir()?;
+    for scanner in SCANNERS {
+        for install in scanner.installed_games(&home) {
+            if install.path.is_dir() && matches(*scanner, &install, game_identifier, def) {
+                eprintln!("[game_sources] Found {} via {:?}: {:?}", install.title, scanner.source(), install.path);
+                return Some(GameLocation { path: install.path.to_string_lossy().to_string(), source: scanner.source() });
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,356 @@
+//! Per-profile history of mod list changes.
+//!
+//! Whenever a save changes a profile's mods (install, remove, toggle, import,
+//! version change) or its config folder, a snapshot is written to
+//! `profiles/<id>/history/<snapshot_id>/`: `snapshot.json` holds the mod set
+//! with versions and enabled flags, and `config/` is a copy of the profile's
+//! `BepInEx/config` folder at that time. Saves that change neither are skipped.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+use tauri::AppHandle;
+
+use crate::profile_store::{self, InstalledMod, Profile};
+
+/// Oldest snapshots beyond this count are pruned
+const MAX_SNAPSHOTS: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfileSnapshot {
+    pub id: String,
+    pub profile_id: String,
+    /// Seconds since the Unix epoch
+    pub created: u64,
+    pub summary: String,
+    pub mods: Vec<InstalledMod>,
+    pub has_config: bool,
+    /// Hash of the config folder's paths and contents; None for older snapshots
+    #[serde(default)]
+    pub config_hash: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SnapshotSummary {
+    pub id: String,
+    pub created: u64,
+    pub summary: String,
+    pub mod_count: usize,
+    pub has_config: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ModRef {
+    pub name: String,
+    pub version: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct VersionChange {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ToggleChange {
+    pub name: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SnapshotDiff {
+    pub added: Vec<ModRef>,
+    pub removed: Vec<ModRef>,
+    pub version_changes: Vec<VersionChange>,
+    pub toggled: Vec<ToggleChange>,
+    /// Config files (relative to `BepInEx/config`) that were added, removed or edited
+    pub config_changes: Vec<String>,
+}
+
+impl SnapshotDiff {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+            && self.version_changes.is_empty() && self.toggled.is_empty()
+    }
+
+    fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.added.is_empty() { parts.push(format!("+{} added", self.added.len())); }
+        if !self.removed.is_empty() { parts.push(format!("-{} removed", self.removed.len())); }
+        if !self.version_changes.is_empty() { parts.push(format!("{} version changes", self.version_changes.len())); }
+        if !self.toggled.is_empty() { parts.push(format!("{} toggled", self.toggled.len())); }
+        if parts.is_empty() { "No changes".to_string() } else { parts.join(", ") }
+    }
+}
+
+fn history_dir(app: &AppHandle, profile_id: &str) -> Result<PathBuf, String> {
+    Ok(profile_store::profile_dir(app, profile_id)?.join("history"))
+}
+
+fn profile_config_dir(app: &AppHandle, profile_id: &str) -> Result<PathBuf, String> {
+    Ok(profile_store::profile_dir(app, profile_id)?.join("BepInEx").join("config"))
+}
+
+fn mod_key(m: &InstalledMod) -> String {
+    m.package_name().to_lowercase()
+}
+
+fn mod_ref(m: &InstalledMod) -> ModRef {
+    ModRef {
+        name: m.package_name().to_string(),
+        version: m.version_number.clone(),
+        enabled: m.enabled,
+    }
+}
+
+/// Compare two mod lists by package name
+pub fn diff_mods(before: &[InstalledMod], after: &[InstalledMod]) -> SnapshotDiff {
+    let before_map: HashMap<String, &InstalledMod> = before.iter().map(|m| (mod_key(m), m)).collect();
+    let after_map: HashMap<String, &InstalledMod> = after.iter().map(|m| (mod_key(m), m)).collect();
+    let mut diff = SnapshotDiff::default();
+
+    for m in after {
+        match before_map.get(&mod_key(m)) {
+            None => diff.added.push(mod_ref(m)),
+            Some(old) => {
+                if old.version_number != m.version_number {
+                    diff.version_changes.push(VersionChange {
+                        name: m.package_name().to_string(),
+                        from: old.version_number.clone(),
+                        to: m.version_number.clone(),
+                    });
+                }
+                if old.enabled != m.enabled {
+                    diff.toggled.push(ToggleChange {
+                        name: m.package_name().to_string(),
+                        enabled: m.enabled,
+                    });
+                }
+            }
+        }
+    }
+    for m in before {
+        if !after_map.contains_key(&mod_key(m)) {
+            diff.removed.push(mod_ref(m));
+        }
+    }
+    diff
+}
+
+/// Relative paths of files that differ between two config folders
+fn diff_config_dirs(before: &Path, after: &Path) -> Vec<String> {
+    fn collect(root: &Path) -> HashMap<String, Vec<u8>> {
+        walkdir::WalkDir::new(root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| {
+                let rel = e.path().strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/");
+                Some((rel, fs::read(e.path()).ok()?))
+            })
+            .collect()
+    }
+    let a = collect(before);
+    let b = collect(after);
+    let mut changed: Vec<String> = a.keys().chain(b.keys())
+        .filter(|k| a.get(*k) != b.get(*k))
+        .cloned()
+        .collect();
+    changed.sort();
+    changed.dedup();
+    changed
+}
+
+/// Hash of every file's relative path and contents under `dir`, None if it doesn't exist
+fn config_hash(dir: &Path) -> Option<String> {
+    if !dir.is_dir() {
+        return None;
+    }
+    let mut files: Vec<(String, PathBuf)> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| Some((e.path().strip_prefix(dir).ok()?.to_string_lossy().replace('\\', "/"), e.path().to_path_buf())))
+        .collect();
+    files.sort();
+    let mut hasher = Sha256::new();
+    for (relative, path) in files {
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update(fs::read(&path).unwrap_or_default());
+        hasher.update([0]);
+    }
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+fn read_snapshot(dir: &Path) -> Option<ProfileSnapshot> {
+    let data = fs::read_to_string(dir.join("snapshot.json")).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// All snapshots of a profile, newest first
+fn load_snapshots(app: &AppHandle, profile_id: &str) -> Result<Vec<ProfileSnapshot>, String> {
+    let dir = history_dir(app, profile_id)?;
+    let mut snapshots: Vec<ProfileSnapshot> = fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| read_snapshot(&e.path()))
+                .collect()
+        })
+        .unwrap_or_default();
+    // Snapshot ids are timestamps and sort chronologically
+    snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(snapshots)
+}
+
+/// Newest snapshot of a profile, without reading the whole history
+fn latest_snapshot(app: &AppHandle, profile_id: &str) -> Option<ProfileSnapshot> {
+    let dir = history_dir(app, profile_id).ok()?;
+    let mut ids: Vec<String> = fs::read_dir(&dir).ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    ids.sort();
+    ids.iter().rev().find_map(|id| read_snapshot(&dir.join(id)))
+}
+
+fn load_snapshot(app: &AppHandle, profile_id: &str, snapshot_id: &str) -> Result<ProfileSnapshot, String> {
+    // Ids come from the frontend and are joined onto the history path
+    if snapshot_id.is_empty() || snapshot_id.contains(['/', '\\']) || snapshot_id.contains("..") {
+        return Err(format!("Invalid snapshot id: {}", snapshot_id));
+    }
+    let dir = history_dir(app, profile_id)?.join(snapshot_id);
+    read_snapshot(&dir).ok_or_else(|| format!("Snapshot not found: {}", snapshot_id))
+}
+
+/// Write a snapshot of `profile` (and its current config folder)
+fn write_snapshot(app: &AppHandle, profile: &Profile, summary: String) -> Result<ProfileSnapshot, String> {
+    let history = history_dir(app, &profile.id)?;
+    // UTC so ids keep sorting chronologically across DST and timezone changes
+    let mut id = chrono::Utc::now().format("%Y%m%d-%H%M%S%.3f").to_string();
+    // Two saves in the same millisecond must not overwrite each other
+    while history.join(&id).exists() {
+        id.push('a');
+    }
+    let snapshot_dir = history.join(&id);
+    fs::create_dir_all(&snapshot_dir).map_err(|e| e.to_string())?;
+
+    let config_src = profile_config_dir(app, &profile.id)?;
+    let has_config = config_src.is_dir();
+    let config_hash = config_hash(&config_src);
+    if has_config {
+        crate::copy_dir_recursive(&config_src, &snapshot_dir.join("config"))
+            .map_err(|e| format!("Failed to snapshot config: {}", e))?;
+    }
+
+    let snapshot = ProfileSnapshot {
+        id,
+        profile_id: profile.id.clone(),
+        created: crate::storage::unix_now(),
+        summary,
+        mods: profile.mods.clone(),
+        has_config,
+        config_hash,
+    };
+    let data = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+    crate::storage::atomic_write(&snapshot_dir.join("snapshot.json"), data.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    for old in load_snapshots(app, &profile.id)?.iter().skip(MAX_SNAPSHOTS) {
+        let _ = fs::remove_dir_all(history.join(&old.id));
+    }
+
+    eprintln!("[history] Snapshot {} for profile {}: {}", snapshot.id, profile.id, snapshot.summary);
+    Ok(snapshot)
+}
+
+/// Snapshot every profile whose mod list or config folder differs from its
+/// newest snapshot. Called after each save of `profiles.json`.
+pub fn record_changes(app: &AppHandle, previous: &[Profile], profiles: &[Profile]) {
+    for profile in profiles {
+        let latest = latest_snapshot(app, &profile.id);
+        let before = latest.as_ref().map(|s| s.mods.as_slice())
+            .or_else(|| previous.iter().find(|p| p.id == profile.id).map(|p| p.mods.as_slice()))
+            .unwrap_or(&[]);
+
+        let diff = diff_mods(before, &profile.mods);
+        let config_changed = match &latest {
+            // Snapshots from before config hashes were kept count as unchanged
+            Some(ProfileSnapshot { config_hash: Some(hash), .. }) => profile_config_dir(app, &profile.id)
+                .map(|dir| config_hash(&dir).as_ref() != Some(hash))
+                .unwrap_or(false),
+            Some(_) => false,
+            None => true,
+        };
+        if diff.is_empty() && !config_changed {
+            continue;
+        }
+        let summary = if diff.is_empty() { "Config changed".to_string() } else { diff.summary() };
+        if let Err(e) = write_snapshot(app, profile, summary) {
+            eprintln!("[history] Failed to snapshot profile {}: {}", profile.id, e);
+        }
+    }
+}
+
+pub fn list_snapshots(app: &AppHandle, profile_id: &str) -> Result<Vec<SnapshotSummary>, String> {
+    Ok(load_snapshots(app, profile_id)?
+        .into_iter()
+        .map(|s| SnapshotSummary {
+            id: s.id,
+            created: s.created,
+            summary: s.summary,
+            mod_count: s.mods.len(),
+            has_config: s.has_config,
+        })
+        .collect())
+}
+
+/// Diff snapshot `from` against snapshot `to`, or against the current profile if `to` is None
+pub fn diff_snapshots(app: &AppHandle, profile_id: &str, from: &str, to: Option<&str>) -> Result<SnapshotDiff, String> {
+    let from_snapshot = load_snapshot(app, profile_id, from)?;
+    let history = history_dir(app, profile_id)?;
+
+    let (to_mods, to_config) = match to {
+        Some(to_id) => (load_snapshot(app, profile_id, to_id)?.mods, history.join(to_id).join("config")),
+        None => (
+            profile_store::find_profile(app, profile_id)?.mods,
+            profile_config_dir(app, profile_id)?,
+        ),
+    };
+
+    let mut diff = diff_mods(&from_snapshot.mods, &to_mods);
+    diff.config_changes = diff_config_dirs(&history.join(from).join("config"), &to_config);
+    Ok(diff)
+}
+
+/// Roll a profile back to a snapshot: its mod list in `profiles.json` and its config folder.
+/// The current state is snapshotted first so the rollback itself can be undone.
+pub fn restore_snapshot(app: &AppHandle, profile_id: &str, snapshot_id: &str) -> Result<Profile, String> {
+    let snapshot = load_snapshot(app, profile_id, snapshot_id)?;
+    let current = profile_store::find_profile(app, profile_id)?;
+    write_snapshot(app, &current, format!("Before restoring {}", snapshot_id))?;
+
+    if snapshot.has_config {
+        let config_dst = profile_config_dir(app, profile_id)?;
+        if config_dst.exists() {
+            fs::remove_dir_all(&config_dst).map_err(|e| e.to_string())?;
+        }
+        let config_src = history_dir(app, profile_id)?.join(snapshot_id).join("config");
+        crate::copy_dir_recursive(&config_src, &config_dst)
+            .map_err(|e| format!("Failed to restore config: {}", e))?;
+    }
+
+    let mut profiles = profile_store::load_profiles(app)?;
+    let profile = profiles.iter_mut()
+        .find(|p| p.id == profile_id)
+        .ok_or("Profile not found")?;
+    profile.mods = snapshot.mods;
+    let restored = profile.clone();
+    profile_store::save_profiles(app, &profiles)?;
+
+    eprintln!("[history] Restored profile {} to snapshot {}", profile_id, snapshot_id);
+    Ok(restored)
+}
@@ -0,0 +1,239 @@
+//! Where each file of a package goes, following r2modman's install rules.
+//!
+//! For BepInEx, a package's top-level folders (`plugins/`, `patchers/`,
+//! `config/`, `monomod/`, `core/`) map onto the matching BepInEx subtree.
+//! Loose files are routed by extension (`*.mm.dll` to monomod, `*.cfg` to
+//! config) and anything else falls back to `BepInEx/plugins/<package>/`.
+//! Subdirectory-tracked routes nest the files under a folder named after the
+//! package so they can be removed again; `config` is shared and installed
+//! flat. Other loaders bring their own rule table (see `mod_loader`).
+//!
+//! Each install writes an `InstallRecord` listing every file and the rule
+//! that placed it, under `profiles/<id>/install_records/`.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+#[derive(Debug)]
+pub struct InstallRule {
+    pub name: &'static str,
+    /// Top-level package folders routed by this rule (case-insensitive)
+    folders: &'static [&'static str],
+    /// Loose-file extensions routed by this rule
+    extensions: &'static [&'static str],
+    /// Destination relative to the game root
+    pub route: &'static str,
+    /// Nest files under `<route>/<package>/`
+    pub subdir: bool,
+}
+
+/// First rule is the fallback for content no other rule matches
+pub const BEPINEX_RULES: &[InstallRule] = &[
+    InstallRule { name: "plugins", folders: &["plugins"], extensions: &[".dll"], route: "BepInEx/plugins", subdir: true },
+    InstallRule { name: "patchers", folders: &["patchers"], extensions: &[], route: "BepInEx/patchers", subdir: true },
+    InstallRule { name: "monomod", folders: &["monomod"], extensions: &[".mm.dll"], route: "BepInEx/monomod", subdir: true },
+    InstallRule { name: "core", folders: &["core"], extensions: &[], route: "BepInEx/core", subdir: true },
+    InstallRule { name: "config", folders: &["config"], extensions: &[".cfg"], route: "BepInEx/config", subdir: false },
+];
+
+/// MelonLoader only loads top-level assemblies, so nothing is nested per package
+pub const MELONLOADER_RULES: &[InstallRule] = &[
+    InstallRule { name: "mods", folders: &["Mods"], extensions: &[".dll"], route: "Mods", subdir: false },
+    InstallRule { name: "plugins", folders: &["Plugins"], extensions: &[".plugin.dll"], route: "Plugins", subdir: false },
+    InstallRule { name: "userlibs", folders: &["UserLibs"], extensions: &[".lib.dll"], route: "UserLibs", subdir: false },
+    InstallRule { name: "userdata", folders: &["UserData"], extensions: &[], route: "UserData", subdir: false },
+    InstallRule { name: "melonloader", folders: &["MelonLoader"], extensions: &[], route: "MelonLoader", subdir: false },
+];
+
+/// Extra rules per community identifier, checked before the defaults
+const GAME_OVERRIDES: &[(&str, &[InstallRule])] = &[
+    ("subnautica", &[InstallRule { name: "qmods", folders: &["QMods"], extensions: &[], route: "QMods", subdir: true }]),
+    ("subnautica-below-zero", &[InstallRule { name: "qmods", folders: &["QMods"], extensions: &[], route: "QMods", subdir: true }]),
+];
+
+pub struct RuleSet {
+    rules: Vec<&'static InstallRule>,
+    default: &'static InstallRule,
+}
+
+/// A file's destination relative to the game root and the rule that chose it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlacedFile {
+    pub path: String,
+    pub rule: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstallRecord {
+    pub package: String,
+    pub files: Vec<PlacedFile>,
+}
+
+impl RuleSet {
+    /// `base` plus the game's overrides; `base[0]` is the fallback rule
+    pub fn new(base: &'static [InstallRule], game_identifier: &str) -> Self {
+        let overrides = GAME_OVERRIDES.iter()
+            .filter(|(game, _)| game.eq_ignore_ascii_case(game_identifier))
+            .flat_map(|(_, rules)| rules.iter());
+        Self { rules: overrides.chain(base.iter()).collect(), default: &base[0] }
+    }
+
+    /// Whether packages get a folder of their own by default (false for flat
+    /// layouts, where only the install records say which file is whose)
+    pub fn nests_packages(&self) -> bool {
+        self.default.subdir
+    }
+
+    /// Routes that nest files per package, relative to the game root
+    pub fn subdir_routes(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.rules.iter().filter(|r| r.subdir).map(|r| r.route)
+    }
+
+    fn by_folder(&self, folder: &str) -> Option<&'static InstallRule> {
+        self.rules.iter().copied()
+            .find(|r| r.folders.iter().any(|f| f.eq_ignore_ascii_case(folder)))
+    }
+
+    /// Longest matching extension wins, so `.mm.dll` beats `.dll`
+    fn by_extension(&self, file_name: &str) -> Option<&'static InstallRule> {
+        let lower = file_name.to_lowercase();
+        self.rules.iter().copied()
+            .filter_map(|r| {
+                r.extensions.iter()
+                    .filter(|ext| lower.ends_with(*ext))
+                    .map(|ext| ext.len())
+                    .max()
+                    .map(|len| (r, len))
+            })
+            .max_by_key(|(_, len)| *len)
+            .map(|(r, _)| r)
+    }
+
+    fn destination(rule: &InstallRule, package: &str, rest: &Path) -> PathBuf {
+        let mut target = PathBuf::from(rule.route);
+        if rule.subdir {
+            target.push(package);
+        }
+        target.join(rest)
+    }
+
+    /// Destination (relative to the game root) of a package file given its
+    /// path inside the archive, plus the name of the rule applied
+    pub fn place(&self, package: &str, entry: &Path) -> (PathBuf, &'static str) {
+        let mut parts: Vec<&std::ffi::OsStr> = entry.components()
+            .filter_map(|c| match c {
+                Component::Normal(p) => Some(p),
+                _ => None,
+            })
+            .collect();
+        // Some packages ship a full "BepInEx/<subtree>/..." layout
+        if parts.len() > 2 && parts[0].eq_ignore_ascii_case("BepInEx") {
+            parts.remove(0);
+        }
+
+        if parts.len() > 1 {
+            if let Some(rule) = parts[0].to_str().and_then(|f| self.by_folder(f)) {
+                let rest: PathBuf = parts[1..].iter().collect();
+                return (Self::destination(rule, package, &rest), rule.name);
+            }
+        }
+        if parts.len() == 1 {
+            if let Some(rule) = parts[0].to_str().and_then(|f| self.by_extension(f)) {
+                return (Self::destination(rule, package, Path::new(parts[0])), rule.name);
+            }
+        }
+        let rest: PathBuf = parts.iter().collect();
+        (Self::destination(self.default, package, &rest), self.default.name)
+    }
+}
+
+fn records_dir(profile_dir: &Path) -> PathBuf {
+    profile_dir.join("install_records")
+}
+
+fn record_path(profile_dir: &Path, package: &str) -> PathBuf {
+    records_dir(profile_dir).join(format!("{}.json", package.replace(['/', '\\'], "_")))
+}
+
+pub fn save_record(profile_dir: &Path, record: &InstallRecord) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(record).map_err(|e| e.to_string())?;
+    crate::storage::atomic_write(&record_path(profile_dir, &record.package), data.as_bytes())
+        .map_err(|e| format!("Failed to save install record for {}: {}", record.package, e))
+}
+
+pub fn load_record(profile_dir: &Path, package: &str) -> Option<InstallRecord> {
+    let data = fs::read_to_string(record_path(profile_dir, package)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+pub fn remove_record(profile_dir: &Path, package: &str) {
+    let _ = fs::remove_file(record_path(profile_dir, package));
+}
+
+pub fn list_records(profile_dir: &Path) -> Vec<InstallRecord> {
+    fs::read_dir(records_dir(profile_dir))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| fs::read_to_string(e.path()).ok())
+                .filter_map(|data| serde_json::from_str(&data).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Move content that older versions left inside `plugins/<package>/` (e.g.
+/// `plugins/<package>/patchers/`) to where the rules put it. Configs that
+/// already exist at their target are not replaced, and files that can't be
+/// moved stay where they are. Returns the record of the resulting layout, or
+/// None if the folder doesn't exist.
+pub fn relayout_plugin_folder(root: &Path, rules: &RuleSet, package: &str) -> Option<InstallRecord> {
+    let plugin_dir = root.join(rules.default.route).join(package);
+    if !rules.default.subdir || !plugin_dir.is_dir() {
+        return None;
+    }
+
+    // Collect first: moving files while walking could visit them twice
+    let entries: Vec<walkdir::DirEntry> = walkdir::WalkDir::new(&plugin_dir).into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .collect();
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let Ok(relative) = entry.path().strip_prefix(&plugin_dir) else { continue };
+        let (target, rule) = rules.place(package, relative);
+        let target_path = root.join(&target);
+        let mut placed = target_path.as_path();
+        if target_path != entry.path() {
+            if rule == "config" && target_path.exists() {
+                // The player's config wins over the default the mod shipped
+                eprintln!("[install_rules] Keeping existing {:?}, leaving {:?} in place", target, entry.path());
+                placed = entry.path();
+            } else {
+                eprintln!("[install_rules] Moving {:?} -> {:?} ({})", entry.path(), target, rule);
+                if let Some(parent) = target_path.parent() {
+                    let _ = fs::create_dir_all(parent);
+                }
+                if let Err(e) = fs::rename(entry.path(), &target_path) {
+                    eprintln!("[install_rules] Failed to move {:?}: {}", entry.path(), e);
+                    placed = entry.path();
+                }
+            }
+        }
+        let Ok(path) = placed.strip_prefix(root) else { continue };
+        files.push(PlacedFile { path: path.to_string_lossy().replace('\\', "/"), rule: rule.to_string() });
+    }
+
+    // Drop folders emptied by the moves
+    for entry in walkdir::WalkDir::new(&plugin_dir).min_depth(1).contents_first(true).into_iter().filter_map(|e| e.ok()) {
+        if entry.file_type().is_dir() {
+            let _ = fs::remove_dir(entry.path());
+        }
+    }
+
+    Some(InstallRecord { package: package.to_string(), files })
+}
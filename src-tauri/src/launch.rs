@@ -0,0 +1,187 @@
+//! Starting the game with the deployed profile.
+//!
+//! Steam mode hands `steam://rungameid/<appid>` to Steam, which owns the game
+//! process from then on, so the process list is polled for something running
+//! from the game folder instead. Direct mode runs the executable itself with
+//! the mod loader's arguments and environment, Wine mode runs a Windows build
+//! through a chosen Wine/Proton binary (see `proton`). All three emit
+//! `game-exited` once the game quits, after stopping the game's log tail and
+//! capturing its configs into the profile; only the last two know the exit code.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{bepinex_log::LogTailers, game_defs::GameDefinition, mod_loader::ModLoader};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchMode {
+    Steam,
+    Direct,
+    Wine,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct GameStarted {
+    pub game_identifier: String,
+    pub profile_id: String,
+    pub mode: LaunchMode,
+    /// Not known in Steam mode
+    pub pid: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct GameExited {
+    pub game_identifier: String,
+    pub profile_id: String,
+    /// None if the process was killed by a signal or was launched through Steam
+    pub exit_code: Option<i32>,
+}
+
+pub fn steam_uri(app_id: &str) -> String {
+    format!("steam://rungameid/{}", app_id)
+}
+
+/// Binary inside a macOS `.app` bundle
+fn app_bundle_binary(bundle: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(bundle.join("Contents").join("MacOS")).ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .find(|p| p.is_file())
+}
+
+/// Command running `exe` from the game folder with the loader's arguments and
+/// environment plus the game's own launch arguments
+pub fn direct_command(exe: &Path, game_root: &Path, loader: &dyn ModLoader, def: Option<&GameDefinition>) -> Result<tokio::process::Command, String> {
+    let is_windows_exe = exe.extension().map(|e| e.eq_ignore_ascii_case("exe")).unwrap_or(false);
+    if is_windows_exe && !cfg!(windows) {
+        return Err(format!("{} is a Windows executable; launch the game through Steam or set a Wine/Proton binary for it", exe.display()));
+    }
+    let program = if exe.extension().map(|e| e == "app").unwrap_or(false) {
+        app_bundle_binary(exe).ok_or_else(|| format!("No executable inside {}", exe.display()))?
+    } else {
+        exe.to_path_buf()
+    };
+
+    let mut command = tokio::process::Command::new(&program);
+    command.current_dir(game_root);
+    command.args(loader.launch_args(game_root));
+    command.envs(loader.launch_env(game_root));
+    if let Some(def) = def {
+        command.args(&def.quirks.launch_args);
+    }
+    eprintln!("[launch_game] Running {:?}", command.as_std());
+    Ok(command)
+}
+
+/// Start `command`, emit `game-started`, and `game-exited` when it quits
+pub fn spawn_and_watch(app: &AppHandle, mut command: tokio::process::Command, mode: LaunchMode, game_identifier: &str, profile_id: &str) -> Result<(), String> {
+    let mut child = command.spawn().map_err(|e| format!("Failed to start the game: {}", e))?;
+
+    let _ = app.emit("game-started", GameStarted {
+        game_identifier: game_identifier.to_string(),
+        profile_id: profile_id.to_string(),
+        mode,
+        pid: child.id(),
+    });
+
+    let app = app.clone();
+    let (game_identifier, profile_id) = (game_identifier.to_string(), profile_id.to_string());
+    tokio::spawn(async move {
+        let exit_code = match child.wait().await {
+            Ok(status) => status.code(),
+            Err(e) => {
+                eprintln!("[launch_game] Failed to wait for the game: {}", e);
+                None
+            }
+        };
+        eprintln!("[launch_game] {} exited with {:?}", game_identifier, exit_code);
+        finish(&app, game_identifier, profile_id, exit_code);
+    });
+    Ok(())
+}
+
+/// How long Steam gets to start the game before it is taken as not launched
+const STEAM_START_TIMEOUT: Duration = Duration::from_secs(120);
+const STEAM_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Emit `game-started` for a launch handed to Steam and `game-exited` once no
+/// process runs from `game_root` anymore
+pub fn watch_steam_launch(app: &AppHandle, game_identifier: &str, profile_id: &str, game_root: &Path) {
+    let _ = app.emit("game-started", GameStarted {
+        game_identifier: game_identifier.to_string(),
+        profile_id: profile_id.to_string(),
+        mode: LaunchMode::Steam,
+        pid: None,
+    });
+
+    let app = app.clone();
+    let (game_identifier, profile_id) = (game_identifier.to_string(), profile_id.to_string());
+    let game_root = normalize_command_path(&game_root.to_string_lossy());
+    tokio::spawn(async move {
+        let started = Instant::now();
+        let mut seen = false;
+        loop {
+            tokio::time::sleep(STEAM_POLL_INTERVAL).await;
+            let running = match game_processes(&game_root).await {
+                Ok(pids) => !pids.is_empty(),
+                Err(e) => {
+                    eprintln!("[launch_game] Cannot watch the game through Steam: {}", e);
+                    return;
+                }
+            };
+            if running {
+                seen = true;
+            } else if seen {
+                break;
+            } else if started.elapsed() > STEAM_START_TIMEOUT {
+                eprintln!("[launch_game] {} did not start within {:?}", game_identifier, STEAM_START_TIMEOUT);
+                break;
+            }
+        }
+        eprintln!("[launch_game] {} exited (launched through Steam)", game_identifier);
+        finish(&app, game_identifier, profile_id, None);
+    });
+}
+
+/// Exit handling shared by every launch mode
+fn finish(app: &AppHandle, game_identifier: String, profile_id: String, exit_code: Option<i32>) {
+    app.state::<LogTailers>().stop(&game_identifier);
+    if let Err(e) = crate::profile_config::capture_deployed(app, &game_identifier) {
+        eprintln!("[launch_game] Failed to capture configs: {}", e);
+    }
+    let _ = app.emit("game-exited", GameExited { game_identifier, profile_id, exit_code });
+}
+
+/// Lowercased, forward-slashed path without a drive letter, so Windows paths
+/// of games running under Proton compare against the Unix game folder
+fn normalize_command_path(path: &str) -> String {
+    let path = path.replace('\\', "/").to_lowercase();
+    let path = match path.as_bytes() {
+        [drive, b':', b'/', ..] if drive.is_ascii_alphabetic() => path[2..].to_string(),
+        _ => path,
+    };
+    format!("{}/", path.trim_end_matches('/'))
+}
+
+/// Ids of the processes whose command line points into `game_root` (normalized)
+async fn game_processes(game_root: &str) -> Result<Vec<u32>, String> {
+    let output = if cfg!(windows) {
+        tokio::process::Command::new("powershell")
+            .args(["-NoProfile", "-Command", "Get-CimInstance Win32_Process | ForEach-Object { \"$($_.ProcessId) $($_.CommandLine)\" }"])
+            .output().await
+    } else {
+        tokio::process::Command::new("ps").args(["-axo", "pid=,command="]).output().await
+    }.map_err(|e| format!("Failed to list processes: {}", e))?;
+
+    let own_pid = std::process::id();
+    Ok(String::from_utf8_lossy(&output.stdout).lines()
+        .filter_map(|line| {
+            let (pid, command) = line.trim().split_once(' ')?;
+            let pid: u32 = pid.parse().ok()?;
+            (pid != own_pid && normalize_command_path(command).contains(game_root)).then_some(pid)
+        })
+        .collect())
+}
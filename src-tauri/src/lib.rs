@@ -2,45 +2,73 @@ use tauri::{command, AppHandle, Manager, Emitter};
 use std::{fs, sync::{Arc, Mutex}, collections::HashMap};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Mod {
-    pub name: String,
-    pub version: String,
-    pub enabled: bool,
-    // Add other fields as needed
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Profile {
-    pub id: String,
-    pub name: String,
-    pub mods: Vec<Mod>,
-    // Add other fields
-}
+mod archive_cache;
+mod bepinex_config;
+mod bepinex_log;
+mod deployment;
+mod downloads;
+mod extract;
+mod game_defs;
+mod game_sources;
+mod history;
+mod install_rules;
+mod launch;
+mod lockfile;
+mod mod_loader;
+mod orphans;
+mod package_index;
+mod profile_config;
+mod profile_store;
+mod proton;
+mod resolver;
+mod steam;
+mod storage;
+mod updates;
+mod vdf;
+
+use profile_store::Profile;
 
 #[command]
-fn get_profiles(app: AppHandle) -> Result<Vec<serde_json::Value>, String> {
-    let profile_path = app.path().app_data_dir().unwrap().join("profiles.json");
-    if !profile_path.exists() {
-        return Ok(vec![]);
+fn get_profiles(app: AppHandle) -> Result<Vec<Profile>, String> {
+    let (profiles, recovered) = profile_store::load_profiles_or_recover(&app)?;
+    if let Some(recovered) = recovered {
+        // Let the frontend tell the user which backup was loaded
+        let _ = app.emit("profiles-recovered", recovered);
     }
-    let data = fs::read_to_string(profile_path).map_err(|e| e.to_string())?;
-    let profiles: Vec<serde_json::Value> = serde_json::from_str(&data).map_err(|e| e.to_string())?;
     Ok(profiles)
 }
 
 #[command]
-fn save_profiles(app: AppHandle, profiles: Vec<serde_json::Value>) -> Result<bool, String> {
-    let profile_path = app.path().app_data_dir().unwrap().join("profiles.json");
-    // Ensure dir exists
-    if let Some(parent) = profile_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
-    let data = serde_json::to_string_pretty(&profiles).map_err(|e| e.to_string())?;
-    fs::write(profile_path, data).map_err(|e| e.to_string())?;
+fn save_profiles(app: AppHandle, profiles: Vec<Profile>) -> Result<bool, String> {
+    profile_store::save_profiles(&app, &profiles)?;
     Ok(true)
 }
 
+#[command]
+fn list_profiles_backups(app: AppHandle) -> Result<Vec<storage::BackupInfo>, String> {
+    profile_store::list_profiles_backups(&app)
+}
+
+#[command]
+fn restore_profiles_backup(app: AppHandle, file_name: String) -> Result<Vec<Profile>, String> {
+    profile_store::restore_profiles_backup(&app, &file_name)
+}
+
+#[command]
+fn list_profile_snapshots(app: AppHandle, profile_id: String) -> Result<Vec<history::SnapshotSummary>, String> {
+    history::list_snapshots(&app, &profile_id)
+}
+
+#[command]
+fn diff_profile_snapshots(app: AppHandle, profile_id: String, from: String, to: Option<String>) -> Result<history::SnapshotDiff, String> {
+    history::diff_snapshots(&app, &profile_id, &from, to.as_deref())
+}
+
+#[command]
+fn restore_profile_snapshot(app: AppHandle, profile_id: String, snapshot_id: String) -> Result<Profile, String> {
+    history::restore_snapshot(&app, &profile_id, &snapshot_id)
+}
+
 #[command]
 async fn fetch_communities() -> Result<Vec<serde_json::Value>, String> {
     let mut url = Some("https://thunderstore.io/api/experimental/community/".to_string());
@@ -114,6 +142,10 @@ struct Settings {
     game_paths: HashMap<String, String>,
     #[serde(default)]
     legacy_install_mode: bool,  // If true, install directly to game (old behavior)
+    #[serde(default)]
+    archive_cache_quota_mb: Option<u64>,  // Max size of the shared archive cache, None = unlimited
+    #[serde(default)]
+    wine_launch: HashMap<String, proton::WineLaunch>,  // Per game: Wine/Proton binary to launch the Windows build with
 }
 
 impl Settings {
@@ -124,6 +156,8 @@ impl Settings {
             favorite_games: Vec::new(),
             game_paths: HashMap::new(),
             legacy_install_mode: false,  // New mode by default
+            archive_cache_quota_mb: None,
+            wine_launch: HashMap::new(),
         }
     }
 }
@@ -132,7 +166,7 @@ fn get_settings_path(app: &AppHandle) -> std::path::PathBuf {
     app.path().app_data_dir().unwrap().join("settings.json")
 }
 
-fn load_settings_impl(app: &AppHandle) -> Settings {
+pub(crate) fn load_settings_impl(app: &AppHandle) -> Settings {
     let path = get_settings_path(app);
     if path.exists() {
         if let Ok(data) = fs::read_to_string(&path) {
@@ -140,6 +174,17 @@ fn load_settings_impl(app: &AppHandle) -> Settings {
                 return settings;
             }
         }
+
+        // Corrupted settings.json: fall back to the newest backup that still parses
+        if let Ok(backup_dir) = storage::backups_dir(app) {
+            let is_valid = |data: &str| serde_json::from_str::<Settings>(data).is_ok();
+            if let Some((backup, data)) = storage::newest_valid_backup(&backup_dir, &path, is_valid) {
+                if let Ok(settings) = serde_json::from_str(&data) {
+                    eprintln!("[settings] settings.json is unreadable, using backup {}", backup.file_name);
+                    return settings;
+                }
+            }
+        }
     }
     Settings::default()
 }
@@ -152,8 +197,7 @@ async fn get_settings(app: AppHandle) -> Result<Settings, String> {
 fn save_settings_impl(app: &AppHandle, settings: &Settings) -> Result<(), String> {
     let path = get_settings_path(app);
     let data = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
-    fs::write(path, data).map_err(|e| e.to_string())?;
-    Ok(())
+    storage::write_with_backup(&storage::backups_dir(app)?, &path, data.as_bytes())
 }
 
 #[command]
@@ -161,49 +205,56 @@ async fn save_settings(app: AppHandle, settings: Settings) -> Result<(), String>
     save_settings_impl(&app, &settings)
 }
 
+/// Steam installs found on this machine, for the settings UI to offer
+#[command]
+async fn detect_steam_installations() -> Result<Vec<steam::SteamInstallation>, String> {
+    Ok(steam::detect_installations())
+}
+
 /// Normalize a string for fuzzy matching: lowercase, remove non-alphanumeric
 fn normalize_for_matching(s: &str) -> String {
     s.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect()
 }
 
-/// Get all Steam library folders
-fn get_steam_library_folders(steam_path: &std::path::Path) -> Vec<std::path::PathBuf> {
-    let mut folders = vec![steam_path.to_path_buf()];
-    
-    let library_folders_path = steam_path.join("steamapps").join("libraryfolders.vdf");
-    if library_folders_path.exists() {
-        if let Ok(content) = fs::read_to_string(&library_folders_path) {
-            let re = regex::Regex::new(r#""path"\s+"([^"]+)""#).unwrap();
-            for cap in re.captures_iter(&content) {
-                folders.push(std::path::PathBuf::from(&cap[1]));
-            }
-        }
-    }
-    
-    folders
-}
+/// Find a game: manual override, then Steam by game definition, then other
+/// launchers (Heroic, Lutris, ...), then fuzzy folder matching in Steam
+fn locate_game(app: &AppHandle, game_identifier: &str) -> Result<Option<game_sources::GameLocation>, String> {
+    use game_sources::{GameLocation, GameSource};
 
-#[command]
-async fn get_game_path(app: AppHandle, game_identifier: String) -> Result<Option<String>, String> {
-    let settings = load_settings_impl(&app);
+    let settings = load_settings_impl(app);
+    let found = |path: &std::path::Path, source| Some(GameLocation { path: path.to_string_lossy().to_string(), source });
 
     // Check manual override first
-    if let Some(path) = settings.game_paths.get(&game_identifier) {
+    if let Some(path) = settings.game_paths.get(game_identifier) {
         if std::path::Path::new(path).exists() {
             eprintln!("[get_game_path] Found manual override: {}", path);
-            return Ok(Some(path.clone()));
+            return Ok(found(std::path::Path::new(path), GameSource::Manual));
         }
     }
 
-    let steam_path_str = settings.steam_path.ok_or("Steam path not configured")?;
-    let steam_path = std::path::Path::new(&steam_path_str);
+    let def = game_defs::get(game_identifier);
+    let libraries = settings.steam_path.as_ref().map(|p| vdf::library_folders(std::path::Path::new(p)));
+
+    // Known games are found by app id / folder name
+    if let (Some(def), Some(libraries)) = (&def, &libraries) {
+        if let Some(path) = game_defs::find_in_libraries(def, libraries) {
+            eprintln!("[get_game_path] Found {} from game definitions: {:?}", def.name, path);
+            return Ok(found(&path, GameSource::Steam));
+        }
+    }
+
+    if let Some(location) = game_sources::find_game(game_identifier, def.as_ref()) {
+        return Ok(Some(location));
+    }
 
-    let normalized_id = normalize_for_matching(&game_identifier);
+    let libraries = libraries.ok_or("Steam path not configured")?;
+    let normalized_id = normalize_for_matching(game_identifier);
     eprintln!("[get_game_path] Looking for game: {} (normalized: {})", game_identifier, normalized_id);
 
-    // Scan all Steam library folders
-    for lib_folder in get_steam_library_folders(steam_path) {
-        let common_path = lib_folder.join("steamapps").join("common");
+    // Scan all Steam library folders, exact matches first so "Game" doesn't pick "Game 2"
+    let mut partial_match = None;
+    for lib_folder in &libraries {
+        let common_path = lib_folder.steamapps().join("common");
         if !common_path.exists() {
             continue;
         }
@@ -213,22 +264,62 @@ async fn get_game_path(app: AppHandle, game_identifier: String) -> Result<Option
                 let folder_name = entry.file_name().to_string_lossy().to_string();
                 let normalized_folder = normalize_for_matching(&folder_name);
                 
-                // Check for match (exact or high similarity)
-                if normalized_folder == normalized_id || 
-                   normalized_folder.contains(&normalized_id) || 
-                   normalized_id.contains(&normalized_folder) {
-                    let game_path = entry.path().to_string_lossy().to_string();
-                    eprintln!("[get_game_path] Found match: {} -> {}", folder_name, game_path);
-                    return Ok(Some(game_path));
+                if normalized_folder == normalized_id {
+                    eprintln!("[get_game_path] Found match: {} -> {:?}", folder_name, entry.path());
+                    return Ok(found(&entry.path(), GameSource::Steam));
+                }
+                if partial_match.is_none() && !normalized_folder.is_empty() &&
+                   (normalized_folder.contains(&normalized_id) || normalized_id.contains(&normalized_folder)) {
+                    partial_match = Some((folder_name, entry.path()));
                 }
             }
         }
     }
 
+    if let Some((folder_name, game_path)) = partial_match {
+        eprintln!("[get_game_path] Found partial match: {} -> {:?}", folder_name, game_path);
+        return Ok(found(&game_path, GameSource::Steam));
+    }
+
     eprintln!("[get_game_path] No match found for: {}", game_identifier);
     Ok(None)
 }
 
+#[command]
+async fn get_game_path(app: AppHandle, game_identifier: String) -> Result<Option<String>, String> {
+    Ok(locate_game(&app, &game_identifier)?.map(|location| location.path))
+}
+
+/// Like `get_game_path`, plus where the path came from (Steam, Epic, GOG, ...)
+#[command]
+async fn get_game_location(app: AppHandle, game_identifier: String) -> Result<Option<game_sources::GameLocation>, String> {
+    locate_game(&app, &game_identifier)
+}
+
+#[command]
+async fn get_game_definition(game_identifier: String) -> Result<Option<game_defs::GameDefinition>, String> {
+    Ok(game_defs::get(&game_identifier))
+}
+
+/// Steam's manifest (build id, last update, install state) for a known game
+#[command]
+async fn get_steam_app_manifest(app: AppHandle, game_identifier: String) -> Result<Option<vdf::AppManifest>, String> {
+    let settings = load_settings_impl(&app);
+    let steam_path_str = settings.steam_path.ok_or("Steam path not configured")?;
+    let Some(app_id) = game_defs::get(&game_identifier).and_then(|def| def.steam_app_id) else {
+        return Ok(None);
+    };
+    let libraries = vdf::library_folders(std::path::Path::new(&steam_path_str));
+    Ok(vdf::find_app(&libraries, app_id).map(|(_, manifest)| manifest))
+}
+
+/// Install a newer game definitions file (JSON, same format as the bundled one)
+#[command]
+async fn update_game_definitions(app: AppHandle, path: String) -> Result<usize, String> {
+    let data = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    game_defs::update(&app, &data)
+}
+
 #[command]
 async fn set_game_path(app: AppHandle, game_identifier: String, path: String) -> Result<(), String> {
     let mut settings = load_settings_impl(&app);
@@ -239,50 +330,21 @@ async fn set_game_path(app: AppHandle, game_identifier: String, path: String) ->
 
 #[command]
 async fn open_game_folder(app: AppHandle, game_identifier: String) -> Result<(), String> {
-    let settings = load_settings_impl(&app);
-    
-    // Check manual override first
-    if let Some(path) = settings.game_paths.get(&game_identifier) {
-        let path_obj = std::path::Path::new(path);
-        if path_obj.exists() {
-             let _ = open::that(path_obj);
-             return Ok(());
-        }
-    }
-
-    if let Some(steam_path_str) = settings.steam_path {
-        let steam_path = std::path::Path::new(&steam_path_str);
-        
-        for lib_folder in get_steam_library_folders(steam_path) {
-            let common = lib_folder.join("steamapps").join("common");
-            if !common.exists() {
-                continue;
-            }
-            
-            if let Ok(entries) = fs::read_dir(&common) {
-                for entry in entries.filter_map(|e| e.ok()) {
-                    if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
-                        let folder_name = entry.file_name().to_string_lossy().to_string();
-                        if folder_name.to_lowercase().contains(&game_identifier.to_lowercase()) {
-                            let game_path = entry.path();
-                            let _ = open::that(&game_path);
-                            return Ok(());
-                        }
-                    }
-                }
-            }
-        }
-        
-        return Err("Game directory not found".to_string());
-    }
-    
-    Err("Steam path not configured".to_string())
+    let location = locate_game(&app, &game_identifier)?.ok_or("Game directory not found")?;
+    let _ = open::that(&location.path);
+    Ok(())
 }
 
 
 #[command]
-async fn find_game_executable(game_path: String) -> Result<Option<String>, String> {
+async fn find_game_executable(game_path: String, game_identifier: Option<String>) -> Result<Option<String>, String> {
     let path = std::path::Path::new(&game_path);
+
+    if let Some(def) = game_identifier.as_deref().and_then(game_defs::get) {
+        if let Some(exe) = game_defs::find_executable(&def, path) {
+            return Ok(Some(exe.to_string_lossy().to_string()));
+        }
+    }
     
     // On macOS, look for .app bundles first
     if let Ok(entries) = fs::read_dir(path) {
@@ -306,28 +368,221 @@ async fn find_game_executable(game_path: String) -> Result<Option<String>, Strin
     
     Ok(None)
 }
-/// Find Steam App ID by matching the game folder name in appmanifest files
+
+/// Start the game with `profile_id`, which must be the profile last applied to
+/// it. `mode` defaults to Steam for Steam installs with a known app id.
+#[command]
+async fn launch_game(app: AppHandle, game_identifier: String, profile_id: String, mode: Option<launch::LaunchMode>) -> Result<launch::LaunchMode, String> {
+    use launch::LaunchMode;
+
+    let profile = profile_store::find_profile(&app, &profile_id)?;
+    deployment::check(&app, &game_identifier, &profile)?;
+
+    let location = locate_game(&app, &game_identifier)?.ok_or("Game path not configured. Please set it in Settings.")?;
+    let game_path = std::path::Path::new(&location.path);
+    let def = game_defs::get(&game_identifier);
+
+    let app_id = def.as_ref().and_then(|d| d.steam_app_id).map(|id| id.to_string()).or_else(|| {
+        let steam_path = load_settings_impl(&app).steam_path?;
+        let folder = game_path.file_name()?.to_string_lossy().to_string();
+        find_steam_app_id(std::path::Path::new(&steam_path), &folder)
+    });
+    let settings = load_settings_impl(&app);
+    let wine = settings.wine_launch.get(&game_identifier);
+    let is_steam_install = location.source == game_sources::GameSource::Steam;
+    let mode = mode.unwrap_or(if is_steam_install && app_id.is_some() {
+        LaunchMode::Steam
+    } else if wine.is_some() {
+        LaunchMode::Wine
+    } else {
+        LaunchMode::Direct
+    });
+    eprintln!("[launch_game] Launching {} with profile {} ({:?})", game_identifier, profile_id, mode);
+    start_log_tail_impl(&app, &game_identifier, &profile_id, game_path)?;
+
+    match mode {
+        LaunchMode::Steam => {
+            let app_id = app_id.ok_or("Steam app id of the game is unknown")?;
+            open::that(launch::steam_uri(&app_id)).map_err(|e| format!("Failed to open Steam: {}", e))?;
+            launch::watch_steam_launch(&app, &game_identifier, &profile_id, game_path);
+        }
+        LaunchMode::Direct => {
+            let exe = find_game_executable(location.path.clone(), Some(game_identifier.clone())).await?
+                .ok_or("Game executable not found")?;
+            let loader = mod_loader::for_game(&game_identifier);
+            let command = launch::direct_command(std::path::Path::new(&exe), game_path, loader, def.as_ref())?;
+            launch::spawn_and_watch(&app, command, mode, &game_identifier, &profile_id)?;
+        }
+        LaunchMode::Wine => {
+            let wine = wine.ok_or("No Wine/Proton binary is set for this game")?;
+            let exe = find_game_executable(location.path.clone(), Some(game_identifier.clone())).await?
+                .ok_or("Game executable not found")?;
+            // Proton without a prefix of its own uses the game's Steam compatdata
+            let steam = settings.steam_path.as_ref().and_then(|steam_path| {
+                let steam_path = std::path::Path::new(steam_path);
+                let app_id: u32 = app_id.as_ref()?.parse().ok()?;
+                let compat_data = proton::detect(steam_path, &vdf::library_folders(steam_path), app_id, mod_loader::for_game(&game_identifier), None).compat_data?;
+                Some((steam_path, std::path::PathBuf::from(compat_data)))
+            });
+            let loader = mod_loader::for_game(&game_identifier);
+            let command = proton::wine_command(wine, std::path::Path::new(&exe), game_path, loader, def.as_ref(), steam)?;
+            launch::spawn_and_watch(&app, command, mode, &game_identifier, &profile_id)?;
+        }
+    }
+    Ok(mode)
+}
+
+fn start_log_tail_impl(app: &AppHandle, game_identifier: &str, profile_id: &str, game_path: &std::path::Path) -> Result<(), String> {
+    if mod_loader::for_game(game_identifier).log_file().is_none() {
+        return Ok(());
+    }
+    let profile_dir = profile_store::profile_dir(app, profile_id)?;
+    app.state::<bepinex_log::LogTailers>().start(app, game_identifier, game_path.to_path_buf(), Some(profile_dir));
+    Ok(())
+}
+
+/// Stream the game's BepInEx log as `game-log` events until the game exits or
+/// `stop_log_tail` is called. Launching the game starts this already.
+#[command]
+async fn start_log_tail(app: AppHandle, game_identifier: String) -> Result<(), String> {
+    let location = locate_game(&app, &game_identifier)?.ok_or("Game path not configured. Please set it in Settings.")?;
+    let profile_id = deployment::get(&app, &game_identifier).map(|d| d.profile_id);
+    let profile_dir = profile_id.map(|id| profile_store::profile_dir(&app, &id)).transpose()?;
+    app.state::<bepinex_log::LogTailers>().start(&app, &game_identifier, std::path::PathBuf::from(location.path), profile_dir);
+    Ok(())
+}
+
+#[command]
+async fn stop_log_tail(app: AppHandle, game_identifier: String) -> Result<(), String> {
+    app.state::<bepinex_log::LogTailers>().stop(&game_identifier);
+    Ok(())
+}
+
+/// Which plugins (and packages) logged errors or exceptions, or failed to
+/// load, during the game's last session
+#[command]
+async fn get_last_session_summary(app: AppHandle, game_identifier: String) -> Result<bepinex_log::SessionSummary, String> {
+    let location = locate_game(&app, &game_identifier)?.ok_or("Game path not configured. Please set it in Settings.")?;
+    let profile_id = deployment::get(&app, &game_identifier).map(|d| d.profile_id);
+    let profile_dir = profile_id.map(|id| profile_store::profile_dir(&app, &id)).transpose()?;
+    bepinex_log::last_session(std::path::Path::new(&location.path), profile_dir.as_deref())
+}
+
+/// Root folder (profile or game) whose `BepInEx/config` a config command works on
+fn config_root(app: &AppHandle, location: &bepinex_config::ConfigLocation) -> Result<std::path::PathBuf, String> {
+    use bepinex_config::ConfigLocation;
+    let (game_identifier, root) = match location {
+        ConfigLocation::Profile(profile_id) => {
+            let profile = profile_store::find_profile(app, profile_id)?;
+            (profile.game_identifier, profile_store::profile_dir(app, profile_id)?)
+        }
+        ConfigLocation::Game(game_identifier) => {
+            let location = locate_game(app, game_identifier)?.ok_or("Game path not configured. Please set it in Settings.")?;
+            (game_identifier.clone(), std::path::PathBuf::from(location.path))
+        }
+    };
+    if mod_loader::for_game(&game_identifier).config_dir().is_none() {
+        return Err("Config editing is only supported for BepInEx games".to_string());
+    }
+    Ok(root)
+}
+
+#[command]
+async fn list_config_files(app: AppHandle, location: bepinex_config::ConfigLocation) -> Result<Vec<bepinex_config::ConfigFileInfo>, String> {
+    Ok(bepinex_config::list_files(&config_root(&app, &location)?))
+}
+
+#[command]
+async fn read_config_file(app: AppHandle, location: bepinex_config::ConfigLocation, path: String) -> Result<bepinex_config::ConfigFile, String> {
+    bepinex_config::read_file(&config_root(&app, &location)?, &path)
+}
+
+#[command]
+async fn set_config_value(app: AppHandle, location: bepinex_config::ConfigLocation, path: String, section: String, key: String, value: String) -> Result<bepinex_config::ConfigEntry, String> {
+    let root = config_root(&app, &location)?;
+    let mut file = bepinex_config::read_file(&root, &path)?;
+    let entry = file.set_value(&section, &key, &value)?;
+    bepinex_config::write_file(&root, &file)?;
+    Ok(entry)
+}
+
+#[command]
+async fn reset_config_entry(app: AppHandle, location: bepinex_config::ConfigLocation, path: String, section: String, key: String) -> Result<bepinex_config::ConfigEntry, String> {
+    let root = config_root(&app, &location)?;
+    let mut file = bepinex_config::read_file(&root, &path)?;
+    let entry = file.reset_value(&section, &key)?;
+    bepinex_config::write_file(&root, &file)?;
+    Ok(entry)
+}
+
+/// Set every entry of a config file back to its default
+#[command]
+async fn reset_config_file(app: AppHandle, location: bepinex_config::ConfigLocation, path: String) -> Result<bepinex_config::ConfigFile, String> {
+    let root = config_root(&app, &location)?;
+    let mut file = bepinex_config::read_file(&root, &path)?;
+    let changed = file.reset_all();
+    if changed > 0 {
+        bepinex_config::write_file(&root, &file)?;
+    }
+    eprintln!("[reset_config_file] Reset {} entries in {}", changed, path);
+    Ok(file)
+}
+
+/// Copy the game folder's configs back into the profile deployed to it, e.g.
+/// after playing a game launched through Steam
+#[command]
+async fn capture_game_config(app: AppHandle, game_identifier: String) -> Result<Option<profile_config::ReverseSync>, String> {
+    profile_config::capture_deployed(&app, &game_identifier)
+}
+
+/// Whether a Steam game runs through Proton, and the launch options its mod
+/// loader needs there. None if the game isn't a known Steam game.
+#[command]
+async fn get_proton_status(app: AppHandle, game_identifier: String) -> Result<Option<proton::ProtonStatus>, String> {
+    let steam_path_str = load_settings_impl(&app).steam_path.ok_or("Steam path not configured")?;
+    let steam_path = std::path::Path::new(&steam_path_str);
+    let def = game_defs::get(&game_identifier);
+    let app_id = match def.as_ref().and_then(|d| d.steam_app_id) {
+        Some(id) => Some(id),
+        None => locate_game(&app, &game_identifier)?
+            .and_then(|location| {
+                let folder = std::path::Path::new(&location.path).file_name()?.to_string_lossy().to_string();
+                find_steam_app_id(steam_path, &folder)
+            })
+            .and_then(|id| id.parse().ok()),
+    };
+    let Some(app_id) = app_id else { return Ok(None) };
+
+    let loader = mod_loader::for_game(&game_identifier);
+    Ok(Some(proton::detect(steam_path, &vdf::library_folders(steam_path), app_id, loader, def.as_ref())))
+}
+
+/// Set (or clear, with None) the Wine/Proton binary a game is launched with
+#[command]
+async fn set_wine_launch(app: AppHandle, game_identifier: String, config: Option<proton::WineLaunch>) -> Result<(), String> {
+    let mut settings = load_settings_impl(&app);
+    match config {
+        Some(config) => settings.wine_launch.insert(game_identifier, config),
+        None => settings.wine_launch.remove(&game_identifier),
+    };
+    save_settings_impl(&app, &settings)
+}
+
+/// Find Steam App ID by matching the game folder name against the manifests
+/// of the apps each library lists
 fn find_steam_app_id(steam_path: &std::path::Path, game_folder: &str) -> Option<String> {
-    for lib_folder in get_steam_library_folders(steam_path) {
-        let steamapps = lib_folder.join("steamapps");
-        if let Ok(entries) = fs::read_dir(&steamapps) {
-            for entry in entries.filter_map(|e| e.ok()) {
-                let name = entry.file_name().to_string_lossy().to_string();
-                if name.starts_with("appmanifest_") && name.ends_with(".acf") {
-                    if let Ok(content) = fs::read_to_string(entry.path()) {
-                        // Parse installdir from manifest
-                        let re = regex::Regex::new(r#""installdir"\s+"([^"]+)""#).unwrap();
-                        if let Some(cap) = re.captures(&content) {
-                            if cap[1].to_lowercase() == game_folder.to_lowercase() {
-                                // Extract app_id from filename
-                                let app_id = name
-                                    .strip_prefix("appmanifest_")
-                                    .and_then(|s| s.strip_suffix(".acf"))
-                                    .unwrap_or("");
-                                return Some(app_id.to_string());
-                            }
-                        }
-                    }
+    for lib_folder in vdf::library_folders(steam_path) {
+        let steamapps = lib_folder.steamapps();
+        // Old-format entries and a Steam root missing from the file have no `apps` map
+        let app_ids: Vec<u32> = if lib_folder.apps.is_empty() {
+            vdf::manifest_ids(&steamapps)
+        } else {
+            lib_folder.apps.keys().copied().collect()
+        };
+        for app_id in app_ids {
+            if let Some(manifest) = vdf::read_app_manifest(&steamapps, app_id) {
+                if manifest.install_dir.eq_ignore_ascii_case(game_folder) {
+                    return Some(app_id.to_string());
                 }
             }
         }
@@ -336,7 +591,7 @@ fn find_steam_app_id(steam_path: &std::path::Path, game_folder: &str) -> Option<
 }
 
 #[command]
-async fn install_to_game(app: AppHandle, game_identifier: String, profile_id: String, disabled_mods: Vec<String>) -> Result<(), String> {
+async fn install_to_game(app: AppHandle, game_identifier: String, profile_id: String, disabled_mods: Vec<String>) -> Result<deployment::CleanupReport, String> {
     // 1. Find game path
     let game_path_str = get_game_path(app.clone(), game_identifier.clone()).await?
         .ok_or("Game not found in Steam library")?;
@@ -352,122 +607,16 @@ async fn install_to_game(app: AppHandle, game_identifier: String, profile_id: St
 
     eprintln!("[install_to_game] Disabled mods: {:?}", disabled_mods);
 
-    // --- FIX BEPINEX STRUCTURE START ---
-    // Always check for BepInExPack in plugins and ensure it's properly installed at root
-    let plugins_dir = profile_dir.join("BepInEx").join("plugins");
-    if plugins_dir.exists() {
-        // Find BepInExPack folder - search for various naming patterns and PRIORITIZE content
-        // Score: 3 = Explicit BepInExPack folder structure, 2 = winhttp.dll present + name match, 1 = winhttp.dll present
-        let mut best_candidate: Option<(std::path::PathBuf, i32)> = None;
-        
-        if let Ok(entries) = fs::read_dir(&plugins_dir) {
-            for entry in entries.filter_map(|e| e.ok()) {
-                let path = entry.path();
-                if !path.is_dir() { continue; }
-
-                let folder_name = path.file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("")
-                    .to_lowercase();
-                
-                // Pattern 1: Nested BepInExPack (Standard Thunderstore structure)
-                // plugins/ModName/BepInExPack/winhttp.dll
-                let nested_pack = path.join("BepInExPack");
-                if nested_pack.join("winhttp.dll").exists() {
-                    eprintln!("[install_to_game] Found nested BepInExPack candidate: {:?}", nested_pack);
-                    let score = 3;
-                    if best_candidate.as_ref().map_or(true, |(_, s)| score > *s) {
-                        best_candidate = Some((nested_pack, score));
-                    }
-                    continue; 
-                }
-                
-                // Pattern 2: Direct or subdirectory check
-                // Check if this folder ITSELF is the pack
-                if path.join("winhttp.dll").exists() {
-                    let mut score = 1;
-                    if folder_name.contains("bepinex") { score += 1; }
-                    
-                    eprintln!("[install_to_game] Found direct BepInExPack candidate: {:?} (score: {})", path, score);
-                    if best_candidate.as_ref().map_or(true, |(_, s)| score > *s) {
-                         best_candidate = Some((path.clone(), score));
-                    }
-                    continue;
-                }
-
-                // Pattern 3: Search subdirectories (e.g. plugins/ModName/BepInExPack_GameName)
-                if let Ok(sub_entries) = fs::read_dir(&path) {
-                    for sub_entry in sub_entries.filter_map(|e| e.ok()) {
-                        let sub_path = sub_entry.path();
-                         if sub_path.is_dir() && sub_path.join("winhttp.dll").exists() {
-                             let sub_name = sub_path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_lowercase();
-                             let mut score = 1;
-                             if sub_name.contains("bepinex") { score += 1; }
-                             
-                             eprintln!("[install_to_game] Found subdirectory BepInExPack candidate: {:?} (score: {})", sub_path, score);
-                             
-                             if best_candidate.as_ref().map_or(true, |(_, s)| score > *s) {
-                                best_candidate = Some((sub_path, score));
-                             }
-                        }
-                    }
-                }
-            }
-        }
-
-        if let Some((pack_dir, score)) = best_candidate {
-            eprintln!("[install_to_game] Selected BepInExPack: {:?} (score: {})", pack_dir, score);
-            
-            // 1. Ensure winhttp.dll is at profile root
-            let winhttp_src = pack_dir.join("winhttp.dll");
-            let winhttp_dst = profile_dir.join("winhttp.dll");
-            if winhttp_src.exists() && !winhttp_dst.exists() {
-                eprintln!("[install_to_game] Copying winhttp.dll to profile root");
-                fs::copy(&winhttp_src, &winhttp_dst)
-                    .map_err(|e| format!("Failed to copy winhttp.dll: {}", e))?;
-            }
-            
-            // 2. Ensure doorstop_config.ini is at profile root
-            let doorstop_src = pack_dir.join("doorstop_config.ini");
-            let doorstop_dst = profile_dir.join("doorstop_config.ini");
-            if doorstop_src.exists() && !doorstop_dst.exists() {
-                eprintln!("[install_to_game] Copying doorstop_config.ini to profile root");
-                fs::copy(&doorstop_src, &doorstop_dst)
-                    .map_err(|e| format!("Failed to copy doorstop_config.ini: {}", e))?;
-                
-                // FORCE ENABLE DOORSTOP
-                // We read the file, verify 'enabled=true', and write it back if needed
-                 if let Ok(content) = fs::read_to_string(&doorstop_dst) {
-                    if !content.contains("enabled=true") && !content.contains("enabled = true") {
-                         eprintln!("[install_to_game] Enforcing enabled=true in doorstop_config.ini");
-                         // Simple replacement or append if missing. 
-                         // To be safe, let's just replace "enabled=false" with "enabled=true" or ensure it's there.
-                         let new_content = content.replace("enabled=false", "enabled=true")
-                                                  .replace("enabled = false", "enabled = true");
-                         let _ = fs::write(&doorstop_dst, new_content);
-                    }
-                 }
-            }
-
-            // 3. Merge BepInEx core/config from the pack (if present)
-            let pack_bepinex = pack_dir.join("BepInEx");
-            if pack_bepinex.exists() {
-                eprintln!("[install_to_game] Merging BepInEx core/config from pack...");
-                let target_bepinex = profile_dir.join("BepInEx");
-                copy_dir_recursive(&pack_bepinex, &target_bepinex)
-                    .map_err(|e| format!("Failed to merge BepInEx folder: {}", e))?;
-            }
-        } else {
-            eprintln!("[install_to_game] Warning: No BepInExPack found in plugins!");
-        }
-    }
-    // --- FIX BEPINEX STRUCTURE END ---
+    let loader = mod_loader::for_game(&game_identifier);
+    loader.prepare_profile(&profile_dir)?;
 
     eprintln!("[install_to_game] Installing profile {} to game {}", profile_id, game_path.display());
+    // Only what the ledger says we placed is removed from the game
+    let mut cleanup = deployment::CleanupReport::default();
 
     // --- SYNC: Remove mods from game that are not in profile OR are disabled ---
-    let profile_plugins = profile_dir.join("BepInEx").join("plugins");
-    let game_plugins = game_path.join("BepInEx").join("plugins");
+    let profile_plugins = profile_dir.join(loader.mods_dir());
+    let game_plugins = game_path.join(loader.mods_dir());
     
     // Create set of enabled mod names (lowercase for comparison)
     let disabled_set: std::collections::HashSet<String> = disabled_mods.iter()
@@ -509,7 +658,8 @@ async fn install_to_game(app: AppHandle, game_identifier: String, profile_id: St
                     if is_disabled || not_in_profile {
                         eprintln!("[install_to_game] Removing mod from game (disabled={}, orphan={}): {}", 
                                   is_disabled, not_in_profile, folder_name);
-                        let _ = fs::remove_dir_all(entry.path());
+                        let scope = format!("{}/{}", loader.mods_dir(), folder_name);
+                        cleanup.merge(deployment::clean(&app, &game_identifier, game_path, &scope, |_| true)?);
                     }
                 }
             }
@@ -517,119 +667,167 @@ async fn install_to_game(app: AppHandle, game_identifier: String, profile_id: St
     }
     // --- END SYNC ---
 
-    // 3. Copy BepInEx structure with filtering for disabled mods
-    let source_bepinex = profile_dir.join("BepInEx");
-    let dest_bepinex = game_path.join("BepInEx");
-    
-    if source_bepinex.exists() {
-        // Create BepInEx dir if needed
-        if !dest_bepinex.exists() {
-            fs::create_dir_all(&dest_bepinex).map_err(|e| e.to_string())?;
+    // Move content older installs left in plugins/<mod>/ (patchers/, config/, ...) to where the install rules put it
+    let rules = loader.install_rules(&game_identifier);
+    if let Ok(entries) = fs::read_dir(&profile_plugins) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let folder_name = entry.file_name().to_string_lossy().to_string();
+            if loader.is_loader_package(&folder_name) || install_rules::load_record(&profile_dir, &folder_name).is_some() {
+                continue;
+            }
+            if let Some(record) = install_rules::relayout_plugin_folder(&profile_dir, &rules, &folder_name) {
+                let _ = install_rules::save_record(&profile_dir, &record);
+            }
         }
+    }
+    // 3. Copy the loader folders (BepInEx/, or MelonLoader's Mods/, UserLibs/, ...) with filtering for disabled mods
+    for managed in loader.managed_dirs() {
+        let source_dir = profile_dir.join(managed);
+        let dest_dir = game_path.join(managed);
+        let subdir_folders: Vec<&str> = rules.subdir_routes()
+            .filter_map(|route| route.strip_prefix(managed)?.strip_prefix('/'))
+            .collect();
+    
+        if source_dir.exists() {
+            // Create the folder if needed
+            if !dest_dir.exists() {
+                fs::create_dir_all(&dest_dir).map_err(|e| e.to_string())?;
+            }
         
-        // Copy everything except plugins (we'll handle that specially)
-        if let Ok(entries) = fs::read_dir(&source_bepinex) {
-            for entry in entries.filter_map(|e| e.ok()) {
-                let name = entry.file_name().to_string_lossy().to_string();
-                let src_path = entry.path();
-                let dst_path = dest_bepinex.join(&name);
+            // Copy everything except plugins (we'll handle that specially)
+            if let Ok(entries) = fs::read_dir(&source_dir) {
+                for entry in entries.filter_map(|e| e.ok()) {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let src_path = entry.path();
+                    let dst_path = dest_dir.join(&name);
                 
-                if name == "plugins" {
-                    // Handle plugins specially - use SYMLINKS to save disk space!
-                    if !dst_path.exists() {
-                        fs::create_dir_all(&dst_path).map_err(|e| e.to_string())?;
+                    if loader.config_dir().map(|dir| std::path::Path::new(managed).join(&name) == std::path::Path::new(dir)).unwrap_or(false) {
+                        // The profile's configs are mirrored below
+                        continue;
                     }
+                    if std::path::Path::new(managed).join(&name) == std::path::Path::new(loader.mods_dir()) {
+                        // Handle plugins specially - use SYMLINKS to save disk space!
+                        if !dst_path.exists() {
+                            fs::create_dir_all(&dst_path).map_err(|e| e.to_string())?;
+                        }
                     
-                    // First, clean up any plugins in destination that are no longer in source or are disabled
-                    if let Ok(dest_entries) = fs::read_dir(&dst_path) {
-                        for dest_entry in dest_entries.filter_map(|e| e.ok()) {
-                            let dest_plugin_name = dest_entry.file_name().to_string_lossy().to_string();
-                            let source_plugin_path = src_path.join(&dest_plugin_name);
-                            let is_disabled = disabled_set.iter().any(|d| dest_plugin_name.to_lowercase().contains(d));
+                        // First, clean up any plugins in destination that are no longer in source or are disabled
+                        if let Ok(dest_entries) = fs::read_dir(&dst_path) {
+                            for dest_entry in dest_entries.filter_map(|e| e.ok()) {
+                                let dest_plugin_name = dest_entry.file_name().to_string_lossy().to_string();
+                                let source_plugin_path = src_path.join(&dest_plugin_name);
+                                let is_disabled = disabled_set.iter().any(|d| dest_plugin_name.to_lowercase().contains(d));
                             
-                            // Remove if disabled or not in source
-                            if is_disabled || !source_plugin_path.exists() {
-                                let dest_plugin_path = dest_entry.path();
-                                if dest_plugin_path.is_symlink() {
-                                    let _ = fs::remove_file(&dest_plugin_path);
-                                } else if dest_plugin_path.is_dir() {
-                                    let _ = fs::remove_dir_all(&dest_plugin_path);
-                                } else {
-                                    let _ = fs::remove_file(&dest_plugin_path);
+                                // Remove if disabled or not in source
+                                if is_disabled || !source_plugin_path.exists() {
+                                    let scope = format!("{}/{}", loader.mods_dir(), dest_plugin_name);
+                                    cleanup.merge(deployment::clean(&app, &game_identifier, game_path, &scope, |_| true)?);
+                                    eprintln!("[install_to_game] Removed old/disabled plugin: {}", dest_plugin_name);
                                 }
-                                eprintln!("[install_to_game] Removed old/disabled plugin: {}", dest_plugin_name);
                             }
                         }
-                    }
                     
-                    // Now create symlinks for enabled plugins
-                    if let Ok(plugin_entries) = fs::read_dir(&src_path) {
-                        for plugin_entry in plugin_entries.filter_map(|e| e.ok()) {
-                            let plugin_name = plugin_entry.file_name().to_string_lossy().to_string();
+                        // Now create symlinks for enabled plugins
+                        if let Ok(plugin_entries) = fs::read_dir(&src_path) {
+                            for plugin_entry in plugin_entries.filter_map(|e| e.ok()) {
+                                let plugin_name = plugin_entry.file_name().to_string_lossy().to_string();
                             
-                            // Check if this plugin is disabled
-                            let is_disabled = disabled_set.iter().any(|d| plugin_name.to_lowercase().contains(d));
+                                // Check if this plugin is disabled
+                                let is_disabled = disabled_set.iter().any(|d| plugin_name.to_lowercase().contains(d));
                             
-                            if is_disabled {
-                                eprintln!("[install_to_game] Skipping disabled plugin: {}", plugin_name);
-                                continue;
-                            }
+                                if is_disabled {
+                                    eprintln!("[install_to_game] Skipping disabled plugin: {}", plugin_name);
+                                    continue;
+                                }
                             
-                            let plugin_dst = dst_path.join(&plugin_name);
-                            let plugin_src = plugin_entry.path();
+                                let plugin_dst = dst_path.join(&plugin_name);
+                                let plugin_src = plugin_entry.path();
                             
-                            // Remove existing file/dir/symlink if present
-                            if plugin_dst.exists() || plugin_dst.is_symlink() {
-                                if plugin_dst.is_symlink() {
-                                    let _ = fs::remove_file(&plugin_dst);
-                                } else if plugin_dst.is_dir() {
-                                    let _ = fs::remove_dir_all(&plugin_dst);
-                                } else {
-                                    let _ = fs::remove_file(&plugin_dst);
+                                // Replace what we placed there before; anything else stays
+                                if plugin_dst.exists() || plugin_dst.is_symlink() {
+                                    let scope = format!("{}/{}", loader.mods_dir(), plugin_name);
+                                    let report = deployment::clean(&app, &game_identifier, game_path, &scope, |_| true)?;
+                                    let blocked = !report.foreign.is_empty() || !report.modified.is_empty();
+                                    cleanup.merge(report);
+                                    if blocked {
+                                        eprintln!("[install_to_game] Not linking {}: the game folder has files there the manager didn't place", plugin_name);
+                                        continue;
+                                    }
                                 }
-                            }
                             
-                            // Create symlink instead of copying
-                            #[cfg(unix)]
-                            {
-                                std::os::unix::fs::symlink(&plugin_src, &plugin_dst)
-                                    .map_err(|e| format!("Failed to create symlink for {}: {}", plugin_name, e))?;
-                                eprintln!("[install_to_game] Created symlink: {} -> {:?}", plugin_name, plugin_src);
-                            }
+                                // Create symlink instead of copying
+                                #[cfg(unix)]
+                                {
+                                    std::os::unix::fs::symlink(&plugin_src, &plugin_dst)
+                                        .map_err(|e| format!("Failed to create symlink for {}: {}", plugin_name, e))?;
+                                    eprintln!("[install_to_game] Created symlink: {} -> {:?}", plugin_name, plugin_src);
+                                }
                             
-                            #[cfg(windows)]
-                            {
-                                // Fallback to copy on Windows (symlinks require admin)
-                                if plugin_src.is_dir() {
-                                    copy_dir_recursive(&plugin_src, &plugin_dst)
-                                        .map_err(|e| format!("Failed to copy plugin {}: {}", plugin_name, e))?;
-                                } else {
-                                    fs::copy(&plugin_src, &plugin_dst)
-                                        .map_err(|e| format!("Failed to copy plugin file {}: {}", plugin_name, e))?;
+                                #[cfg(windows)]
+                                {
+                                    // Fallback to copy on Windows (symlinks require admin)
+                                    if plugin_src.is_dir() {
+                                        copy_dir_recursive(&plugin_src, &plugin_dst)
+                                            .map_err(|e| format!("Failed to copy plugin {}: {}", plugin_name, e))?;
+                                    } else {
+                                        fs::copy(&plugin_src, &plugin_dst)
+                                            .map_err(|e| format!("Failed to copy plugin file {}: {}", plugin_name, e))?;
+                                    }
                                 }
                             }
                         }
-                    }
-                } else {
-                    // Copy other BepInEx folders normally
-                    if src_path.is_dir() {
-                        copy_dir_recursive(&src_path, &dst_path)
-                            .map_err(|e| format!("Failed to copy {}: {}", name, e))?;
+                    } else if subdir_folders.contains(&name.as_str()) && src_path.is_dir() {
+                        // Per-mod folders (patchers/<mod>, monomod/<mod>, ...): skip disabled mods
+                        if let Ok(children) = fs::read_dir(&src_path) {
+                            for child in children.filter_map(|e| e.ok()) {
+                                let child_name = child.file_name().to_string_lossy().to_string();
+                                let child_dst = dst_path.join(&child_name);
+                                if disabled_set.iter().any(|d| child_name.to_lowercase().contains(d)) {
+                                    let scope = format!("{}/{}/{}", managed, name, child_name);
+                                    cleanup.merge(deployment::clean(&app, &game_identifier, game_path, &scope, |_| true)?);
+                                    continue;
+                                }
+                                let result = if child.path().is_dir() {
+                                    copy_dir_recursive(&child.path(), &child_dst)
+                                } else {
+                                    fs::create_dir_all(&dst_path).and_then(|_| fs::copy(child.path(), &child_dst).map(|_| ()))
+                                };
+                                result.map_err(|e| format!("Failed to copy {}/{}: {}", name, child_name, e))?;
+                            }
+                        }
                     } else {
-                        if dst_path.exists() {
-                            let _ = fs::remove_file(&dst_path);
+                        // Copy other folders normally
+                        if src_path.is_dir() {
+                            copy_dir_recursive(&src_path, &dst_path)
+                                .map_err(|e| format!("Failed to copy {}: {}", name, e))?;
+                        } else {
+                            if dst_path.exists() {
+                                let _ = fs::remove_file(&dst_path);
+                            }
+                            fs::copy(&src_path, &dst_path)
+                                .map_err(|e| format!("Failed to copy {}: {}", name, e))?;
                         }
-                        fs::copy(&src_path, &dst_path)
-                            .map_err(|e| format!("Failed to copy {}: {}", name, e))?;
                     }
                 }
             }
+            eprintln!("[install_to_game] Synced {} to game folder", managed);
+        }
+    }
+
+    // Flat layouts can't drop a disabled mod's folder, so remove the files its install record lists
+    if !rules.nests_packages() {
+        for record in install_rules::list_records(&profile_dir) {
+            if disabled_set.iter().any(|d| record.package.to_lowercase().contains(d)) {
+                eprintln!("[install_to_game] Removing files of disabled mod: {}", record.package);
+                for file in &record.files {
+                    cleanup.merge(deployment::clean(&app, &game_identifier, game_path, &file.path, |_| true)?);
+                }
+            }
         }
-        eprintln!("[install_to_game] Synced BepInEx to game folder");
     }
 
-    // 4. Copy root files (doorstop_config.ini, winhttp.dll)
-    for item_name in ["doorstop_config.ini", "winhttp.dll"].iter() {
+    // 4. Copy root files (doorstop_config.ini, winhttp.dll, ...)
+    for item_name in loader.root_files() {
         let source = profile_dir.join(item_name);
         let dest = game_path.join(item_name);
         
@@ -642,8 +840,18 @@ async fn install_to_game(app: AppHandle, game_identifier: String, profile_id: St
         }
     }
 
+    // 5. Switch the game's configs to the profile's
+    profile_config::deploy(&app, &game_identifier, &profile_id, game_path)?;
+
+    if let Ok(profile) = profile_store::find_profile(&app, &profile_id) {
+        deployment::record(&app, &game_identifier, &profile, &game_path_str)?;
+    }
+
+    if !cleanup.foreign.is_empty() || !cleanup.modified.is_empty() {
+        eprintln!("[install_to_game] Left {} foreign and {} modified files in the game folder", cleanup.foreign.len(), cleanup.modified.len());
+    }
     eprintln!("[install_to_game] Sync complete!");
-    Ok(())
+    Ok(cleanup)
 }
 
 // Helper function for recursive directory copy with forced overwrite
@@ -719,31 +927,24 @@ async fn sync_profile_to_game(app: AppHandle, profile_id: String, game_identifie
     let game_path_str = get_game_path(app.clone(), game_identifier.clone()).await?
         .ok_or("Game path not configured. Please set it in Settings.")?;
     let game_path = std::path::Path::new(&game_path_str);
-    let game_plugins = game_path.join("BepInEx").join("plugins");
+    let loader = mod_loader::for_game(&game_identifier);
+    let game_plugins = game_path.join(loader.mods_dir());
     
     // Profile cache path
     let profile_dir = app.path().app_data_dir().map_err(|e| e.to_string())?
         .join("profiles").join(&profile_id);
-    let profile_plugins = profile_dir.join("BepInEx").join("plugins");
+    let profile_plugins = profile_dir.join(loader.mods_dir());
 
     eprintln!("[sync_profile_to_game] Syncing profile {} to game {:?} (legacy_cache: {})", profile_id, game_path, use_cache);
 
     // 2. Read profile mods from profiles.json
-    let profiles_path = app.path().app_data_dir().unwrap().join("profiles.json");
-    let profiles_data = fs::read_to_string(&profiles_path).map_err(|e| e.to_string())?;
-    let profiles: Vec<serde_json::Value> = serde_json::from_str(&profiles_data).map_err(|e| e.to_string())?;
-    
-    let profile = profiles.iter()
-        .find(|p| p["id"].as_str() == Some(&profile_id))
-        .ok_or("Profile not found")?;
+    let profile = profile_store::find_profile(&app, &profile_id)?;
     
     // Get list of mod names from profile (format: "Author-ModName-Version")
     // We keep the full name for matching
-    let profile_mod_full_names: Vec<String> = profile["mods"]
-        .as_array()
-        .unwrap_or(&vec![])
+    let profile_mod_full_names: Vec<String> = profile.mods
         .iter()
-        .filter_map(|m| m["fullName"].as_str().map(|s| s.to_string()))
+        .map(|m| m.full_name.clone())
         .collect();
     
     // Also create a set of "Author-ModName" keys for fuzzy matching
@@ -791,13 +992,13 @@ async fn sync_profile_to_game(app: AppHandle, profile_id: String, game_identifie
         .collect();
 
     // to_install: in profile but not in game (by key)
-    // Special case: BepInExPack installs to game root, not plugins - check if BepInEx folder exists
-    let bepinex_installed = game_path.join("BepInEx").join("core").exists();
+    // Special case: the loader package (e.g. BepInExPack) installs to game root, not plugins - check if the loader is there
+    let loader_installed = loader.is_installed(game_path);
     
     let to_install: Vec<&String> = profile_mod_keys.iter()
         .filter(|pm_key| {
-            // Skip BepInExPack if BepInEx is already installed
-            if pm_key.to_lowercase().contains("bepinex") && bepinex_installed {
+            // Skip the loader package if the loader is already installed
+            if loader.is_loader_package(pm_key) && loader_installed {
                 return false;
             }
             // Check if not already in game plugins
@@ -808,14 +1009,21 @@ async fn sync_profile_to_game(app: AppHandle, profile_id: String, game_identifie
     eprintln!("[sync_profile_to_game] To remove: {:?}, To install: {:?}", to_remove.len(), to_install.len());
 
     // 5. Remove mods not in profile (we have the exact folder names from the tuple)
+    // Only what the ledger says we placed is removed; anything else is reported
+    let rules = loader.install_rules(&game_identifier);
     let mut removed = 0;
+    let mut cleanup = deployment::CleanupReport::default();
     for (folder_name, _key) in &to_remove {
-        let folder_path = game_plugins.join(folder_name);
-        if folder_path.exists() {
-            eprintln!("[sync_profile_to_game] Removing: {}", folder_name);
-            let _ = fs::remove_dir_all(&folder_path);
+        eprintln!("[sync_profile_to_game] Removing: {}", folder_name);
+        let report = deployment::clean(&app, &game_identifier, game_path, &format!("{}/{}", loader.mods_dir(), folder_name), |_| true)?;
+        if !report.removed.is_empty() {
             removed += 1;
         }
+        cleanup.merge(report);
+        // Per-mod folders the install rules created outside plugins/
+        for route in rules.subdir_routes() {
+            cleanup.merge(deployment::clean(&app, &game_identifier, game_path, &format!("{}/{}", route, folder_name), |_| true)?);
+        }
     }
 
     // 6. If legacy cache enabled, copy mods from game to profile cache (reverse sync)
@@ -852,12 +1060,17 @@ async fn sync_profile_to_game(app: AppHandle, profile_id: String, game_identifie
     // 7. Return info about what needs to be installed (frontend will handle download)
     let to_install_names: Vec<String> = to_install.iter().map(|s| s.to_string()).collect();
     let already_installed = game_mod_folders.len() - removed;
+    // 8. Copy config changes made in-game back into the profile that was deployed, then switch configs
+    let config_sync = profile_config::deploy(&app, &game_identifier, &profile_id, game_path)?;
+    deployment::record(&app, &game_identifier, &profile, &game_path_str)?;
 
     Ok(serde_json::json!({
         "removed": removed,
         "to_install": to_install_names,
         "already_installed": already_installed,
-        "cached": cached
+        "cached": cached,
+        "config_sync": config_sync,
+        "cleanup": cleanup
     }))
 }
 
@@ -871,15 +1084,24 @@ pub fn run() {
         .manage(AppState {
             packages: Arc::new(Mutex::new(HashMap::new())),
         })
+        .manage(downloads::DownloadManager::default())
+        .manage(bepinex_log::LogTailers::default())
 
         .invoke_handler(tauri::generate_handler![
             get_profiles,
             save_profiles,
+            list_profiles_backups,
+            restore_profiles_backup,
+            list_profile_snapshots,
+            diff_profile_snapshots,
+            restore_profile_snapshot,
             fetch_communities,
             fetch_community_images,
             select_folder,
             select_file,
             install_mod,
+            install_mods,
+            cancel_downloads,
             import_profile_from_file,
             import_profile,
             open_mod_folder,
@@ -887,8 +1109,17 @@ pub fn run() {
             get_packages,
             get_available_categories,
             lookup_packages_by_names,
+            resolve_dependencies,
+            check_profile_updates,
+            update_profile_mods,
+            list_package_versions,
+            install_mod_version,
+            set_mod_pinned,
+            get_profile_lockfile,
+            save_profile_lockfile,
             fetch_package_by_name,
             delete_profile_folder,
+            compute_orphans,
             remove_mod,
             toggle_mod,
             check_directory_exists,
@@ -896,10 +1127,25 @@ pub fn run() {
             share_profile,
             get_settings,
             save_settings,
+            detect_steam_installations,
             get_game_path,
+            get_game_location,
             set_game_path,
             read_image,
             open_game_folder,
+            find_game_executable,
+            launch_game,
+            get_proton_status,
+            set_wine_launch,
+            start_log_tail,
+            stop_log_tail,
+            get_last_session_summary,
+            list_config_files,
+            read_config_file,
+            set_config_value,
+            reset_config_entry,
+            reset_config_file,
+            capture_game_config,
             install_to_game,
             confirm_dialog,
             alert_dialog,
@@ -909,6 +1155,11 @@ pub fn run() {
             sync_profile_to_game,
             copy_mod_from_cache,
             clear_profile_cache,
+            get_archive_cache_stats,
+            prune_archive_cache,
+            get_game_definition,
+            update_game_definitions,
+            get_steam_app_manifest,
         ])
         .setup(|app| {
             use chrono::Datelike;
@@ -933,6 +1184,8 @@ pub fn run() {
                 }
             }
 
+            game_defs::load(app.handle());
+
             // Clear chunks cache on startup to keep app light
             if let Ok(cache_dir) = app.path().app_cache_dir() {
                 let chunks_dir = cache_dir.join("chunks");
@@ -955,11 +1208,11 @@ pub fn run() {
                             for entry in entries.filter_map(|e| e.ok()) {
                                 let profile_path = entry.path();
                                 if profile_path.is_dir() {
-                                    // Remove BepInEx folder from profile (old cache)
+                                    // Remove BepInEx folder from profile (old cache), except the profile's configs
                                     let bepinex_path = profile_path.join("BepInEx");
                                     if bepinex_path.exists() {
                                         eprintln!("[startup] Cleaning old profile cache: {:?}", bepinex_path);
-                                        let _ = std::fs::remove_dir_all(&bepinex_path);
+                                        remove_profile_cache(&bepinex_path);
                                     }
                                     // Also remove winhttp.dll and doorstop_config.ini from profile
                                     let _ = std::fs::remove_file(profile_path.join("winhttp.dll"));
@@ -1165,147 +1418,123 @@ async fn read_image(path: String) -> Result<Option<String>, String> {
 }
 
 #[command]
-async fn install_mod(app: AppHandle, profile_id: String, download_url: String, mod_name: String, game_path: String, use_profile_cache: Option<bool>) -> Result<serde_json::Value, String> {
-    // Install DIRECTLY to game folder
-    let game_dir = std::path::Path::new(&game_path);
-    let plugins_dir = game_dir.join("BepInEx").join("plugins");
-    let mod_dir = plugins_dir.join(&mod_name);
+async fn install_mod(app: AppHandle, profile_id: String, download_url: String, mod_name: String, game_path: String, use_profile_cache: Option<bool>, expected_size: Option<u64>) -> Result<serde_json::Value, String> {
+    // Serve from the shared archive cache (keyed by "Author-Name-Version") or download
+    let item = downloads::DownloadItem { mod_name, download_url, expected_size };
+    let archive = downloads::fetch_archive(&app, &item, None).await.map_err(|e| e.to_string())?;
 
-    eprintln!("[install_mod] Installing {} directly to game: {:?}", mod_name, game_dir);
+    install_archive(&app, &profile_id, &item, &game_path, use_profile_cache.unwrap_or(false), archive.path())?;
+    Ok(serde_json::json!({ "success": true }))
+}
 
-    // Download
-    let response = reqwest::get(&download_url).await.map_err(|e| e.to_string())?;
-    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
-    
-    // Check if this is BepInExPack by looking for "BepInExPack" folder at root
-    let cursor = std::io::Cursor::new(&bytes);
-    let mut archive = zip::ZipArchive::new(cursor).map_err(|e| e.to_string())?;
-    let is_bepinex_pack = (0..archive.len()).any(|i| {
-        archive.by_index(i).ok().map(|f| f.name().starts_with("BepInExPack/")).unwrap_or(false)
-    });
+/// Download and install several mods through the download queue. Progress is
+/// reported as `download-progress` events tagged with `batch_id`.
+#[command]
+async fn install_mods(
+    app: AppHandle,
+    downloads_state: tauri::State<'_, downloads::DownloadManager>,
+    batch_id: String,
+    profile_id: String,
+    game_path: String,
+    items: Vec<downloads::DownloadItem>,
+    use_profile_cache: Option<bool>,
+) -> Result<Vec<downloads::ItemResult>, String> {
+    let install_app = app.clone();
+    let use_profile_cache = use_profile_cache.unwrap_or(false);
+    downloads::run_batch(
+        app,
+        &downloads_state,
+        batch_id,
+        items,
+        downloads::DEFAULT_CONCURRENCY,
+        move |item, archive| install_archive(&install_app, &profile_id, item, &game_path, use_profile_cache, archive),
+    ).await
+}
 
-    // Install to game folder
-    let cursor = std::io::Cursor::new(&bytes);
-    let mut archive = zip::ZipArchive::new(cursor).map_err(|e| e.to_string())?;
+/// Cancel one queued/running download, or the whole batch when `mod_name` is omitted
+#[command]
+fn cancel_downloads(downloads_state: tauri::State<'_, downloads::DownloadManager>, batch_id: String, mod_name: Option<String>) -> bool {
+    downloads_state.cancel(&batch_id, mod_name.as_deref())
+}
 
-    if is_bepinex_pack {
-        // Install BepInExPack to GAME root (not profile!)
-        eprintln!("[install_mod] Detected BepInExPack - installing to game root");
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
-            let name = file.name().to_string();
-            
-            if name.starts_with("BepInExPack/") {
-                // Strip "BepInExPack/" prefix
-                let relative_path = &name["BepInExPack/".len()..];
-                if relative_path.is_empty() { continue; }
-                
-                let outpath = game_dir.join(relative_path);
-                
-                if name.ends_with('/') {
-                    fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
-                } else {
-                    if let Some(p) = outpath.parent() {
-                        if !p.exists() {
-                            fs::create_dir_all(p).map_err(|e| e.to_string())?;
-                        }
-                    }
-                    let mut outfile = fs::File::create(&outpath).map_err(|e| e.to_string())?;
-                    std::io::copy(&mut file, &mut outfile).map_err(|e| e.to_string())?;
-                }
-            }
-        }
-    } else {
-        // Normal mod installation to game/BepInEx/plugins/{mod_name}
-        fs::create_dir_all(&mod_dir).map_err(|e| e.to_string())?;
-
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
-            let outpath = match file.enclosed_name() {
-                Some(path) => mod_dir.join(path),
-                None => continue,
-            };
+/// Extract a mod archive into the game folder (and the profile cache in legacy
+/// mode), after checking it against the profile's lockfile
+fn install_archive(app: &AppHandle, profile_id: &str, item: &downloads::DownloadItem, game_path: &str, use_profile_cache: bool, archive_path: &std::path::Path) -> Result<(), String> {
+    // Install DIRECTLY to game folder
+    let game_dir = std::path::Path::new(game_path);
+    let profile_dir = profile_store::profile_dir(app, profile_id)?;
+    let mod_name = item.mod_name.as_str();
+    let (locked_name, locked) = lockfile::verify(&profile_dir, mod_name, &item.download_url, archive_path)?;
 
-            if (*file.name()).ends_with('/') {
-                fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
-            } else {
-                if let Some(p) = outpath.parent() {
-                    if !p.exists() {
-                        fs::create_dir_all(p).map_err(|e| e.to_string())?;
-                    }
-                }
-                let mut outfile = fs::File::create(&outpath).map_err(|e| e.to_string())?;
-                std::io::copy(&mut file, &mut outfile).map_err(|e| e.to_string())?;
-            }
-        }
-    }
+    eprintln!("[install_mod] Installing {} directly to game: {:?}", mod_name, game_dir);
 
-    // LEGACY MODE: Also save to profile cache folder
-    if use_profile_cache.unwrap_or(false) {
-        let profile_dir = app.path().app_data_dir().map_err(|e| e.to_string())?
-            .join("profiles").join(&profile_id);
-        let profile_plugins_dir = profile_dir.join("BepInEx").join("plugins");
-        let profile_mod_dir = profile_plugins_dir.join(&mod_name);
+    let game_identifier = profile_store::find_profile(app, profile_id)
+        .map(|p| p.game_identifier)
+        .unwrap_or_default();
+    let (record, written) = extract_package(&game_identifier, mod_name, archive_path, game_dir)?;
+    if let Err(e) = install_rules::save_record(&profile_dir, &record) {
+        eprintln!("[install_mod] {}", e);
+    }
+    if let Err(e) = lockfile::record(&profile_dir, locked_name, locked) {
+        eprintln!("[install_mod] {}", e);
+    }
+    if let Err(e) = deployment::claim(app, &game_identifier, profile_id, game_dir, Some(mod_name), record.files.iter().map(|f| f.path.as_str())) {
+        eprintln!("[install_mod] {}", e);
+    }
 
+    // LEGACY MODE: Also save to profile cache folder, copied from the extracted files
+    if use_profile_cache {
         eprintln!("[install_mod] LEGACY: Also caching to profile: {:?}", profile_dir);
+        if let Err(e) = extract::copy_files(game_dir, &profile_dir, &written) {
+            eprintln!("[install_mod] ERROR caching to profile: {}", e);
+        }
+    }
 
-        let cursor = std::io::Cursor::new(&bytes);
-        let mut archive = zip::ZipArchive::new(cursor).map_err(|e| e.to_string())?;
+    eprintln!("[install_mod] Successfully installed {} to game folder", mod_name);
+    Ok(())
+}
 
-        if is_bepinex_pack {
-            // Cache BepInExPack to profile root
-            for i in 0..archive.len() {
-                let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
-                let name = file.name().to_string();
-                
-                if name.starts_with("BepInExPack/") {
-                    let relative_path = &name["BepInExPack/".len()..];
-                    if relative_path.is_empty() { continue; }
-                    
-                    let outpath = profile_dir.join(relative_path);
-                    
-                    if name.ends_with('/') {
-                        let _ = fs::create_dir_all(&outpath);
-                    } else {
-                        if let Some(p) = outpath.parent() {
-                            let _ = fs::create_dir_all(p);
-                        }
-                        if let Ok(mut outfile) = fs::File::create(&outpath) {
-                            let _ = std::io::copy(&mut file, &mut outfile);
-                        }
-                    }
-                }
-            }
-        } else {
-            // Cache normal mod to profile/BepInEx/plugins/{mod_name}
-            eprintln!("[install_mod] Creating profile cache dir: {:?}", profile_mod_dir);
-            if let Err(e) = fs::create_dir_all(&profile_mod_dir) {
-                eprintln!("[install_mod] ERROR creating profile cache dir: {}", e);
-            }
+/// Extract a mod archive into `root` (a game folder, or a staging folder laid
+/// out like one) and return the install record of the files written
+fn extract_package(game_identifier: &str, mod_name: &str, archive_path: &std::path::Path, root: &std::path::Path) -> Result<(install_rules::InstallRecord, Vec<extract::ExtractedFile>), String> {
+    let mut archive = extract::open_archive(archive_path)?;
+    let loader = mod_loader::for_game(game_identifier);
+    let is_loader_package = loader.is_loader_archive(&archive.file_names().collect::<Vec<_>>());
+
+    // The loader package (e.g. BepInExPack) goes to the GAME root (not profile!), other mods are routed by the install rules
+    let rules = loader.install_rules(game_identifier);
+    let written = if is_loader_package {
+        eprintln!("[install_mod] Detected {} package - installing to game root", loader.name());
+        extract::extract(&mut archive, root, |entry| loader.loader_package_target(entry))?
+    } else {
+        extract::extract(&mut archive, root, |entry| Some(rules.place(mod_name, entry).0))?
+    };
 
-            for i in 0..archive.len() {
-                let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
-                let outpath = match file.enclosed_name() {
-                    Some(path) => profile_mod_dir.join(path),
-                    None => continue,
-                };
+    let record = install_rules::InstallRecord {
+        package: mod_name.to_string(),
+        files: written.iter()
+            .map(|f| install_rules::PlacedFile {
+                path: f.target.to_string_lossy().replace('\\', "/"),
+                rule: if is_loader_package { "loader" } else { rules.place(mod_name, &f.entry).1 }.to_string(),
+            })
+            .collect(),
+    };
+    Ok((record, written))
+}
 
-                if (*file.name()).ends_with('/') {
-                    let _ = fs::create_dir_all(&outpath);
-                } else {
-                    if let Some(p) = outpath.parent() {
-                        let _ = fs::create_dir_all(p);
-                    }
-                    if let Ok(mut outfile) = fs::File::create(&outpath) {
-                        let _ = std::io::copy(&mut file, &mut outfile);
-                    }
-                }
-            }
-        }
-    }
+#[command]
+async fn get_archive_cache_stats(app: AppHandle) -> Result<archive_cache::CacheStats, String> {
+    let cache = archive_cache::ArchiveCache::open(&app)?;
+    let quota_bytes = load_settings_impl(&app).archive_cache_quota_mb.map(|mb| mb * 1024 * 1024);
+    Ok(cache.stats(quota_bytes))
+}
 
-    eprintln!("[install_mod] Successfully installed {} to game folder", mod_name);
-    Ok(serde_json::json!({ "success": true }))
+/// Prune archives unused for `unused_for_days`, then down to `max_mb` (defaults to the quota setting)
+#[command]
+async fn prune_archive_cache(app: AppHandle, unused_for_days: Option<u64>, max_mb: Option<u64>) -> Result<archive_cache::PruneResult, String> {
+    let cache = archive_cache::ArchiveCache::open(&app)?;
+    let max_mb = max_mb.or(load_settings_impl(&app).archive_cache_quota_mb);
+    Ok(cache.prune(unused_for_days, max_mb.map(|mb| mb * 1024 * 1024)))
 }
 
 /// Copy a mod from profile cache to game folder (for instant profile switching in legacy mode)
@@ -1313,11 +1542,32 @@ async fn install_mod(app: AppHandle, profile_id: String, download_url: String, m
 async fn copy_mod_from_cache(app: AppHandle, profile_id: String, mod_name: String, game_path: String) -> Result<serde_json::Value, String> {
     let profile_dir = app.path().app_data_dir().map_err(|e| e.to_string())?
         .join("profiles").join(&profile_id);
-    let profile_plugins_dir = profile_dir.join("BepInEx").join("plugins");
+    let game_identifier = profile_store::find_profile(&app, &profile_id)
+        .map(|p| p.game_identifier)
+        .unwrap_or_default();
+    let loader = mod_loader::for_game(&game_identifier);
+    let profile_plugins_dir = profile_dir.join(loader.mods_dir());
     
     let game_dir = std::path::Path::new(&game_path);
-    let game_plugins_dir = game_dir.join("BepInEx").join("plugins");
-    
+    let game_plugins_dir = game_dir.join(loader.mods_dir());
+    
+    // Files placed by the install rules are copied back to the same places
+    if let Some(record) = install_rules::load_record(&profile_dir, &mod_name) {
+        let cached = !record.files.is_empty() && record.files.iter().all(|f| profile_dir.join(&f.path).exists());
+        if cached {
+            eprintln!("[copy_mod_from_cache] Copying {} ({} files) from cache to game", mod_name, record.files.len());
+            for file in &record.files {
+                let dst_path = game_dir.join(&file.path);
+                if let Some(parent) = dst_path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                fs::copy(profile_dir.join(&file.path), &dst_path).map_err(|e| format!("Failed to copy {}: {}", file.path, e))?;
+            }
+            deployment::claim(&app, &game_identifier, &profile_id, game_dir, Some(&mod_name), record.files.iter().map(|f| f.path.as_str()))?;
+            return Ok(serde_json::json!({ "success": true, "copied": true }));
+        }
+    }
+
     // Find the mod folder in profile cache (case insensitive partial match)
     let mod_name_lower = mod_name.to_lowercase();
     
@@ -1336,15 +1586,24 @@ async fn copy_mod_from_cache(app: AppHandle, profile_id: String, mod_name: Strin
                     // Ensure target dir exists
                     fs::create_dir_all(&game_plugins_dir).map_err(|e| e.to_string())?;
                     
-                    // Remove existing if present
-                    if dst_path.exists() {
-                        let _ = fs::remove_dir_all(&dst_path);
+                    // Remove what we placed there before; anything else stays and is reported
+                    let mut cleanup = deployment::CleanupReport::default();
+                    if dst_path.exists() || dst_path.is_symlink() {
+                        let scope = format!("{}/{}", loader.mods_dir(), folder_name);
+                        cleanup = deployment::clean(&app, &game_identifier, game_dir, &scope, |_| true)?;
+                        if !cleanup.foreign.is_empty() || !cleanup.modified.is_empty() {
+                            eprintln!("[copy_mod_from_cache] Not copying {}: the game folder has files there the manager didn't place", folder_name);
+                            return Ok(serde_json::json!({ "success": false, "copied": false, "cleanup": cleanup }));
+                        }
                     }
                     
-                    // Copy
+                    // Copy, then route anything misplaced inside it
                     copy_dir_recursive(&src_path, &dst_path).map_err(|e| e.to_string())?;
+                    if let Some(record) = install_rules::relayout_plugin_folder(game_dir, &loader.install_rules(&game_identifier), &folder_name) {
+                        deployment::claim(&app, &game_identifier, &profile_id, game_dir, Some(&folder_name), record.files.iter().map(|f| f.path.as_str()))?;
+                    }
                     
-                    return Ok(serde_json::json!({ "success": true, "copied": true }));
+                    return Ok(serde_json::json!({ "success": true, "copied": true, "cleanup": cleanup }));
                 }
             }
         }
@@ -1359,11 +1618,11 @@ async fn copy_mod_from_cache(app: AppHandle, profile_id: String, mod_name: Strin
 #[command]
 async fn open_mod_folder(app: AppHandle, _profile_id: String, mod_name: String, game_identifier: String) -> Result<(), String> {
     // Open mod folder in GAME directory (not cache!)
-    let game_path_str = get_game_path(app.clone(), game_identifier).await?
+    let game_path_str = get_game_path(app.clone(), game_identifier.clone()).await?
         .ok_or("Game path not configured. Please set it in Settings.")?;
     
     let game_path = std::path::Path::new(&game_path_str);
-    let plugins_dir = game_path.join("BepInEx").join("plugins");
+    let plugins_dir = game_path.join(mod_loader::for_game(&game_identifier).mods_dir());
     
     if plugins_dir.exists() {
         for entry in walkdir::WalkDir::new(&plugins_dir)
@@ -1791,6 +2050,183 @@ async fn lookup_packages_by_names(
     }
 }
 
+/// Resolve the full dependency closure of a package ("Author-Name" or "Author-Name-1.2.3"),
+/// a whole profile, or a package added on top of a profile
+#[command]
+async fn resolve_dependencies(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    game_id: String,
+    package: Option<String>,
+    profile_id: Option<String>
+) -> Result<resolver::Resolution, String> {
+    let profile_mods = match profile_id {
+        Some(id) => profile_store::find_profile(&app, &id)?.mods,
+        None => vec![],
+    };
+
+    let mut roots = Vec::new();
+    if let Some(spec) = &package {
+        roots.push(resolver::Root::parse(spec, resolver::REQUESTED));
+    }
+    roots.extend(profile_mods.iter().map(resolver::Root::from_installed));
+    if roots.is_empty() {
+        return Err("Nothing to resolve: pass a package or a profile".to_string());
+    }
+
+    let packages_lock = state.packages.lock().map_err(|_| "Failed to lock state".to_string())?;
+    let packages = packages_lock.get(&game_id).ok_or("Game packages not loaded")?;
+    let index = package_index::PackageIndex::new(packages);
+    let resolution = resolver::resolve(&index, &roots, &profile_mods);
+
+    eprintln!("[resolve_dependencies] {} packages, {} conflicts, {} missing",
+        resolution.packages.len(), resolution.conflicts.len(), resolution.missing.len());
+    Ok(resolution)
+}
+
+/// Current vs latest version of each mod in a profile
+#[command]
+async fn check_profile_updates(app: AppHandle, state: tauri::State<'_, AppState>, profile_id: String) -> Result<Vec<updates::ModUpdate>, String> {
+    let profile = profile_store::find_profile(&app, &profile_id)?;
+    let packages_lock = state.packages.lock().map_err(|_| "Failed to lock state".to_string())?;
+    let packages = packages_lock.get(&profile.game_identifier).ok_or("Game packages not loaded")?;
+    let checked = updates::check(&package_index::PackageIndex::new(packages), &profile);
+    eprintln!("[check_profile_updates] {} of {} mods have updates", checked.iter().filter(|u| u.update_available).count(), checked.len());
+    Ok(checked)
+}
+
+/// Update the given mods ("Author-Name") of a profile to their latest
+/// versions. Pinned mods are skipped. Progress is reported as
+/// `download-progress` events tagged with `batch_id`.
+#[command]
+async fn update_profile_mods(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    downloads_state: tauri::State<'_, downloads::DownloadManager>,
+    batch_id: String,
+    profile_id: String,
+    packages: Vec<String>,
+) -> Result<updates::UpdateOutcome, String> {
+    let profile = profile_store::find_profile(&app, &profile_id)?;
+    let mut targets = Vec::new();
+    let mut skipped = Vec::new();
+    {
+        let packages_lock = state.packages.lock().map_err(|_| "Failed to lock state".to_string())?;
+        let listing = packages_lock.get(&profile.game_identifier).ok_or("Game packages not loaded")?;
+        let index = package_index::PackageIndex::new(listing);
+        for name in &packages {
+            let skip = |reason: &str| updates::SkippedMod { package: name.clone(), reason: reason.to_string() };
+            let Some(installed) = profile.mods.iter().find(|m| m.package_name().eq_ignore_ascii_case(name)) else {
+                skipped.push(skip("not in the profile"));
+                continue;
+            };
+            if installed.pinned {
+                skipped.push(skip("pinned"));
+                continue;
+            }
+            let package = index.get(name);
+            let latest = package.and_then(package_index::PackageIndex::latest);
+            match package.zip(latest).and_then(|(p, v)| updates::Target::new(p, &installed.full_name, v)) {
+                Some(target) if package_index::compare_versions(&target.version, &installed.version_number).is_gt() => targets.push(target),
+                Some(_) => skipped.push(skip("already up to date")),
+                None => skipped.push(skip("not found in the package listing")),
+            }
+        }
+    }
+
+    let mut outcome = if targets.is_empty() {
+        updates::UpdateOutcome::default()
+    } else {
+        updates::switch_versions(&app, &downloads_state, batch_id, &profile_id, targets).await?
+    };
+    outcome.skipped = skipped;
+    Ok(outcome)
+}
+
+/// Every listed version of a package ("Author-Name"), highest first. With a
+/// profile, the version it has installed is flagged.
+#[command]
+async fn list_package_versions(app: AppHandle, state: tauri::State<'_, AppState>, game_id: String, package: String, profile_id: Option<String>) -> Result<Vec<package_index::PackageVersion>, String> {
+    let installed = match profile_id {
+        Some(id) => profile_store::find_profile(&app, &id)?.mods.into_iter()
+            .find(|m| m.package_name().eq_ignore_ascii_case(&package))
+            .map(|m| m.version_number),
+        None => None,
+    };
+    let packages_lock = state.packages.lock().map_err(|_| "Failed to lock state".to_string())?;
+    let listing = packages_lock.get(&game_id).ok_or("Game packages not loaded")?;
+    let index = package_index::PackageIndex::new(listing);
+    let listed = index.get(&package).ok_or_else(|| format!("{} not found in the package listing", package))?;
+    Ok(package_index::PackageIndex::version_list(listed, installed.as_deref()))
+}
+
+/// Switch a profile's copy of a mod ("Author-Name") to `version`, newer or
+/// older, replacing the installed files. This is an explicit choice, so it
+/// applies to pinned mods too and leaves the pin as it is.
+#[command]
+async fn install_mod_version(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    downloads_state: tauri::State<'_, downloads::DownloadManager>,
+    batch_id: String,
+    profile_id: String,
+    package: String,
+    version: String,
+) -> Result<updates::UpdateOutcome, String> {
+    let profile = profile_store::find_profile(&app, &profile_id)?;
+    let installed = profile.mods.iter()
+        .find(|m| m.package_name().eq_ignore_ascii_case(&package))
+        .ok_or_else(|| format!("{} is not in the profile, install it first", package))?;
+
+    if installed.version_number == version {
+        return Ok(updates::UpdateOutcome {
+            skipped: vec![updates::SkippedMod { package: package.clone(), reason: "already installed".to_string() }],
+            ..Default::default()
+        });
+    }
+    let target = {
+        let packages_lock = state.packages.lock().map_err(|_| "Failed to lock state".to_string())?;
+        let listing = packages_lock.get(&profile.game_identifier).ok_or("Game packages not loaded")?;
+        let index = package_index::PackageIndex::new(listing);
+        let listed = index.get(&package).ok_or_else(|| format!("{} not found in the package listing", package))?;
+        package_index::PackageIndex::version(listed, &version)
+            .and_then(|v| updates::Target::new(listed, &installed.full_name, v))
+            .ok_or_else(|| format!("{} has no version {}", package, version))?
+    };
+    updates::switch_versions(&app, &downloads_state, batch_id, &profile_id, vec![target]).await
+}
+
+/// Pin a mod ("Author-Name") to its current version, or unpin it
+#[command]
+async fn set_mod_pinned(app: AppHandle, profile_id: String, package: String, pinned: bool) -> Result<Profile, String> {
+    let mut profiles = profile_store::load_profiles(&app)?;
+    let profile = profiles.iter_mut().find(|p| p.id == profile_id).ok_or("Profile not found")?;
+    let installed = profile.mods.iter_mut()
+        .find(|m| m.package_name().eq_ignore_ascii_case(&package))
+        .ok_or_else(|| format!("{} is not in the profile", package))?;
+    installed.pinned = pinned;
+    let profile = profile.clone();
+    profile_store::save_profiles(&app, &profiles)?;
+    Ok(profile)
+}
+
+/// Lockfile entries of the profile's current mods
+#[command]
+async fn get_profile_lockfile(app: AppHandle, profile_id: String) -> Result<lockfile::Lockfile, String> {
+    let profile = profile_store::find_profile(&app, &profile_id)?;
+    let profile_dir = profile_store::profile_dir(&app, &profile_id)?;
+    Ok(lockfile::load(&profile_dir).for_profile(&profile))
+}
+
+/// Replace a profile's lockfile, e.g. with the one of an imported profile
+/// before its mods are installed
+#[command]
+async fn save_profile_lockfile(app: AppHandle, profile_id: String, lockfile: lockfile::Lockfile) -> Result<(), String> {
+    let profile_dir = profile_store::profile_dir(&app, &profile_id)?;
+    fs::create_dir_all(&profile_dir).map_err(|e| e.to_string())?;
+    lockfile::save(&profile_dir, &lockfile)
+}
+
 #[command]
 async fn fetch_package_by_name(state: tauri::State<'_, AppState>, name: String, game_id: Option<String>) -> Result<Option<serde_json::Value>, String> {
     // name might be "Namespace-Name" or "Namespace-Name-Version"
@@ -1875,7 +2311,7 @@ fn clean_mod_name(name: &str, version: &str) -> String {
     name.to_string()
 }
 
-fn process_zip_archive(mut archive: zip::ZipArchive<std::io::Cursor<Vec<u8>>>) -> Result<serde_json::Value, String> {
+fn process_zip_archive<R: std::io::Read + std::io::Seek>(mut archive: zip::ZipArchive<R>) -> Result<serde_json::Value, String> {
     eprintln!("[process_zip_archive] Processing zip with {} files", archive.len());
     
     let mut content = String::new();
@@ -1952,135 +2388,151 @@ fn process_zip_archive(mut archive: zip::ZipArchive<std::io::Cursor<Vec<u8>>>) -
         })
     }).collect::<Vec<_>>();
 
+    // Lockfile written by r2modmac exports, if any
+    let mut lock_content = String::new();
+    let lockfile = match archive.by_name(lockfile::LOCKFILE) {
+        Ok(mut file) => {
+            use std::io::Read;
+            file.read_to_string(&mut lock_content).map_err(|e| e.to_string())?;
+            Some(lockfile::Lockfile::parse(&lock_content)?)
+        }
+        Err(_) => None,
+    };
+
     let result = serde_json::json!({
         "type": "profile",
         "name": profile_name,
-        "mods": mods
+        "mods": mods,
+        "lockfile": lockfile
     });
     
     eprintln!("[process_zip_archive] Final result: {:?}", result);
     Ok(result)
 }
 
+#[derive(Debug, Serialize)]
+struct DeleteProfileResult {
+    deleted: bool,
+    /// What was removed from (and left in) the game folder
+    cleanup: Option<deployment::CleanupReport>,
+}
+
 #[command]
-async fn delete_profile_folder(app: AppHandle, profile_id: String, game_identifier: Option<String>) -> Result<bool, String> {
+async fn delete_profile_folder(app: AppHandle, profile_id: String, game_identifier: Option<String>) -> Result<DeleteProfileResult, String> {
     let profile_dir = app.path().app_data_dir().unwrap().join("profiles").join(&profile_id);
+    let mut cleanup = None;
     
-    // If game_identifier is provided, clean up ALL BepInEx-related files from the game folder
+    // If game_identifier is provided, remove what this profile deployed (BepInEx/, winhttp.dll, ...) from the game folder
     if let Some(game_id) = game_identifier {
-        if let Ok(Some(game_path_str)) = get_game_path(app.clone(), game_id).await {
+        if let Ok(Some(game_path_str)) = get_game_path(app.clone(), game_id.clone()).await {
             let game_path = std::path::Path::new(&game_path_str);
+            let loader = mod_loader::for_game(&game_id);
+            let mut report = deployment::CleanupReport::default();
             
-            // Remove BepInEx folder
-            let bepinex_path = game_path.join("BepInEx");
-            if bepinex_path.exists() {
-                eprintln!("[delete_profile] Removing BepInEx folder from game");
-                let _ = fs::remove_dir_all(&bepinex_path);
-            }
-            
-            // Remove winhttp.dll
-            let winhttp_path = game_path.join("winhttp.dll");
-            if winhttp_path.exists() {
-                eprintln!("[delete_profile] Removing winhttp.dll from game");
-                let _ = fs::remove_file(&winhttp_path);
+            for scope in loader.managed_dirs().iter().chain(loader.root_files()) {
+                report.merge(deployment::clean(&app, &game_id, game_path, scope, |entry| entry.profile_id == profile_id)?);
             }
+            deployment::forget(&app, &game_id, &profile_id)?;
             
-            // Remove doorstop_config.ini
-            let doorstop_path = game_path.join("doorstop_config.ini");
-            if doorstop_path.exists() {
-                eprintln!("[delete_profile] Removing doorstop_config.ini from game");
-                let _ = fs::remove_file(&doorstop_path);
-            }
-            
-            eprintln!("[delete_profile] Cleaned up game folder: {}", game_path.display());
+            eprintln!("[delete_profile] Cleaned up game folder {}: {} removed, {} foreign, {} modified",
+                game_path.display(), report.removed.len(), report.foreign.len(), report.modified.len());
+            cleanup = Some(report);
         }
     }
     
     // Delete the profile folder
-    if profile_dir.exists() {
+    let deleted = profile_dir.exists();
+    if deleted {
         fs::remove_dir_all(profile_dir).map_err(|e| e.to_string())?;
-        Ok(true)
-    } else {
-        Ok(false)
     }
+    Ok(DeleteProfileResult { deleted, cleanup })
 }
 
 #[command]
 async fn toggle_mod(app: AppHandle, profile_id: String, mod_name: String, enabled: bool, game_identifier: Option<String>) -> Result<(), String> {
     eprintln!("[toggle_mod] Toggle mod: {} enabled: {} in profile: {}", mod_name, enabled, profile_id);
+    let profile = profile_store::find_profile(&app, &profile_id)?;
+    let loader = mod_loader::for_game(&profile.game_identifier);
     
     // Get game path for sync (optional - toggle still works without it)
-    let game_plugins = if let Some(ref game_id) = game_identifier {
+    let game = if let Some(ref game_id) = game_identifier {
         if let Ok(Some(game_path_str)) = get_game_path(app.clone(), game_id.clone()).await {
-            Some(std::path::Path::new(&game_path_str).to_path_buf().join("BepInEx").join("plugins"))
+            Some((game_id.clone(), std::path::PathBuf::from(game_path_str)))
         } else {
             None
         }
     } else {
         None
     };
+    let game_mods_dir = game.as_ref().map(|(_, root)| root.join(loader.mods_dir()));
     
     // Get profile cache path (may or may not exist depending on legacy mode)
-    let profile_dir = app.path().app_data_dir().map_err(|e| e.to_string())?
-        .join("profiles").join(&profile_id);
-    let profile_plugins_dir = profile_dir.join("BepInEx").join("plugins");
-    
-    // Find mod in profile cache OR game folder
-    let mod_name_lower = mod_name.to_lowercase();
-    let mut found_folder_name: Option<String> = None;
-    
-    // Try profile cache first
-    if profile_plugins_dir.exists() {
-        if let Ok(entries) = fs::read_dir(&profile_plugins_dir) {
-            for entry in entries.filter_map(|e| e.ok()) {
-                let folder_name = entry.file_name().to_string_lossy().to_string();
-                if folder_name.to_lowercase().contains(&mod_name_lower) && entry.path().is_dir() {
-                    found_folder_name = Some(folder_name);
-                    break;
-                }
-            }
-        }
-    }
-    
-    // If not found in profile cache, try game folder
-    if found_folder_name.is_none() {
-        if let Some(ref game_plugins_path) = game_plugins {
-            if game_plugins_path.exists() {
-                if let Ok(entries) = fs::read_dir(game_plugins_path) {
-                    for entry in entries.filter_map(|e| e.ok()) {
-                        let folder_name = entry.file_name().to_string_lossy().to_string();
-                        if folder_name.to_lowercase().contains(&mod_name_lower) && entry.path().is_dir() {
-                            found_folder_name = Some(folder_name);
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-    }
+    let profile_dir = profile_store::profile_dir(&app, &profile_id)?;
+    let profile_mods_dir = profile_dir.join(loader.mods_dir());
+    
+    // mod_name is "Author-Name"; folders are "Author-Name-Version", and "Author-Lib"
+    // must not match "Author-Lib-Extended-1.0.0"
+    let find_folder = |dir: &std::path::Path| -> Option<String> {
+        fs::read_dir(dir).ok()?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .find(|folder_name| package_index::split_versioned_name(folder_name).0.eq_ignore_ascii_case(&mod_name))
+    };
+    let toggled = profile.mods.iter().find(|m| m.package_name().eq_ignore_ascii_case(&mod_name));
     
     // If we have a game folder, sync the mod state
-    if let Some(ref game_plugins_path) = game_plugins {
-        if let Some(ref folder_name) = found_folder_name {
-            let game_mod_path = game_plugins_path.join(folder_name);
-            let profile_mod_path = profile_plugins_dir.join(folder_name);
-            
-            if enabled {
-                // Need to add mod to game - copy from profile cache if available
-                if profile_mod_path.exists() && !game_mod_path.exists() {
-                    eprintln!("[toggle_mod] Enabling mod - copying from cache to game: {}", folder_name);
-                    copy_dir_recursive(&profile_mod_path, &game_mod_path)
-                        .map_err(|e| format!("Failed to sync mod to game: {}", e))?;
+    if let (Some(game_mods_path), Some((game_id, game_root)), Some(toggled)) = (&game_mods_dir, &game, toggled) {
+        if enabled {
+            // Copy the mod's files from the profile cache, wherever its install record put them
+            let files: Vec<String> = match install_rules::load_record(&profile_dir, &toggled.full_name) {
+                Some(record) => record.files.into_iter().map(|f| f.path).collect(),
+                // Installed before records were kept: its folder under the mods folder
+                None => find_folder(&profile_mods_dir).map(|folder_name| {
+                    walkdir::WalkDir::new(profile_mods_dir.join(&folder_name)).into_iter()
+                        .filter_map(|e| e.ok())
+                        .filter(|e| e.file_type().is_file())
+                        .filter_map(|e| Some(e.path().strip_prefix(&profile_dir).ok()?.to_string_lossy().replace('\\', "/")))
+                        .collect()
+                }).unwrap_or_default(),
+            };
+            let mut copied = Vec::new();
+            for relative in files {
+                let source = profile_dir.join(&relative);
+                let dest = game_root.join(&relative);
+                // Files already in the game (configs the player kept, a folder still linked) stay
+                if !source.is_file() || fs::symlink_metadata(&dest).is_ok() {
+                    continue;
                 }
-            } else {
-                // Remove mod from game folder (keep in cache)
-                if game_mod_path.exists() {
-                    eprintln!("[toggle_mod] Disabling mod - removing from game: {}", folder_name);
-                    fs::remove_dir_all(&game_mod_path)
-                        .map_err(|e| format!("Failed to remove mod from game: {}", e))?;
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent).map_err(|e| format!("Failed to sync mod to game: {}", e))?;
                 }
+                fs::copy(&source, &dest).map_err(|e| format!("Failed to sync mod to game: {}", e))?;
+                copied.push(relative);
+            }
+            if !copied.is_empty() {
+                eprintln!("[toggle_mod] Enabling mod - copied {} files from cache to game: {}", copied.len(), toggled.full_name);
+                deployment::claim(&app, game_id, &profile_id, game_root, Some(&toggled.full_name), copied.iter().map(String::as_str))?;
+            }
+        } else {
+            // Remove everything the mod placed in the game folder (plugins, patchers,
+            // core, ...), keeping the cache and anything changed since it was placed
+            eprintln!("[toggle_mod] Disabling mod - removing from game: {}", toggled.full_name);
+            let owned = |entry: &deployment::LedgerEntry| entry.package.as_deref()
+                .map(|p| package_index::split_versioned_name(p).0.eq_ignore_ascii_case(&mod_name))
+                .unwrap_or(false);
+            let mut report = deployment::remove_owned(&app, game_id, game_root, owned)?;
+            // A folder the ledger doesn't name the mod in (e.g. a link from an older deploy)
+            if let Some(folder_name) = find_folder(game_mods_path) {
+                report.merge(deployment::clean(&app, game_id, game_root, &format!("{}/{}", loader.mods_dir(), folder_name), |_| true)?);
+            }
+            if !report.foreign.is_empty() || !report.modified.is_empty() {
+                eprintln!("[toggle_mod] Left {} files of {} the manager didn't place or that changed", report.foreign.len() + report.modified.len(), toggled.full_name);
             }
         }
+
+        // The game now differs from the last deploy by this one mod
+        deployment::set_mod_enabled(&app, game_id, &profile_id, &toggled.full_name, enabled)?;
     }
     
     // Always succeed - the enabled state is tracked in profiles.json, not file system
@@ -2103,12 +2555,12 @@ async fn clear_profile_cache(app: AppHandle) -> Result<serde_json::Value, String
                 if entry.path().is_dir() {
                     let bepinex_dir = entry.path().join("BepInEx");
                     if bepinex_dir.exists() {
-                        // Calculate size before deleting
+                        // Calculate size before deleting (configs are kept)
                         if let Ok(size) = calculate_dir_size(&bepinex_dir) {
-                            size_freed += size;
+                            size_freed += size.saturating_sub(calculate_dir_size(&bepinex_dir.join("config")).unwrap_or(0));
                         }
                         eprintln!("[clear_profile_cache] Removing: {:?}", bepinex_dir);
-                        let _ = fs::remove_dir_all(&bepinex_dir);
+                        remove_profile_cache(&bepinex_dir);
                         cleared += 1;
                     }
                 }
@@ -2124,6 +2576,20 @@ async fn clear_profile_cache(app: AppHandle) -> Result<serde_json::Value, String
     }))
 }
 
+/// Remove a profile's cached `BepInEx` folder but keep its `config` tree,
+/// which the profile owns
+fn remove_profile_cache(bepinex_dir: &std::path::Path) {
+    if let Ok(entries) = fs::read_dir(bepinex_dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            if entry.file_name() == "config" {
+                continue;
+            }
+            let path = entry.path();
+            let _ = if path.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+        }
+    }
+}
+
 /// Calculate directory size recursively
 fn calculate_dir_size(path: &std::path::Path) -> std::io::Result<u64> {
     let mut size = 0;
@@ -2149,16 +2615,9 @@ fn check_directory_exists(path: String) -> bool {
 #[command]
 async fn import_profile_from_file(_app: AppHandle, path: String) -> Result<serde_json::Value, String> {
     eprintln!("[import_profile_from_file] Starting import from file: {}", path);
-    let bytes = fs::read(&path).map_err(|e| {
-        eprintln!("[import_profile_from_file] Failed to read file: {}", e);
-        e.to_string()
-    })?;
-    eprintln!("[import_profile_from_file] Read {} bytes", bytes.len());
-    
-    let cursor = std::io::Cursor::new(bytes);
-    let archive = zip::ZipArchive::new(cursor).map_err(|e| {
-        eprintln!("[import_profile_from_file] Failed to create zip archive: {}", e);
-        e.to_string()
+    let archive = extract::open_archive(std::path::Path::new(&path)).map_err(|e| {
+        eprintln!("[import_profile_from_file] Failed to open zip archive: {}", e);
+        e
     })?;
     
     eprintln!("[import_profile_from_file] Zip archive created, processing...");
@@ -2253,96 +2712,167 @@ async fn import_profile(_app: AppHandle, code: String) -> Result<serde_json::Val
     }))
 }
 
+#[derive(Debug, Serialize)]
+struct RemoveModResult {
+    removed: bool,
+    /// Package names whose folders were removed from the profile cache
+    removed_mods: Vec<String>,
+    failed: Vec<String>,
+    /// Updated profile when a plan was applied (profiles.json is saved by the backend)
+    profile: Option<Profile>,
+    /// What was removed from the game folder, if the profile is deployed there
+    game_cleanup: Option<deployment::CleanupReport>,
+}
+
+/// Remove the files the mods ("Author-Name") placed in the game folder, if
+/// the profile is the one deployed there
+fn remove_from_deployed_game(app: &AppHandle, profile_id: &str, packages: &[String]) -> Result<Option<deployment::CleanupReport>, String> {
+    let profile = profile_store::find_profile(app, profile_id)?;
+    let Some(deployed) = deployment::get(app, &profile.game_identifier).filter(|d| d.profile_id == profile_id) else {
+        return Ok(None);
+    };
+    let keys: std::collections::HashSet<String> = packages.iter().map(|p| p.to_lowercase()).collect();
+    let owned = |entry: &deployment::LedgerEntry| entry.package.as_deref()
+        .map(|p| keys.contains(&package_index::split_versioned_name(p).0.to_lowercase()))
+        .unwrap_or(false);
+    let report = deployment::remove_owned(app, &profile.game_identifier, std::path::Path::new(&deployed.game_path), owned)?;
+    for m in profile.mods.iter().filter(|m| keys.contains(&m.package_name().to_lowercase())) {
+        deployment::set_mod_enabled(app, &profile.game_identifier, profile_id, &m.full_name, false)?;
+    }
+    eprintln!("[remove_mod] Removed {} files from the game folder ({} changed, left alone)", report.removed.len(), report.modified.len());
+    Ok(Some(report))
+}
+
+/// Computes which dependencies become unreferenced if `mods_to_remove` are uninstalled
 #[command]
-async fn remove_mod(app: AppHandle, profile_id: String, mod_name: String) -> Result<bool, String> {
-    let profile_dir = app.path().app_data_dir().unwrap().join("profiles").join(&profile_id);
-    let plugins_dir = profile_dir.join("BepInEx").join("plugins");
-    
-    // mod_name is usually "Namespace-Name-Version" or "Namespace-Name"
-    // We need to find the folder.
-    // Logic similar to open_mod_folder
-    
-    if plugins_dir.exists() {
-        for entry in walkdir::WalkDir::new(&plugins_dir)
-            .min_depth(1)
-            .max_depth(2)
-            .into_iter()
-            .filter_map(|e| e.ok()) 
-        {
-            if entry.file_type().is_dir() {
-                if let Some(name) = entry.file_name().to_str() {
-                    // Simple check: if folder name contains mod_name (case insensitive)
-                    // Better: check if folder name STARTS with mod_name (Namespace-Name)
-                    // But mod_name passed from frontend is usually "Namespace-Name-Version"
-                    // We should probably pass the "clean name" or handle it.
-                    
-                    // Let's try to match loosely for now, or require exact match if possible.
-                    // Frontend passes `mod.uuid4` to store, but `removeMod` in store has `modId`.
-                    // Wait, store `removeMod` takes `modId` (uuid4).
-                    // But to delete file, I need the name.
-                    // The store has the profile, so it knows the name.
-                    
-                    // I will update the store to pass the name.
-                    
-                    if name.to_lowercase().contains(&mod_name.to_lowercase()) {
-                        fs::remove_dir_all(entry.path()).map_err(|e| e.to_string())?;
-                        return Ok(true);
-                    }
+async fn compute_orphans(app: AppHandle, state: tauri::State<'_, AppState>, profile_id: String, mods_to_remove: Vec<String>) -> Result<orphans::OrphanPlan, String> {
+    let profile = profile_store::find_profile(&app, &profile_id)?;
+    let packages_lock = state.packages.lock().map_err(|_| "Failed to lock state".to_string())?;
+    let packages = packages_lock.get(&profile.game_identifier).ok_or("Game packages not loaded")?;
+    let index = package_index::PackageIndex::new(packages);
+    let plan = orphans::compute_orphans(&index, &profile, &mods_to_remove);
+
+    eprintln!("[compute_orphans] Removing {:?}: {} orphans, {} retained",
+        plan.remove, plan.orphans.len(), plan.retained.len());
+    Ok(plan)
+}
+
+/// Delete the profile cache folders of the packages ("Author-Name") under
+/// `mods_dir`, their install records with the files they list (configs stay)
+/// and their lockfile entries. Returns the packages removed and those whose
+/// folder couldn't be deleted.
+fn remove_from_profile(profile_dir: &std::path::Path, mods_dir: &std::path::Path, profile: &Profile, names: &[String]) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut removed_mods = Vec::new();
+    let mut failed = Vec::new();
+    if mods_dir.exists() {
+        for entry in fs::read_dir(mods_dir).map_err(|e| e.to_string())?.filter_map(|e| e.ok()) {
+            let folder_name = entry.file_name().to_string_lossy().to_string();
+            // Folders are named "Author-Name-Version"; "Author-Lib" must not match "Author-Lib-Extended-1.0.0"
+            let package = package_index::split_versioned_name(&folder_name).0;
+            let Some(name) = names.iter().find(|n| n.eq_ignore_ascii_case(package)) else { continue };
+            if !entry.path().is_dir() { continue; }
+            
+            match fs::remove_dir_all(entry.path()) {
+                Ok(_) => removed_mods.push(name.clone()),
+                Err(e) => {
+                    eprintln!("[remove_mod] Failed to remove {:?}: {}", entry.path(), e);
+                    failed.push(name.clone());
                 }
             }
         }
     }
-    Ok(false)
+    
+    for m in profile.mods.iter().filter(|m| names.iter().any(|n| n.eq_ignore_ascii_case(m.package_name()))) {
+        if let Some(record) = install_rules::load_record(profile_dir, &m.full_name) {
+            for file in record.files.iter().filter(|f| f.rule != "config") {
+                let _ = fs::remove_file(profile_dir.join(&file.path));
+            }
+        }
+        install_rules::remove_record(profile_dir, &m.full_name);
+    }
+    lockfile::forget(profile_dir, names)?;
+    Ok((removed_mods, failed))
 }
 
 #[command]
-async fn export_profile(app: AppHandle, profile_id: String) -> Result<serde_json::Value, String> {
-    // 1. Read profiles.json
-    let profiles_path = app.path().app_data_dir().unwrap().join("profiles.json");
-    if !profiles_path.exists() {
-        return Err("No profiles found".to_string());
+async fn remove_mod(app: AppHandle, profile_id: String, mod_name: Option<String>, plan: Option<orphans::OrphanPlan>) -> Result<RemoveModResult, String> {
+    let profile_dir = profile_store::profile_dir(&app, &profile_id)?;
+    let profile = profile_store::find_profile(&app, &profile_id)?;
+    let plugins_dir = profile_dir.join(mod_loader::for_game(&profile.game_identifier).mods_dir());
+    
+    // Plan mode: remove the mods and their orphans in one go, files and profile entries
+    if let Some(plan) = plan {
+        if plan.profile_id != profile_id {
+            return Err("Uninstall plan belongs to a different profile".to_string());
+        }
+        let names: Vec<String> = plan.remove.iter().cloned()
+            .chain(plan.orphans.iter().map(|o| o.name.clone()))
+            .collect();
+        let game_cleanup = remove_from_deployed_game(&app, &profile_id, &names)?;
+        let (removed_mods, failed) = remove_from_profile(&profile_dir, &plugins_dir, &profile, &names)?;
+        
+        let remove_keys: std::collections::HashSet<String> = names.iter().map(|n| n.to_lowercase()).collect();
+        let mut profiles = profile_store::load_profiles(&app)?;
+        let profile = profiles.iter_mut().find(|p| p.id == profile_id).ok_or("Profile not found")?;
+        profile.mods.retain(|m| !remove_keys.contains(&m.package_name().to_lowercase()));
+        let updated = profile.clone();
+        profile_store::save_profiles(&app, &profiles)?;
+        
+        eprintln!("[remove_mod] Applied uninstall plan: {} mods removed from profile {}", names.len(), profile_id);
+        return Ok(RemoveModResult { removed: true, removed_mods, failed, profile: Some(updated), game_cleanup });
     }
-    let profiles_data = fs::read_to_string(&profiles_path).map_err(|e| e.to_string())?;
-    let profiles: Vec<serde_json::Value> = serde_json::from_str(&profiles_data).map_err(|e| e.to_string())?;
     
-    let profile = profiles.iter().find(|p| p["id"] == profile_id).ok_or("Profile not found")?;
+    // mod_name is usually "Namespace-Name", sometimes "Namespace-Name-Version"
+    let mod_name = mod_name.ok_or("Either mod_name or plan is required")?;
+    let package = package_index::split_versioned_name(&mod_name).0.to_string();
+    let game_cleanup = remove_from_deployed_game(&app, &profile_id, std::slice::from_ref(&package))?;
+    let (removed_mods, failed) = remove_from_profile(&profile_dir, &plugins_dir, &profile, std::slice::from_ref(&package))?;
     
-    // 2. Create export data
-    let mods = profile["mods"].as_array().unwrap_or(&vec![]).iter().map(|m| {
-        let full_name = m["fullName"].as_str().unwrap_or("");
-        let version_number = m["versionNumber"].as_str().unwrap_or("0.0.0");
-        let enabled = m["enabled"].as_bool().unwrap_or(true);
-        
-        // Clean name logic (strip version suffix)
-        let clean_name = if full_name.ends_with(&format!("-{}", version_number)) {
-            &full_name[0..full_name.len() - version_number.len() - 1]
-        } else {
-            full_name
-        };
-        
-        let version_parts: Vec<&str> = version_number.split('.').collect();
-        let major = version_parts.get(0).unwrap_or(&"0").parse().unwrap_or(0);
-        let minor = version_parts.get(1).unwrap_or(&"0").parse().unwrap_or(0);
-        let patch = version_parts.get(2).unwrap_or(&"0").parse().unwrap_or(0);
+    let removed = !removed_mods.is_empty() || game_cleanup.as_ref().map(|c| !c.removed.is_empty()).unwrap_or(false);
+    Ok(RemoveModResult { removed, removed_mods: if removed { vec![mod_name] } else { vec![] }, failed, profile: None, game_cleanup })
+}
+
+/// Build the r2modman `export.r2x` YAML for a profile
+fn build_export_yaml(profile: &Profile) -> Result<String, String> {
+    let mods = profile.mods.iter().map(|m| {
+        let (major, minor, patch) = m.version_triple();
         
         serde_json::json!({
-            "name": clean_name,
+            "name": m.package_name(),
             "version": {
                 "major": major,
                 "minor": minor,
                 "patch": patch
             },
-            "enabled": enabled
+            "enabled": m.enabled
         })
     }).collect::<Vec<_>>();
     
     let export_data = serde_json::json!({
-        "profileName": profile["name"],
+        "profileName": profile.name,
         "mods": mods
     });
     
-    // 3. Convert to YAML
-    let yaml_content = serde_yaml::to_string(&export_data).map_err(|e| e.to_string())?;
+    serde_yaml::to_string(&export_data).map_err(|e| e.to_string())
+}
+
+/// The profile's lockfile as exported next to `export.r2x`
+fn build_export_lockfile(app: &AppHandle, profile: &Profile) -> Result<String, String> {
+    let profile_dir = profile_store::profile_dir(app, &profile.id)?;
+    let lockfile = lockfile::load(&profile_dir).for_profile(profile);
+    if lockfile.packages.len() < profile.mods.len() {
+        eprintln!("[export] Lockfile of {} covers {} of {} mods", profile.id, lockfile.packages.len(), profile.mods.len());
+    }
+    lockfile.to_json()
+}
+
+#[command]
+async fn export_profile(app: AppHandle, profile_id: String, include_lockfile: Option<bool>) -> Result<serde_json::Value, String> {
+    // 1. Read profile from profiles.json
+    let profile = profile_store::find_profile(&app, &profile_id)?;
+    
+    // 2-3. Create export data as YAML
+    let yaml_content = build_export_yaml(&profile)?;
     
     // 4. Create Zip
     let temp_dir = std::env::temp_dir().join(format!("r2modmac-export-{}", profile_id));
@@ -2355,13 +2885,17 @@ async fn export_profile(app: AppHandle, profile_id: String) -> Result<serde_json
     zip.start_file("export.r2x", options).map_err(|e| e.to_string())?;
     use std::io::Write;
     zip.write_all(yaml_content.as_bytes()).map_err(|e| e.to_string())?;
+    if include_lockfile.unwrap_or(false) {
+        zip.start_file(lockfile::LOCKFILE, options).map_err(|e| e.to_string())?;
+        zip.write_all(build_export_lockfile(&app, &profile)?.as_bytes()).map_err(|e| e.to_string())?;
+    }
     zip.finish().map_err(|e| e.to_string())?;
     
     // 5. Save Dialog
     use tauri_plugin_dialog::DialogExt;
     let save_path = app.dialog().file()
         .add_filter("r2modman Profile", &["r2z"])
-        .set_file_name(&format!("{}.r2z", profile["name"].as_str().unwrap_or("profile")))
+        .set_file_name(format!("{}.r2z", profile.name))
         .blocking_save_file();
         
     if let Some(path) = save_path {
@@ -2374,53 +2908,12 @@ async fn export_profile(app: AppHandle, profile_id: String) -> Result<serde_json
 }
 
 #[command]
-async fn share_profile(app: AppHandle, profile_id: String) -> Result<String, String> {
-    // 1. Read profiles.json
-    let profiles_path = app.path().app_data_dir().unwrap().join("profiles.json");
-    if !profiles_path.exists() {
-        return Err("No profiles found".to_string());
-    }
-    let profiles_data = fs::read_to_string(&profiles_path).map_err(|e| e.to_string())?;
-    let profiles: Vec<serde_json::Value> = serde_json::from_str(&profiles_data).map_err(|e| e.to_string())?;
+async fn share_profile(app: AppHandle, profile_id: String, include_lockfile: Option<bool>) -> Result<String, String> {
+    // 1. Read profile from profiles.json
+    let profile = profile_store::find_profile(&app, &profile_id)?;
     
-    let profile = profiles.iter().find(|p| p["id"] == profile_id).ok_or("Profile not found")?;
-    
-    // 2. Create export data (Same logic as export_profile)
-    let mods = profile["mods"].as_array().unwrap_or(&vec![]).iter().map(|m| {
-        let full_name = m["fullName"].as_str().unwrap_or("");
-        let version_number = m["versionNumber"].as_str().unwrap_or("0.0.0");
-        let enabled = m["enabled"].as_bool().unwrap_or(true);
-        
-        // Clean name logic (strip version suffix)
-        let clean_name = if full_name.ends_with(&format!("-{}", version_number)) {
-            &full_name[0..full_name.len() - version_number.len() - 1]
-        } else {
-            full_name
-        };
-        
-        let version_parts: Vec<&str> = version_number.split('.').collect();
-        let major = version_parts.get(0).unwrap_or(&"0").parse().unwrap_or(0);
-        let minor = version_parts.get(1).unwrap_or(&"0").parse().unwrap_or(0);
-        let patch = version_parts.get(2).unwrap_or(&"0").parse().unwrap_or(0);
-        
-        serde_json::json!({
-            "name": clean_name,
-            "version": {
-                "major": major,
-                "minor": minor,
-                "patch": patch
-            },
-            "enabled": enabled
-        })
-    }).collect::<Vec<_>>();
-    
-    let export_data = serde_json::json!({
-        "profileName": profile["name"],
-        "mods": mods
-    });
-    
-    // 3. Convert to YAML
-    let yaml_content = serde_yaml::to_string(&export_data).map_err(|e| e.to_string())?;
+    // 2-3. Create export data as YAML (same as export_profile)
+    let yaml_content = build_export_yaml(&profile)?;
     
     // 4. Create Zip in Memory
     let mut zip_buffer = Vec::new();
@@ -2432,6 +2925,10 @@ async fn share_profile(app: AppHandle, profile_id: String) -> Result<String, Str
         zip.start_file("export.r2x", options).map_err(|e| e.to_string())?;
         use std::io::Write;
         zip.write_all(yaml_content.as_bytes()).map_err(|e| e.to_string())?;
+        if include_lockfile.unwrap_or(false) {
+            zip.start_file(lockfile::LOCKFILE, options).map_err(|e| e.to_string())?;
+            zip.write_all(build_export_lockfile(&app, &profile)?.as_bytes()).map_err(|e| e.to_string())?;
+        }
         
         zip.finish().map_err(|e| e.to_string())?;
     }
@@ -0,0 +1,154 @@
+//! Per-profile lockfile (`profiles/<id>/mods.lock.json`).
+//!
+//! Every archive installed into a profile is recorded with its exact version,
+//! download URL, SHA-256 and the dependencies its manifest declares. Installing
+//! the same version again must produce the same archive, so a re-uploaded or
+//! corrupted archive is refused instead of silently giving a different setup.
+//! Exports and share codes can carry the lockfile, and importing one seeds the
+//! new profile's lockfile before anything is installed.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::Path,
+    sync::Mutex,
+};
+
+use crate::{package_index::split_versioned_name, profile_store::Profile, storage};
+
+/// File name in the profile folder and in exported profile archives
+pub const LOCKFILE: &str = "mods.lock.json";
+const LOCKFILE_VERSION: u32 = 1;
+
+/// Installs run concurrently; read-modify-write of the lockfile must not
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LockedPackage {
+    pub version: String,
+    pub download_url: String,
+    pub sha256: String,
+    /// "Author-Name-1.2.3" as declared in the package's manifest
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Lockfile {
+    pub lockfile_version: u32,
+    /// By "Author-Name"
+    pub packages: BTreeMap<String, LockedPackage>,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self { lockfile_version: LOCKFILE_VERSION, packages: BTreeMap::new() }
+    }
+}
+
+impl Lockfile {
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let lockfile: Lockfile = serde_json::from_str(data).map_err(|e| format!("Invalid lockfile: {}", e))?;
+        if lockfile.lockfile_version > LOCKFILE_VERSION {
+            return Err(format!("Lockfile version {} is newer than this app supports", lockfile.lockfile_version));
+        }
+        Ok(lockfile)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// Entries matching the profile's current mods and versions
+    pub fn for_profile(&self, profile: &Profile) -> Lockfile {
+        let packages = profile.mods.iter()
+            .filter_map(|m| {
+                let (name, locked) = self.packages.get_key_value(m.package_name())?;
+                (locked.version == m.version_number).then(|| (name.clone(), locked.clone()))
+            })
+            .collect();
+        Lockfile { lockfile_version: LOCKFILE_VERSION, packages }
+    }
+}
+
+pub fn load(profile_dir: &Path) -> Lockfile {
+    fs::read_to_string(profile_dir.join(LOCKFILE)).ok()
+        .and_then(|data| Lockfile::parse(&data).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(profile_dir: &Path, lockfile: &Lockfile) -> Result<(), String> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    storage::atomic_write(&profile_dir.join(LOCKFILE), lockfile.to_json()?.as_bytes())
+        .map_err(|e| format!("Failed to save lockfile: {}", e))
+}
+
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Dependencies listed in the archive's Thunderstore `manifest.json`
+fn manifest_dependencies(archive_path: &Path) -> Vec<String> {
+    let read = || -> Option<Vec<String>> {
+        let mut archive = crate::extract::open_archive(archive_path).ok()?;
+        let mut data = String::new();
+        io::Read::read_to_string(&mut archive.by_name("manifest.json").ok()?, &mut data).ok()?;
+        let manifest: serde_json::Value = serde_json::from_str(data.trim_start_matches('\u{feff}')).ok()?;
+        Some(manifest["dependencies"].as_array()?.iter().filter_map(|d| d.as_str().map(str::to_string)).collect())
+    };
+    read().unwrap_or_default()
+}
+
+/// Check an archive of `mod_name` ("Author-Name-1.2.3") against the lockfile.
+/// Returns the entry to record once it's installed.
+pub fn verify(profile_dir: &Path, mod_name: &str, download_url: &str, archive_path: &Path) -> Result<(String, LockedPackage), String> {
+    let (name, version) = split_versioned_name(mod_name);
+    let version = version.ok_or_else(|| format!("{} has no version to lock", mod_name))?;
+    let sha256 = sha256_file(archive_path).map_err(|e| format!("Failed to hash {}: {}", archive_path.display(), e))?;
+
+    if let Some(locked) = load(profile_dir).packages.get(name).filter(|l| l.version == version) {
+        if locked.sha256 != sha256 {
+            return Err(format!(
+                "Archive of {} does not match the profile's lockfile (expected SHA-256 {}, got {}). It may have been re-uploaded",
+                mod_name, locked.sha256, sha256
+            ));
+        }
+    }
+    Ok((name.to_string(), LockedPackage {
+        version: version.to_string(),
+        download_url: download_url.to_string(),
+        sha256,
+        dependencies: manifest_dependencies(archive_path),
+    }))
+}
+
+/// Record an installed package
+pub fn record(profile_dir: &Path, name: String, package: LockedPackage) -> Result<(), String> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut lockfile = load(profile_dir);
+    if lockfile.packages.get(&name) == Some(&package) {
+        return Ok(());
+    }
+    lockfile.packages.insert(name, package);
+    storage::atomic_write(&profile_dir.join(LOCKFILE), lockfile.to_json()?.as_bytes())
+        .map_err(|e| format!("Failed to save lockfile: {}", e))
+}
+
+/// Drop the entries of removed packages ("Author-Name")
+pub fn forget(profile_dir: &Path, names: &[String]) -> Result<(), String> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut lockfile = load(profile_dir);
+    let before = lockfile.packages.len();
+    lockfile.packages.retain(|name, _| !names.iter().any(|n| n.eq_ignore_ascii_case(name)));
+    if lockfile.packages.len() == before {
+        return Ok(());
+    }
+    storage::atomic_write(&profile_dir.join(LOCKFILE), lockfile.to_json()?.as_bytes())
+        .map_err(|e| format!("Failed to save lockfile: {}", e))
+}
@@ -0,0 +1,345 @@
+//! Mod loader support.
+//!
+//! Each Thunderstore community runs one mod loader. A `ModLoader` knows how to
+//! recognise its own package, where that package and ordinary mods go, and
+//! which folders and root files it owns in the game directory (deployed from
+//! the profile and removed again on cleanup). Which loader a game uses comes
+//! from its entry in `game_defs`; BepInEx is the default.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::game_defs::{self, LoaderKind};
+use crate::install_rules::{RuleSet, BEPINEX_RULES, MELONLOADER_RULES};
+
+pub trait ModLoader: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Whether "Author-Name" is this loader's own package
+    fn is_loader_package(&self, package_name: &str) -> bool;
+
+    /// Whether an archive (given its entry names) is this loader's package
+    fn is_loader_archive(&self, entry_names: &[&str]) -> bool;
+
+    /// Where a loader package entry goes, relative to the game root
+    fn loader_package_target(&self, entry: &Path) -> Option<PathBuf>;
+
+    /// Whether the loader is present in `root` (a game folder or profile)
+    fn is_installed(&self, root: &Path) -> bool;
+
+    /// Install rules for ordinary mods
+    fn install_rules(&self, game_identifier: &str) -> RuleSet;
+
+    /// Folder (relative to the root) holding the installed mods
+    fn mods_dir(&self) -> &'static str;
+
+    /// Folders (relative to the root) the loader and its mods live in
+    fn managed_dirs(&self) -> &'static [&'static str];
+
+    /// Loose files at the root that belong to the loader
+    fn root_files(&self) -> &'static [&'static str];
+
+    /// Fix up a profile before it is deployed
+    fn prepare_profile(&self, _profile_dir: &Path) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Arguments the game executable needs for the loader to start
+    fn launch_args(&self, _game_root: &Path) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Environment the game executable needs for the loader to start
+    fn launch_env(&self, _game_root: &Path) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// `WINEDLLOVERRIDES` value that makes Wine load the loader's proxy DLL
+    fn wine_dll_overrides(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Log file (relative to the game root) the loader writes each session
+    fn log_file(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Folder (relative to the root) of the mods' config files, kept per profile
+    fn config_dir(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// Root folder of BepInExPack archives; its contents go to the game root
+const BEPINEX_PACK_PREFIX: &str = "BepInExPack/";
+
+pub struct BepInEx;
+pub struct MelonLoader;
+
+/// Major version of the Unity Doorstop shipped with BepInEx (3 if unknown)
+fn doorstop_major(game_root: &Path) -> u32 {
+    fs::read_to_string(game_root.join(".doorstop_version")).ok()
+        .and_then(|v| v.trim().split('.').next().and_then(|major| major.parse().ok()))
+        .unwrap_or(3)
+}
+
+/// Loader named in the game's definition, BepInEx for unknown games
+pub fn for_game(game_identifier: &str) -> &'static dyn ModLoader {
+    match game_defs::get(game_identifier).map(|def| def.loader) {
+        Some(LoaderKind::Melonloader) => &MelonLoader,
+        Some(LoaderKind::Bepinex) | None => &BepInEx,
+    }
+}
+
+impl ModLoader for BepInEx {
+    fn name(&self) -> &'static str {
+        "BepInEx"
+    }
+
+    fn is_loader_package(&self, package_name: &str) -> bool {
+        package_name.to_lowercase().contains("bepinexpack")
+    }
+
+    fn is_loader_archive(&self, entry_names: &[&str]) -> bool {
+        entry_names.iter().any(|name| name.starts_with(BEPINEX_PACK_PREFIX))
+    }
+
+    fn loader_package_target(&self, entry: &Path) -> Option<PathBuf> {
+        crate::extract::strip_prefix(BEPINEX_PACK_PREFIX)(entry)
+    }
+
+    fn is_installed(&self, root: &Path) -> bool {
+        root.join("BepInEx").join("core").exists()
+    }
+
+    fn install_rules(&self, game_identifier: &str) -> RuleSet {
+        RuleSet::new(BEPINEX_RULES, game_identifier)
+    }
+
+    fn mods_dir(&self) -> &'static str {
+        "BepInEx/plugins"
+    }
+
+    fn managed_dirs(&self) -> &'static [&'static str] {
+        &["BepInEx"]
+    }
+
+    fn root_files(&self) -> &'static [&'static str] {
+        &["doorstop_config.ini", "winhttp.dll", ".doorstop_version", "libdoorstop.dylib", "libdoorstop.so"]
+    }
+
+    /// Doorstop 4 and 3 spell their switches differently; `.doorstop_version`
+    /// only exists from 4 on
+    fn launch_args(&self, game_root: &Path) -> Vec<String> {
+        let preloader = game_root.join("BepInEx").join("core").join("BepInEx.Preloader.dll");
+        let preloader = preloader.to_string_lossy().to_string();
+        if doorstop_major(game_root) >= 4 {
+            vec!["--doorstop-enabled".into(), "true".into(), "--doorstop-target-assembly".into(), preloader]
+        } else {
+            vec!["--doorstop-enable".into(), "true".into(), "--doorstop-target".into(), preloader]
+        }
+    }
+
+    /// Native builds only start Doorstop when libdoorstop is preloaded into the game
+    fn launch_env(&self, game_root: &Path) -> Vec<(String, String)> {
+        let preloader = game_root.join("BepInEx").join("core").join("BepInEx.Preloader.dll");
+        let preloader = preloader.to_string_lossy().to_string();
+        let mut env: Vec<(String, String)> = if doorstop_major(game_root) >= 4 {
+            vec![("DOORSTOP_ENABLED".into(), "1".into()), ("DOORSTOP_TARGET_ASSEMBLY".into(), preloader)]
+        } else {
+            vec![("DOORSTOP_ENABLE".into(), "TRUE".into()), ("DOORSTOP_INVOKE_DLL_PATH".into(), preloader)]
+        };
+
+        let (preload_var, lib_name) = if cfg!(target_os = "macos") {
+            ("DYLD_INSERT_LIBRARIES", "libdoorstop.dylib")
+        } else {
+            ("LD_PRELOAD", "libdoorstop.so")
+        };
+        let lib = game_root.join(lib_name);
+        if lib.is_file() {
+            let mut value = lib.to_string_lossy().to_string();
+            if let Ok(existing) = std::env::var(preload_var) {
+                if !existing.is_empty() {
+                    value = format!("{}:{}", value, existing);
+                }
+            }
+            env.push((preload_var.into(), value));
+        }
+        env
+    }
+
+    fn wine_dll_overrides(&self) -> Option<&'static str> {
+        Some("winhttp=n,b")
+    }
+
+    fn log_file(&self) -> Option<&'static str> {
+        Some(crate::bepinex_log::LOG_FILE)
+    }
+
+    fn config_dir(&self) -> Option<&'static str> {
+        Some(crate::bepinex_config::CONFIG_DIR)
+    }
+
+    /// Older installs put BepInExPack under plugins; make sure it ends up at the profile root
+    fn prepare_profile(&self, profile_dir: &Path) -> Result<(), String> {
+        let plugins_dir = profile_dir.join("BepInEx").join("plugins");
+        if !plugins_dir.exists() {
+            return Ok(());
+        }
+
+        // Find the BepInExPack folder, preferring the more explicit layouts
+        // Score: 3 = nested BepInExPack folder, 2 = winhttp.dll present + name match, 1 = winhttp.dll present
+        let mut best_candidate: Option<(PathBuf, i32)> = None;
+        let mut consider = |candidate: PathBuf, score: i32| {
+            if best_candidate.as_ref().map_or(true, |(_, s)| score > *s) {
+                best_candidate = Some((candidate, score));
+            }
+        };
+
+        if let Ok(entries) = fs::read_dir(&plugins_dir) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if !path.is_dir() { continue; }
+
+                let folder_name = path.file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("")
+                    .to_lowercase();
+
+                // Pattern 1: nested BepInExPack (standard Thunderstore layout)
+                // plugins/ModName/BepInExPack/winhttp.dll
+                let nested_pack = path.join("BepInExPack");
+                if nested_pack.join("winhttp.dll").exists() {
+                    eprintln!("[mod_loader] Found nested BepInExPack candidate: {:?}", nested_pack);
+                    consider(nested_pack, 3);
+                    continue;
+                }
+
+                // Pattern 2: the folder itself is the pack
+                if path.join("winhttp.dll").exists() {
+                    let mut score = 1;
+                    if folder_name.contains("bepinex") { score += 1; }
+
+                    eprintln!("[mod_loader] Found direct BepInExPack candidate: {:?} (score: {})", path, score);
+                    consider(path, score);
+                    continue;
+                }
+
+                // Pattern 3: a subdirectory is the pack (e.g. plugins/ModName/BepInExPack_GameName)
+                if let Ok(sub_entries) = fs::read_dir(&path) {
+                    for sub_entry in sub_entries.filter_map(|e| e.ok()) {
+                        let sub_path = sub_entry.path();
+                        if sub_path.is_dir() && sub_path.join("winhttp.dll").exists() {
+                            let sub_name = sub_path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_lowercase();
+                            let mut score = 1;
+                            if sub_name.contains("bepinex") { score += 1; }
+
+                            eprintln!("[mod_loader] Found subdirectory BepInExPack candidate: {:?} (score: {})", sub_path, score);
+                            consider(sub_path, score);
+                        }
+                    }
+                }
+            }
+        }
+
+        let Some((pack_dir, score)) = best_candidate else {
+            eprintln!("[mod_loader] Warning: No BepInExPack found in plugins!");
+            return Ok(());
+        };
+        eprintln!("[mod_loader] Selected BepInExPack: {:?} (score: {})", pack_dir, score);
+
+        // 1. Ensure winhttp.dll is at the profile root
+        let winhttp_src = pack_dir.join("winhttp.dll");
+        let winhttp_dst = profile_dir.join("winhttp.dll");
+        if winhttp_src.exists() && !winhttp_dst.exists() {
+            eprintln!("[mod_loader] Copying winhttp.dll to profile root");
+            fs::copy(&winhttp_src, &winhttp_dst)
+                .map_err(|e| format!("Failed to copy winhttp.dll: {}", e))?;
+        }
+
+        // Doorstop 4 packs also ship a version marker and, for native builds, the preload library
+        for name in [".doorstop_version", "libdoorstop.dylib", "libdoorstop.so"] {
+            let src = pack_dir.join(name);
+            let dst = profile_dir.join(name);
+            if src.is_file() && !dst.exists() {
+                eprintln!("[mod_loader] Copying {} to profile root", name);
+                fs::copy(&src, &dst)
+                    .map_err(|e| format!("Failed to copy {}: {}", name, e))?;
+            }
+        }
+
+        // 2. Ensure doorstop_config.ini is at the profile root, with Doorstop enabled
+        let doorstop_src = pack_dir.join("doorstop_config.ini");
+        let doorstop_dst = profile_dir.join("doorstop_config.ini");
+        if doorstop_src.exists() && !doorstop_dst.exists() {
+            eprintln!("[mod_loader] Copying doorstop_config.ini to profile root");
+            fs::copy(&doorstop_src, &doorstop_dst)
+                .map_err(|e| format!("Failed to copy doorstop_config.ini: {}", e))?;
+
+            if let Ok(content) = fs::read_to_string(&doorstop_dst) {
+                if !content.contains("enabled=true") && !content.contains("enabled = true") {
+                    eprintln!("[mod_loader] Enforcing enabled=true in doorstop_config.ini");
+                    let new_content = content.replace("enabled=false", "enabled=true")
+                        .replace("enabled = false", "enabled = true");
+                    let _ = fs::write(&doorstop_dst, new_content);
+                }
+            }
+        }
+
+        // 3. Merge BepInEx core/config from the pack (if present)
+        let pack_bepinex = pack_dir.join("BepInEx");
+        if pack_bepinex.exists() {
+            eprintln!("[mod_loader] Merging BepInEx core/config from pack...");
+            let target_bepinex = profile_dir.join("BepInEx");
+            crate::copy_dir_recursive(&pack_bepinex, &target_bepinex)
+                .map_err(|e| format!("Failed to merge BepInEx folder: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+impl ModLoader for MelonLoader {
+    fn name(&self) -> &'static str {
+        "MelonLoader"
+    }
+
+    fn is_loader_package(&self, package_name: &str) -> bool {
+        package_name.to_lowercase().contains("melonloader")
+    }
+
+    fn is_loader_archive(&self, entry_names: &[&str]) -> bool {
+        entry_names.iter().any(|name| name.starts_with("MelonLoader/"))
+            && entry_names.iter().any(|name| name.eq_ignore_ascii_case("version.dll"))
+    }
+
+    /// The loader package mirrors the game root
+    fn loader_package_target(&self, entry: &Path) -> Option<PathBuf> {
+        Some(entry.to_path_buf())
+    }
+
+    fn is_installed(&self, root: &Path) -> bool {
+        root.join("MelonLoader").is_dir() && root.join("version.dll").exists()
+    }
+
+    fn install_rules(&self, game_identifier: &str) -> RuleSet {
+        RuleSet::new(MELONLOADER_RULES, game_identifier)
+    }
+
+    fn mods_dir(&self) -> &'static str {
+        "Mods"
+    }
+
+    fn managed_dirs(&self) -> &'static [&'static str] {
+        &["MelonLoader", "Mods", "Plugins", "UserLibs", "UserData"]
+    }
+
+    fn root_files(&self) -> &'static [&'static str] {
+        &["version.dll", "dobby.dll"]
+    }
+
+    fn wine_dll_overrides(&self) -> Option<&'static str> {
+        Some("version=n,b")
+    }
+}
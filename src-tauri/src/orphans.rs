@@ -0,0 +1,153 @@
+//! Orphaned-dependency analysis for uninstalls.
+//!
+//! Builds the dependency graph of a profile's installed mods from the package
+//! index, then works out which dependencies of the mods being removed would no
+//! longer be referenced by anything that stays installed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use crate::mod_loader;
+use crate::package_index::{split_versioned_name, PackageIndex};
+use crate::profile_store::Profile;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrphanedDependency {
+    /// "Author-Name"
+    pub name: String,
+    pub version: String,
+    /// Packages being removed that depended on this one
+    pub orphaned_by: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RetainedDependency {
+    pub name: String,
+    pub version: String,
+    /// Installed packages that stay and still depend on this one
+    pub needed_by: Vec<String>,
+    pub reason: String,
+}
+
+/// What removing `remove` from a profile implies. Also accepted back by
+/// `remove_mod`, which removes `remove` plus whatever is left in `orphans`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrphanPlan {
+    pub profile_id: String,
+    pub remove: Vec<String>,
+    pub orphans: Vec<OrphanedDependency>,
+    #[serde(default)]
+    pub retained: Vec<RetainedDependency>,
+    /// Installed mods missing from the package index (their dependencies are unknown)
+    #[serde(default)]
+    pub unresolved: Vec<String>,
+}
+
+pub fn compute_orphans(index: &PackageIndex, profile: &Profile, mods_to_remove: &[String]) -> OrphanPlan {
+    // key -> (display name, version)
+    let installed: HashMap<String, (String, String)> = profile.mods.iter()
+        .map(|m| (m.package_name().to_lowercase(), (m.package_name().to_string(), m.version_number.clone())))
+        .collect();
+
+    let mut unresolved = Vec::new();
+    let mut deps: HashMap<String, Vec<String>> = HashMap::new();
+    let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, (name, version)) in &installed {
+        let Some(package) = index.get(name) else {
+            unresolved.push(name.clone());
+            continue;
+        };
+        // Fall back to the latest listing if the installed version was removed upstream
+        let Some(version_data) = PackageIndex::version(package, version).or_else(|| PackageIndex::latest(package)) else {
+            continue;
+        };
+        for dep in PackageIndex::dependencies(version_data) {
+            let dep_key = split_versioned_name(dep).0.to_lowercase();
+            if installed.contains_key(&dep_key) && dep_key != *key {
+                deps.entry(key.clone()).or_default().push(dep_key.clone());
+                dependents.entry(dep_key).or_default().push(key.clone());
+            }
+        }
+    }
+
+    let remove: HashSet<String> = mods_to_remove.iter()
+        .map(|m| split_versioned_name(m).0.to_lowercase())
+        .filter(|k| installed.contains_key(k))
+        .collect();
+
+    let reachable = |starts: &mut dyn Iterator<Item = &String>| -> HashSet<String> {
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<String> = starts.cloned().collect();
+        while let Some(key) = queue.pop_front() {
+            for dep in deps.get(&key).into_iter().flatten() {
+                if seen.insert(dep.clone()) {
+                    queue.push_back(dep.clone());
+                }
+            }
+        }
+        seen
+    };
+
+    // Everything the removed mods pull in, then everything the remaining mods still pull in
+    let candidates: HashSet<String> = reachable(&mut remove.iter())
+        .into_iter()
+        .filter(|k| !remove.contains(k))
+        .collect();
+    let kept_roots: Vec<&String> = installed.keys()
+        .filter(|k| !remove.contains(*k) && !candidates.contains(*k))
+        .collect();
+    let still_needed = reachable(&mut kept_roots.into_iter());
+
+    let display = |key: &String| installed.get(key).map(|(n, _)| n.clone()).unwrap_or_else(|| key.clone());
+    let version_of = |key: &String| installed.get(key).map(|(_, v)| v.clone()).unwrap_or_default();
+
+    // Mod loader packs are never offered as orphans
+    let loader = mod_loader::for_game(&profile.game_identifier);
+    let orphan_keys: HashSet<&String> = candidates.iter()
+        .filter(|k| !still_needed.contains(*k) && !loader.is_loader_package(k))
+        .collect();
+
+    let mut orphans = Vec::new();
+    let mut retained = Vec::new();
+    for key in &candidates {
+        let key_dependents = dependents.get(key).cloned().unwrap_or_default();
+        if orphan_keys.contains(key) {
+            let orphaned_by: BTreeSet<String> = key_dependents.iter()
+                .filter(|d| remove.contains(*d) || orphan_keys.contains(d))
+                .map(display)
+                .collect();
+            orphans.push(OrphanedDependency {
+                name: display(key),
+                version: version_of(key),
+                orphaned_by: orphaned_by.into_iter().collect(),
+            });
+        } else {
+            let needed_by: BTreeSet<String> = key_dependents.iter()
+                .filter(|d| !remove.contains(*d) && !orphan_keys.contains(d))
+                .map(display)
+                .collect();
+            let reason = if !needed_by.is_empty() {
+                format!("Still required by {}", needed_by.iter().cloned().collect::<Vec<_>>().join(", "))
+            } else {
+                "Mod loader".to_string()
+            };
+            retained.push(RetainedDependency {
+                name: display(key),
+                version: version_of(key),
+                needed_by: needed_by.into_iter().collect(),
+                reason,
+            });
+        }
+    }
+    orphans.sort_by(|a, b| a.name.cmp(&b.name));
+    retained.sort_by(|a, b| a.name.cmp(&b.name));
+    unresolved.sort();
+
+    OrphanPlan {
+        profile_id: profile.id.clone(),
+        remove: remove.iter().map(display).collect(),
+        orphans,
+        retained,
+        unresolved,
+    }
+}
@@ -0,0 +1,118 @@
+//! Read-only lookups over a game's cached Thunderstore package listing.
+//!
+//! `AppState` keeps the raw listing as `serde_json::Value`s; this wraps a
+//! slice of them with a by-name map and the version helpers the resolver,
+//! orphan analysis and update checks need.
+
+use serde::Serialize;
+use std::{cmp::Ordering, collections::HashMap};
+
+/// Split "Author-Name-1.2.3" into ("Author-Name", Some("1.2.3")).
+/// Names without a trailing X.Y.Z version are returned whole.
+pub fn split_versioned_name(s: &str) -> (&str, Option<&str>) {
+    if let Some((name, version)) = s.rsplit_once('-') {
+        let is_version = version.split('.').count() == 3
+            && version.split('.').all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+        if is_version {
+            return (name, Some(version));
+        }
+    }
+    (s, None)
+}
+
+fn version_parts(v: &str) -> Vec<u64> {
+    v.split('.').map(|p| p.parse().unwrap_or(0)).collect()
+}
+
+/// Numeric comparison of dotted versions ("1.10.0" > "1.9.3")
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (pa, pb) = (version_parts(a), version_parts(b));
+    for i in 0..pa.len().max(pb.len()) {
+        let ord = pa.get(i).unwrap_or(&0).cmp(pb.get(i).unwrap_or(&0));
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
+
+/// One version of a package as shown in a version picker
+#[derive(Debug, Serialize, Clone)]
+pub struct PackageVersion {
+    pub version_number: String,
+    pub full_name: String,
+    pub date_created: Option<String>,
+    pub downloads: u64,
+    pub file_size: Option<u64>,
+    pub download_url: String,
+    pub dependencies: Vec<String>,
+    /// The version the profile has installed
+    pub installed: bool,
+}
+
+pub struct PackageIndex<'a> {
+    by_name: HashMap<String, &'a serde_json::Value>,
+}
+
+impl<'a> PackageIndex<'a> {
+    pub fn new(packages: &'a [serde_json::Value]) -> Self {
+        let by_name = packages.iter()
+            .filter_map(|p| p["full_name"].as_str().map(|n| (n.to_lowercase(), p)))
+            .collect();
+        Self { by_name }
+    }
+
+    /// Package by "Author-Name" (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&'a serde_json::Value> {
+        self.by_name.get(&name.to_lowercase()).copied()
+    }
+
+    /// All versions of a package, as listed by Thunderstore (newest first)
+    pub fn versions(package: &'a serde_json::Value) -> &'a [serde_json::Value] {
+        package["versions"].as_array().map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn version(package: &'a serde_json::Value, version: &str) -> Option<&'a serde_json::Value> {
+        Self::versions(package).iter()
+            .find(|v| v["version_number"].as_str() == Some(version))
+    }
+
+    /// Highest version of a package
+    pub fn latest(package: &'a serde_json::Value) -> Option<&'a serde_json::Value> {
+        Self::versions(package).iter().max_by(|a, b| {
+            compare_versions(
+                a["version_number"].as_str().unwrap_or("0"),
+                b["version_number"].as_str().unwrap_or("0"),
+            )
+        })
+    }
+
+    /// Every listed version of a package, highest first
+    pub fn version_list(package: &'a serde_json::Value, installed_version: Option<&str>) -> Vec<PackageVersion> {
+        let mut versions: Vec<PackageVersion> = Self::versions(package).iter()
+            .filter_map(|v| {
+                let version_number = v["version_number"].as_str()?.to_string();
+                Some(PackageVersion {
+                    installed: installed_version == Some(version_number.as_str()),
+                    full_name: v["full_name"].as_str().map(str::to_string)
+                        .unwrap_or_else(|| format!("{}-{}", package["full_name"].as_str().unwrap_or(""), version_number)),
+                    date_created: v["date_created"].as_str().map(str::to_string),
+                    downloads: v["downloads"].as_u64().unwrap_or(0),
+                    file_size: v["file_size"].as_u64(),
+                    download_url: v["download_url"].as_str()?.to_string(),
+                    dependencies: Self::dependencies(v).into_iter().map(str::to_string).collect(),
+                    version_number,
+                })
+            })
+            .collect();
+        versions.sort_by(|a, b| compare_versions(&b.version_number, &a.version_number));
+        versions
+    }
+
+    /// Dependency strings ("Author-Name-1.2.3") of a package version
+    pub fn dependencies(version: &'a serde_json::Value) -> Vec<&'a str> {
+        version["dependencies"].as_array()
+            .map(|deps| deps.iter().filter_map(|d| d.as_str()).collect())
+            .unwrap_or_default()
+    }
+}
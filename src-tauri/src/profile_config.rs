@@ -0,0 +1,203 @@
+//! Per-profile mod configs.
+//!
+//! Each profile owns its `BepInEx/config` tree under `profiles/<id>/`. Deploying
+//! a profile mirrors that tree into the game folder and records the hash of
+//! every file in `profiles/<id>/config-state.json`. Before switching to another
+//! profile and after the game exits, files the game (or an in-game mod menu)
+//! changed since that deploy are copied back into the deployed profile. When
+//! the profile's own copy changed too, that's a conflict: the profile's copy is
+//! kept and the game's is saved under `profiles/<id>/config-conflicts/`.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+use tauri::AppHandle;
+
+use crate::{deployment, mod_loader, profile_store, storage};
+
+const STATE_FILE: &str = "config-state.json";
+const CONFLICTS_DIR: &str = "config-conflicts";
+
+/// Config files as they were last deployed
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct DeployedState {
+    game_path: String,
+    /// Path relative to the config folder -> SHA-256 (hex)
+    files: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ConfigConflict {
+    pub path: String,
+    /// Where the game's version was saved
+    pub game_copy: String,
+}
+
+/// Result of copying the game's config changes back into a profile
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ReverseSync {
+    pub profile_id: String,
+    pub copied: Vec<String>,
+    pub removed: Vec<String>,
+    pub conflicts: Vec<ConfigConflict>,
+}
+
+fn sha256_file(path: &Path) -> Option<String> {
+    let data = fs::read(path).ok()?;
+    Some(format!("{:x}", Sha256::digest(&data)))
+}
+
+/// Relative path (forward slashes) -> hash of every file under `dir`
+fn hash_tree(dir: &Path) -> HashMap<String, String> {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(dir).ok()?.to_string_lossy().replace('\\', "/");
+            Some((relative, sha256_file(e.path())?))
+        })
+        .collect()
+}
+
+fn copy_file(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::copy(from, to).map_err(|e| format!("Failed to copy {}: {}", from.display(), e))?;
+    Ok(())
+}
+
+fn is_newer(a: &Path, b: &Path) -> bool {
+    let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+    match (modified(a), modified(b)) {
+        (Some(a), Some(b)) => a > b,
+        _ => true,
+    }
+}
+
+/// Make `dst` hold exactly the files of `src`, copying only what differs.
+/// Returns the number of files copied and removed.
+pub fn mirror(src: &Path, dst: &Path) -> Result<(usize, usize), String> {
+    let src_files = hash_tree(src);
+    let dst_files = hash_tree(dst);
+
+    let stale: HashSet<&String> = dst_files.keys().filter(|p| !src_files.contains_key(*p)).collect();
+    for relative in &stale {
+        let _ = fs::remove_file(dst.join(relative));
+    }
+    let mut copied = 0;
+    for (relative, hash) in &src_files {
+        if dst_files.get(relative) != Some(hash) {
+            copy_file(&src.join(relative), &dst.join(relative))?;
+            copied += 1;
+        }
+    }
+    Ok((copied, stale.len()))
+}
+
+fn load_state(profile_dir: &Path) -> Option<DeployedState> {
+    let data = fs::read_to_string(profile_dir.join(STATE_FILE)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+fn save_state(profile_dir: &Path, state: &DeployedState) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    storage::atomic_write(&profile_dir.join(STATE_FILE), data.as_bytes())
+        .map_err(|e| format!("Failed to save config state: {}", e))
+}
+
+/// Copy config files changed in `game_config` since the last deploy into the
+/// profile. Without a recorded deploy, a game file wins if it is newer.
+fn reverse_sync(profile_id: &str, profile_dir: &Path, game_config: &Path, profile_config: &Path, base: Option<&HashMap<String, String>>) -> Result<ReverseSync, String> {
+    let mut result = ReverseSync { profile_id: profile_id.to_string(), ..Default::default() };
+    let game = hash_tree(game_config);
+    let profile = hash_tree(profile_config);
+
+    for (relative, game_hash) in &game {
+        let deployed = base.and_then(|b| b.get(relative));
+        let ours = profile.get(relative);
+        if deployed == Some(game_hash) || ours == Some(game_hash) {
+            continue;
+        }
+        let (from, to) = (game_config.join(relative), profile_config.join(relative));
+        let profile_changed = match base {
+            Some(_) => ours != deployed,
+            None => ours.is_some() && !is_newer(&from, &to),
+        };
+        if !profile_changed {
+            copy_file(&from, &to)?;
+            result.copied.push(relative.clone());
+        } else if base.is_some() {
+            let game_copy = profile_dir.join(CONFLICTS_DIR).join(relative);
+            copy_file(&from, &game_copy)?;
+            eprintln!("[profile_config] Conflict in {}: both the game and profile {} changed it", relative, profile_id);
+            result.conflicts.push(ConfigConflict { path: relative.clone(), game_copy: game_copy.to_string_lossy().to_string() });
+        }
+    }
+
+    // Deleted in the game and untouched in the profile
+    for (relative, deployed) in base.into_iter().flatten() {
+        if !game.contains_key(relative) && profile.get(relative) == Some(deployed) {
+            let _ = fs::remove_file(profile_config.join(relative));
+            result.removed.push(relative.clone());
+        }
+    }
+    Ok(result)
+}
+
+/// Copy the game's config changes back into the profile currently deployed to it
+pub fn capture_deployed(app: &AppHandle, game_identifier: &str) -> Result<Option<ReverseSync>, String> {
+    let Some(config_dir) = mod_loader::for_game(game_identifier).config_dir() else { return Ok(None) };
+    let Some(deployed) = deployment::get(app, game_identifier) else { return Ok(None) };
+    // A deleted profile has nothing to capture into
+    if profile_store::find_profile(app, &deployed.profile_id).is_err() {
+        return Ok(None);
+    }
+    let game_config = Path::new(&deployed.game_path).join(config_dir);
+    if !game_config.is_dir() {
+        return Ok(None);
+    }
+    let profile_dir = profile_store::profile_dir(app, &deployed.profile_id)?;
+    let profile_config = profile_dir.join(config_dir);
+
+    let state = load_state(&profile_dir).filter(|s| s.game_path == deployed.game_path);
+    let result = reverse_sync(&deployed.profile_id, &profile_dir, &game_config, &profile_config, state.as_ref().map(|s| &s.files))?;
+    // What the game holds now is the new baseline
+    save_state(&profile_dir, &DeployedState { game_path: deployed.game_path.clone(), files: hash_tree(&game_config) })?;
+
+    eprintln!("[profile_config] Captured configs of {} into profile {}: {} copied, {} removed, {} conflicts",
+        game_identifier, deployed.profile_id, result.copied.len(), result.removed.len(), result.conflicts.len());
+    Ok(Some(result))
+}
+
+/// Switch the game's configs to `profile_id`'s: capture changes into the
+/// deployed profile first, then mirror the new profile's tree into the game
+/// folder. Returns what the capture did.
+pub fn deploy(app: &AppHandle, game_identifier: &str, profile_id: &str, game_path: &Path) -> Result<Option<ReverseSync>, String> {
+    let Some(config_dir) = mod_loader::for_game(game_identifier).config_dir() else { return Ok(None) };
+    let game_config = game_path.join(config_dir);
+    let profile_dir = profile_store::profile_dir(app, profile_id)?;
+    let profile_config = profile_dir.join(config_dir);
+
+    let previous = deployment::get(app, game_identifier);
+    let mut captured = None;
+    if previous.as_ref().map(|d| Path::new(&d.game_path) == game_path).unwrap_or(false) {
+        captured = capture_deployed(app, game_identifier)?;
+    } else if !profile_config.exists() && game_config.is_dir() {
+        // Nothing deployed by us yet: the configs in the game folder are the player's, keep them
+        eprintln!("[profile_config] Adopting existing game configs into profile {}", profile_id);
+        mirror(&game_config, &profile_config)?;
+    }
+
+    fs::create_dir_all(&profile_config).map_err(|e| format!("Failed to create {}: {}", profile_config.display(), e))?;
+    let (copied, removed) = mirror(&profile_config, &game_config)?;
+    save_state(&profile_dir, &DeployedState { game_path: game_path.to_string_lossy().to_string(), files: hash_tree(&profile_config) })?;
+    eprintln!("[profile_config] Deployed configs of profile {}: {} copied, {} removed", profile_id, copied, removed);
+    Ok(captured)
+}
@@ -0,0 +1,253 @@
+//! Typed, versioned storage for `profiles.json`.
+//!
+//! The file used to be a bare JSON array of profiles (schema v1). It is now an
+//! object carrying a `schemaVersion` next to the profile list, and every older
+//! layout is upgraded through the `MIGRATIONS` chain when it is read.
+
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::{history, storage};
+
+/// Current on-disk schema version of `profiles.json`
+pub const PROFILES_SCHEMA_VERSION: u32 = 2;
+
+type Migration = fn(serde_json::Value) -> Result<serde_json::Value, String>;
+
+/// Migration steps, indexed by the version they upgrade FROM (index 0 = v1 -> v2)
+const MIGRATIONS: &[Migration] = &[
+    migrate_v1_to_v2,
+];
+
+fn default_true() -> bool {
+    true
+}
+
+/// A mod installed in a profile, as stored by the frontend
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledMod {
+    pub uuid4: String,
+    /// e.g. "ebkr-r2modman-3.1.0"
+    pub full_name: String,
+    pub version_number: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Left out of update checks and bulk updates
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Fields we don't know about are kept so the frontend never loses data
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl InstalledMod {
+    /// "Author-ModName" without the version suffix
+    pub fn package_name(&self) -> &str {
+        self.full_name
+            .strip_suffix(&self.version_number)
+            .and_then(|s| s.strip_suffix('-'))
+            .unwrap_or(&self.full_name)
+    }
+
+    /// Version as (major, minor, patch), missing or invalid parts are 0
+    pub fn version_triple(&self) -> (u64, u64, u64) {
+        let parts: Vec<u64> = self.version_number
+            .split('.')
+            .map(|s| s.parse().unwrap_or(0))
+            .collect();
+        (
+            parts.first().copied().unwrap_or(0),
+            parts.get(1).copied().unwrap_or(0),
+            parts.get(2).copied().unwrap_or(0),
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub game_identifier: String,
+    #[serde(default)]
+    pub mods: Vec<InstalledMod>,
+    #[serde(default)]
+    pub date_created: u64,
+    #[serde(default)]
+    pub last_used: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_image_url: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfilesFile {
+    schema_version: u32,
+    profiles: Vec<Profile>,
+}
+
+/// v1 was a bare array of profiles
+fn migrate_v1_to_v2(value: serde_json::Value) -> Result<serde_json::Value, String> {
+    match value {
+        serde_json::Value::Array(profiles) => Ok(serde_json::json!({
+            "schemaVersion": 2,
+            "profiles": profiles
+        })),
+        _ => Err("Expected a profile array in schema v1".to_string()),
+    }
+}
+
+fn detect_schema_version(value: &serde_json::Value) -> Result<u32, String> {
+    if value.is_array() {
+        return Ok(1);
+    }
+    value.get("schemaVersion")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .ok_or_else(|| "profiles.json has no schemaVersion".to_string())
+}
+
+/// Deserialize a single profile, naming the profile and mod at fault on error
+fn parse_profile(index: usize, value: serde_json::Value) -> Result<Profile, String> {
+    let label = value.get("name")
+        .or_else(|| value.get("id"))
+        .and_then(|v| v.as_str())
+        .map(|s| format!("profile #{} ('{}')", index, s))
+        .unwrap_or_else(|| format!("profile #{}", index));
+
+    if let Some(mods) = value.get("mods").and_then(|m| m.as_array()) {
+        for (mod_index, m) in mods.iter().enumerate() {
+            if let Err(e) = serde_json::from_value::<InstalledMod>(m.clone()) {
+                let mod_label = m.get("fullName").and_then(|v| v.as_str()).unwrap_or("?");
+                return Err(format!("Malformed mod #{} ('{}') in {}: {}", mod_index, mod_label, label, e));
+            }
+        }
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("Malformed {}: {}", label, e))
+}
+
+/// Parse the contents of `profiles.json`, upgrading older schema versions
+pub fn parse_profiles(data: &str) -> Result<Vec<Profile>, String> {
+    let mut value: serde_json::Value = serde_json::from_str(data)
+        .map_err(|e| format!("profiles.json is not valid JSON: {}", e))?;
+
+    let mut version = detect_schema_version(&value)?;
+    if version == 0 || version > PROFILES_SCHEMA_VERSION {
+        return Err(format!(
+            "profiles.json has schema version {} but this version of r2modmac only supports up to {}",
+            version, PROFILES_SCHEMA_VERSION
+        ));
+    }
+    while version < PROFILES_SCHEMA_VERSION {
+        eprintln!("[profile_store] Migrating profiles.json from v{} to v{}", version, version + 1);
+        value = MIGRATIONS[(version - 1) as usize](value)?;
+        version += 1;
+    }
+
+    let profiles = match value.get_mut("profiles").map(serde_json::Value::take) {
+        Some(serde_json::Value::Array(profiles)) => profiles,
+        _ => return Err("profiles.json has no profile list".to_string()),
+    };
+
+    profiles.into_iter()
+        .enumerate()
+        .map(|(index, p)| parse_profile(index, p))
+        .collect()
+}
+
+/// Serialize profiles in the current schema
+pub fn serialize_profiles(profiles: &[Profile]) -> Result<String, String> {
+    let file = ProfilesFile {
+        schema_version: PROFILES_SCHEMA_VERSION,
+        profiles: profiles.to_vec(),
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| e.to_string())
+}
+
+/// Per-profile folder (`profiles/<id>`) holding the profile cache and history
+pub fn profile_dir(app: &AppHandle, profile_id: &str) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join("profiles").join(profile_id))
+}
+
+pub fn profiles_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join("profiles.json"))
+}
+
+fn is_valid_profiles_file(data: &str) -> bool {
+    parse_profiles(data).is_ok()
+}
+
+/// A backup that was loaded because `profiles.json` could not be parsed
+#[derive(Debug, Serialize, Clone)]
+pub struct RecoveredProfiles {
+    pub backup: storage::BackupInfo,
+    pub error: String,
+}
+
+pub fn load_profiles(app: &AppHandle) -> Result<Vec<Profile>, String> {
+    let path = profiles_path(app)?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_profiles(&data)
+}
+
+/// Like `load_profiles`, but falls back to the newest backup that parses.
+/// The broken `profiles.json` is left untouched until the next save backs it up.
+pub fn load_profiles_or_recover(app: &AppHandle) -> Result<(Vec<Profile>, Option<RecoveredProfiles>), String> {
+    let error = match load_profiles(app) {
+        Ok(profiles) => return Ok((profiles, None)),
+        Err(e) => e,
+    };
+    eprintln!("[profile_store] Failed to load profiles.json: {}", error);
+
+    let path = profiles_path(app)?;
+    let (backup, data) = storage::newest_valid_backup(&storage::backups_dir(app)?, &path, is_valid_profiles_file)
+        .ok_or_else(|| format!("{} (no valid backup available)", error))?;
+    eprintln!("[profile_store] Recovered profiles from backup {}", backup.file_name);
+
+    let profiles = parse_profiles(&data)?;
+    Ok((profiles, Some(RecoveredProfiles { backup, error })))
+}
+
+pub fn save_profiles(app: &AppHandle, profiles: &[Profile]) -> Result<(), String> {
+    let previous = load_profiles(app).unwrap_or_default();
+    let data = serialize_profiles(profiles)?;
+    storage::write_with_backup(&storage::backups_dir(app)?, &profiles_path(app)?, data.as_bytes())?;
+    history::record_changes(app, &previous, profiles);
+    Ok(())
+}
+
+pub fn list_profiles_backups(app: &AppHandle) -> Result<Vec<storage::BackupInfo>, String> {
+    Ok(storage::list_backups(&storage::backups_dir(app)?, &profiles_path(app)?, is_valid_profiles_file))
+}
+
+/// Replace `profiles.json` with the given backup (the current file is backed up first)
+pub fn restore_profiles_backup(app: &AppHandle, file_name: &str) -> Result<Vec<Profile>, String> {
+    let backup = list_profiles_backups(app)?
+        .into_iter()
+        .find(|b| b.file_name == file_name)
+        .ok_or_else(|| format!("Backup not found: {}", file_name))?;
+    let data = fs::read_to_string(&backup.path).map_err(|e| e.to_string())?;
+    let profiles = parse_profiles(&data)
+        .map_err(|e| format!("Backup {} is not usable: {}", file_name, e))?;
+    save_profiles(app, &profiles)?;
+    eprintln!("[profile_store] Restored profiles from backup {}", file_name);
+    Ok(profiles)
+}
+
+/// Load a single profile by id
+pub fn find_profile(app: &AppHandle, profile_id: &str) -> Result<Profile, String> {
+    load_profiles(app)?
+        .into_iter()
+        .find(|p| p.id == profile_id)
+        .ok_or_else(|| "Profile not found".to_string())
+}
@@ -0,0 +1,129 @@
+//! Running Windows builds through Proton or Wine.
+//!
+//! Under Wine the loader's proxy DLL (`winhttp.dll` for BepInEx, `version.dll`
+//! for MelonLoader) is ignored unless `WINEDLLOVERRIDES` prefers the native
+//! copy. Steam games launched by Steam only get that through their launch
+//! options, so for Proton games we generate the string to paste there; games
+//! started here through a chosen Wine or Proton binary get it set directly.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{game_defs::GameDefinition, mod_loader::ModLoader, vdf};
+
+/// Wine or Proton binary (and prefix) to run a game with
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WineLaunch {
+    /// `wine`/`wine64` or a Proton install's `proton` script
+    pub binary: String,
+    /// WINEPREFIX for Wine, compatdata folder for Proton; None uses the
+    /// default prefix (Wine) or the game's Steam compatdata (Proton)
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+impl WineLaunch {
+    fn is_proton(&self) -> bool {
+        Path::new(&self.binary).file_name().map(|n| n == "proton").unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProtonStatus {
+    pub uses_proton: bool,
+    /// Tool forced in Steam's compatibility settings (e.g. "proton_9"),
+    /// None if the game uses Steam's default or runs natively
+    pub compat_tool: Option<String>,
+    /// The game's `steamapps/compatdata/<appid>` folder
+    pub compat_data: Option<String>,
+    /// What to put in the game's Steam launch options
+    pub launch_options: String,
+}
+
+/// Compatibility tool Steam's `config.vdf` maps the app to
+fn compat_tool(steam_path: &Path, app_id: u32) -> Option<String> {
+    let text = std::fs::read_to_string(steam_path.join("config").join("config.vdf")).ok()?;
+    let root = vdf::parse(&text).ok()?;
+    let mapping = root.get_object("InstallConfigStore")?
+        .get_object("Software")?
+        .get_object("Valve")?
+        .get_object("Steam")?
+        .get_object("CompatToolMapping")?;
+    let name = mapping.get_object(&app_id.to_string())?.get_str("name")?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Proton prefix of the app, in whichever library Steam created it
+fn compat_data(libraries: &[vdf::LibraryFolder], app_id: u32) -> Option<PathBuf> {
+    libraries.iter()
+        .map(|lib| lib.steamapps().join("compatdata").join(app_id.to_string()))
+        .find(|path| path.is_dir())
+}
+
+fn quote_arg(arg: &str) -> String {
+    if arg.contains(char::is_whitespace) {
+        format!("\"{}\"", arg.replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+/// Steam launch options that make the loader's proxy DLL load under Proton
+pub fn launch_options(loader: &dyn ModLoader, def: Option<&GameDefinition>) -> String {
+    let mut options = match loader.wine_dll_overrides() {
+        Some(overrides) => format!("WINEDLLOVERRIDES=\"{}\" %command%", overrides),
+        None => "%command%".to_string(),
+    };
+    for arg in def.map(|d| d.quirks.launch_args.as_slice()).unwrap_or_default() {
+        options.push(' ');
+        options.push_str(&quote_arg(arg));
+    }
+    options
+}
+
+pub fn detect(steam_path: &Path, libraries: &[vdf::LibraryFolder], app_id: u32, loader: &dyn ModLoader, def: Option<&GameDefinition>) -> ProtonStatus {
+    let compat_tool = compat_tool(steam_path, app_id);
+    let compat_data = compat_data(libraries, app_id);
+    ProtonStatus {
+        uses_proton: compat_tool.is_some() || compat_data.is_some(),
+        compat_tool,
+        compat_data: compat_data.map(|p| p.to_string_lossy().to_string()),
+        launch_options: launch_options(loader, def),
+    }
+}
+
+/// Command running a Windows `exe` through Wine or Proton with the loader's
+/// DLL override. `steam` is the Steam root and the game's compatdata, which
+/// Proton needs when no prefix is configured.
+pub fn wine_command(
+    config: &WineLaunch,
+    exe: &Path,
+    game_root: &Path,
+    loader: &dyn ModLoader,
+    def: Option<&GameDefinition>,
+    steam: Option<(&Path, PathBuf)>,
+) -> Result<tokio::process::Command, String> {
+    let mut command = tokio::process::Command::new(&config.binary);
+    command.current_dir(game_root);
+
+    if config.is_proton() {
+        let (steam_path, default_prefix) = steam.ok_or("Proton needs the game's Steam compatdata folder or a prefix")?;
+        let prefix = config.prefix.as_ref().map(PathBuf::from).unwrap_or(default_prefix);
+        command.arg("run");
+        command.env("STEAM_COMPAT_DATA_PATH", prefix);
+        command.env("STEAM_COMPAT_CLIENT_INSTALL_PATH", steam_path);
+    } else if let Some(prefix) = &config.prefix {
+        command.env("WINEPREFIX", prefix);
+    }
+    if let Some(overrides) = loader.wine_dll_overrides() {
+        command.env("WINEDLLOVERRIDES", overrides);
+    }
+
+    // Doorstop reads its config file next to the proxy DLL, so no loader arguments
+    command.arg(exe);
+    if let Some(def) = def {
+        command.args(&def.quirks.launch_args);
+    }
+    eprintln!("[launch_game] Running {:?}", command.as_std());
+    Ok(command)
+}
@@ -0,0 +1,350 @@
+//! Dependency resolution over the cached package index.
+//!
+//! Every dependency string ("Author-Name-1.2.3") is a version request. When a
+//! package is requested at several versions, the highest requested version
+//! that exists in the index wins; if none of them exist, the latest one does.
+//! Because a different version can have a different dependency list, a package
+//! is re-expanded whenever its chosen version changes, and the version it
+//! moved away from withdraws the requests it made (a package left without
+//! requests is dropped). Only requests of currently chosen versions count, so
+//! the result follows "highest requested version wins" over the final graph.
+//! With withdrawals versions can move both ways, so the number of expansions
+//! is capped; real dependency graphs settle long before that.
+
+use serde::Serialize;
+use std::{cmp::Ordering, collections::{HashMap, HashSet, VecDeque}};
+
+use crate::package_index::{compare_versions, split_versioned_name, PackageIndex};
+use crate::profile_store::InstalledMod;
+
+/// Bound on package expansions, in case dependency metadata makes versions
+/// flip back and forth
+const MAX_STEPS: usize = 100_000;
+
+/// Requester label for packages asked for directly
+pub const REQUESTED: &str = "requested";
+/// Requester label for mods already in the profile
+pub const PROFILE: &str = "profile";
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanAction {
+    Install,
+    Keep,
+    Upgrade,
+    Downgrade,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct VersionRequest {
+    pub version: String,
+    pub required_by: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ResolvedPackage {
+    /// "Author-Name"
+    pub name: String,
+    pub version: String,
+    /// "Author-Name-1.2.3"
+    pub full_name: String,
+    pub download_url: Option<String>,
+    pub file_size: u64,
+    pub dependencies: Vec<String>,
+    /// Versioned names of the packages (or "requested"/"profile") that pulled this one in
+    pub required_by: Vec<String>,
+    pub installed_version: Option<String>,
+    pub action: PlanAction,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct VersionConflict {
+    pub name: String,
+    pub chosen: String,
+    pub requests: Vec<VersionRequest>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MissingPackage {
+    pub name: String,
+    pub version: Option<String>,
+    pub required_by: Vec<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct Resolution {
+    /// Dependencies come before the packages that need them
+    pub packages: Vec<ResolvedPackage>,
+    pub conflicts: Vec<VersionConflict>,
+    pub missing: Vec<MissingPackage>,
+}
+
+/// A starting point for resolution
+pub struct Root {
+    pub name: String,
+    pub version: Option<String>,
+    pub requester: &'static str,
+}
+
+impl Root {
+    /// Parse "Author-Name" or "Author-Name-1.2.3"
+    pub fn parse(spec: &str, requester: &'static str) -> Self {
+        let (name, version) = split_versioned_name(spec);
+        Self { name: name.to_string(), version: version.map(|v| v.to_string()), requester }
+    }
+
+    pub fn from_installed(m: &InstalledMod) -> Self {
+        Self {
+            name: m.package_name().to_string(),
+            version: Some(m.version_number.clone()),
+            requester: PROFILE,
+        }
+    }
+}
+
+struct Resolver<'a, 'i> {
+    index: &'i PackageIndex<'a>,
+    requests: HashMap<String, Vec<VersionRequest>>,
+    chosen: HashMap<String, String>,
+    worklist: VecDeque<String>,
+}
+
+impl<'a, 'i> Resolver<'a, 'i> {
+    /// Highest requested version that exists, else the latest available
+    fn pick(&self, package: &serde_json::Value, requests: &[VersionRequest]) -> Option<String> {
+        requests.iter()
+            .map(|r| r.version.as_str())
+            .filter(|v| PackageIndex::version(package, v).is_some())
+            .max_by(|a, b| compare_versions(a, b))
+            .map(|v| v.to_string())
+            .or_else(|| {
+                PackageIndex::latest(package)
+                    .and_then(|v| v["version_number"].as_str())
+                    .map(|v| v.to_string())
+            })
+    }
+
+    fn request(&mut self, name: &str, version: Option<&str>, required_by: &str) {
+        let Some(package) = self.index.get(name) else { return };
+        let key = name.to_lowercase();
+        let version = match version {
+            Some(v) => v.to_string(),
+            None => match PackageIndex::latest(package).and_then(|v| v["version_number"].as_str()) {
+                Some(v) => v.to_string(),
+                None => return,
+            },
+        };
+
+        self.requests.entry(key.clone()).or_default()
+            .push(VersionRequest { version, required_by: required_by.to_string() });
+        self.choose(&key);
+    }
+
+    /// Re-pick the version of `key` from its requests. A version that is no
+    /// longer chosen withdraws the requests it made; a package nobody asks for
+    /// anymore is dropped.
+    fn choose(&mut self, key: &str) {
+        let Some(package) = self.index.get(key) else { return };
+        let requests = self.requests.get(key).cloned().unwrap_or_default();
+        let best = if requests.is_empty() { None } else { self.pick(package, &requests) };
+        if self.chosen.get(key) == best.as_ref() {
+            return;
+        }
+
+        let previous = match best {
+            Some(best) => {
+                self.worklist.push_back(key.to_string());
+                self.chosen.insert(key.to_string(), best)
+            }
+            None => self.chosen.remove(key),
+        };
+        let superseded = previous
+            .and_then(|v| PackageIndex::version(package, &v))
+            .and_then(|v| v["full_name"].as_str());
+        if let Some(requester) = superseded {
+            self.withdraw(requester);
+        }
+    }
+
+    /// Drop the requests `requester` ("Author-Name-1.2.3") made and re-pick
+    /// the packages it asked for
+    fn withdraw(&mut self, requester: &str) {
+        let mut affected = Vec::new();
+        for (key, requests) in self.requests.iter_mut() {
+            let before = requests.len();
+            requests.retain(|r| r.required_by != requester);
+            if requests.len() != before {
+                affected.push(key.clone());
+            }
+        }
+        for key in affected {
+            self.choose(&key);
+        }
+    }
+
+    fn run(&mut self, roots: &[Root]) {
+        for root in roots {
+            self.request(&root.name, root.version.as_deref(), root.requester);
+        }
+        let mut steps = 0;
+        while let Some(key) = self.worklist.pop_front() {
+            steps += 1;
+            if steps > MAX_STEPS {
+                eprintln!("[resolver] Versions keep changing after {} steps, giving up", MAX_STEPS);
+                break;
+            }
+            let (Some(package), Some(version)) = (self.index.get(&key), self.chosen.get(&key).cloned()) else { continue };
+            let Some(version_data) = PackageIndex::version(package, &version) else { continue };
+            let requester = version_data["full_name"].as_str().unwrap_or(&key).to_string();
+            for dep in PackageIndex::dependencies(version_data) {
+                let (dep_name, dep_version) = split_versioned_name(dep);
+                self.request(dep_name, dep_version, &requester);
+            }
+        }
+    }
+}
+
+/// Resolve the transitive closure of `roots`. `installed` is only used to
+/// label each package with the action needed to reach the chosen version.
+pub fn resolve(index: &PackageIndex, roots: &[Root], installed: &[InstalledMod]) -> Resolution {
+    let mut resolver = Resolver {
+        index,
+        requests: HashMap::new(),
+        chosen: HashMap::new(),
+        worklist: VecDeque::new(),
+    };
+    resolver.run(roots);
+    let chosen = resolver.chosen;
+
+    // Walk the final graph again so edges from superseded versions don't linger
+    let mut requests: HashMap<String, Vec<VersionRequest>> = HashMap::new();
+    let mut missing: HashMap<String, MissingPackage> = HashMap::new();
+    let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+    let mut root_keys = Vec::new();
+
+    let mut note = |name: &str, version: Option<&str>, required_by: &str,
+                    requests: &mut HashMap<String, Vec<VersionRequest>>| -> Option<String> {
+        let key = name.to_lowercase();
+        let Some(package) = index.get(name) else {
+            let entry = missing.entry(key).or_insert_with(|| MissingPackage {
+                name: name.to_string(),
+                version: version.map(|v| v.to_string()),
+                required_by: vec![],
+                reason: "Package not found".to_string(),
+            });
+            entry.required_by.push(required_by.to_string());
+            return None;
+        };
+        if let Some(v) = version {
+            if PackageIndex::version(package, v).is_none() {
+                let entry = missing.entry(format!("{}-{}", key, v)).or_insert_with(|| MissingPackage {
+                    name: package["full_name"].as_str().unwrap_or(name).to_string(),
+                    version: Some(v.to_string()),
+                    required_by: vec![],
+                    reason: format!("Version {} not found, using {}", v, chosen.get(&key).map(|s| s.as_str()).unwrap_or("?")),
+                });
+                entry.required_by.push(required_by.to_string());
+            }
+        }
+        // Unversioned requests ("latest") count as asking for whatever was chosen
+        if let Some(v) = version.or_else(|| chosen.get(&key).map(|s| s.as_str())) {
+            requests.entry(key.clone()).or_default()
+                .push(VersionRequest { version: v.to_string(), required_by: required_by.to_string() });
+        }
+        Some(key)
+    };
+
+    for root in roots {
+        if let Some(key) = note(&root.name, root.version.as_deref(), root.requester, &mut requests) {
+            root_keys.push(key);
+        }
+    }
+
+    let mut queue: VecDeque<String> = root_keys.iter().cloned().collect();
+    let mut seen: HashSet<String> = HashSet::new();
+    while let Some(key) = queue.pop_front() {
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        let (Some(package), Some(version)) = (index.get(&key), chosen.get(&key)) else { continue };
+        let Some(version_data) = PackageIndex::version(package, version) else { continue };
+        let requester = version_data["full_name"].as_str().unwrap_or(&key).to_string();
+        for dep in PackageIndex::dependencies(version_data) {
+            let (dep_name, dep_version) = split_versioned_name(dep);
+            if let Some(dep_key) = note(dep_name, dep_version, &requester, &mut requests) {
+                edges.entry(key.clone()).or_default().push(dep_key.clone());
+                queue.push_back(dep_key);
+            }
+        }
+    }
+
+    // Dependencies first (post-order DFS from the roots)
+    fn visit(key: &str, edges: &HashMap<String, Vec<String>>, done: &mut HashSet<String>, order: &mut Vec<String>) {
+        if !done.insert(key.to_string()) {
+            return;
+        }
+        for dep in edges.get(key).into_iter().flatten() {
+            visit(dep, edges, done, order);
+        }
+        order.push(key.to_string());
+    }
+    let mut order = Vec::new();
+    let mut done = HashSet::new();
+    for key in &root_keys {
+        visit(key, &edges, &mut done, &mut order);
+    }
+
+    let installed_versions: HashMap<String, &str> = installed.iter()
+        .map(|m| (m.package_name().to_lowercase(), m.version_number.as_str()))
+        .collect();
+
+    let mut resolution = Resolution::default();
+    for key in order {
+        let (Some(package), Some(version)) = (index.get(&key), chosen.get(&key)) else { continue };
+        let Some(version_data) = PackageIndex::version(package, version) else { continue };
+        let name = package["full_name"].as_str().unwrap_or(&key).to_string();
+        let key_requests = requests.get(&key).cloned().unwrap_or_default();
+
+        let mut required_by: Vec<String> = key_requests.iter().map(|r| r.required_by.clone()).collect();
+        required_by.sort();
+        required_by.dedup();
+
+        let mut distinct: Vec<&str> = key_requests.iter().map(|r| r.version.as_str()).collect();
+        distinct.sort();
+        distinct.dedup();
+        if distinct.len() > 1 {
+            resolution.conflicts.push(VersionConflict {
+                name: name.clone(),
+                chosen: version.clone(),
+                requests: key_requests,
+            });
+        }
+
+        let installed_version = installed_versions.get(&key).map(|v| v.to_string());
+        let action = match installed_version.as_deref() {
+            None => PlanAction::Install,
+            Some(current) => match compare_versions(version, current) {
+                Ordering::Equal => PlanAction::Keep,
+                Ordering::Greater => PlanAction::Upgrade,
+                Ordering::Less => PlanAction::Downgrade,
+            },
+        };
+
+        resolution.packages.push(ResolvedPackage {
+            full_name: format!("{}-{}", name, version),
+            name,
+            version: version.clone(),
+            download_url: version_data["download_url"].as_str().map(|s| s.to_string()),
+            file_size: version_data["file_size"].as_u64().unwrap_or(0),
+            dependencies: PackageIndex::dependencies(version_data).iter().map(|d| d.to_string()).collect(),
+            required_by,
+            installed_version,
+            action,
+        });
+    }
+
+    resolution.missing = missing.into_values().collect();
+    resolution.missing.sort_by(|a, b| a.name.cmp(&b.name));
+    resolution
+}
@@ -0,0 +1,72 @@
+//! Finding Steam installs on this machine.
+//!
+//! Steam can live in several places on Linux: the native package
+//! (`~/.local/share/Steam`, usually also reachable through the `~/.steam/steam`
+//! symlink), Flatpak and Snap each keep their own copy, and macOS has
+//! `~/Library/Application Support/Steam`. Symlinked duplicates are reported once.
+
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::vdf;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SteamInstallation {
+    pub path: String,
+    /// "native", "flatpak", "snap" or "macos"
+    pub kind: &'static str,
+    pub library_count: usize,
+    /// Unix time `libraryfolders.vdf` (or `steamapps`) last changed, 0 if unknown
+    pub last_modified: u64,
+}
+
+fn candidates(home: &Path) -> Vec<(PathBuf, &'static str)> {
+    vec![
+        (home.join(".steam/steam"), "native"),
+        (home.join(".local/share/Steam"), "native"),
+        (home.join(".steam/root"), "native"),
+        (home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"), "flatpak"),
+        (home.join(".var/app/com.valvesoftware.Steam/.steam/steam"), "flatpak"),
+        (home.join("snap/steam/common/.local/share/Steam"), "snap"),
+        (home.join("snap/steam/common/.steam/steam"), "snap"),
+        (home.join("Library/Application Support/Steam"), "macos"),
+    ]
+}
+
+fn modified_secs(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+/// Every Steam root that has a `steamapps` folder, most recently used first
+pub fn detect_installations() -> Vec<SteamInstallation> {
+    let Some(home) = dirs::home_dir() else { return Vec::new() };
+
+    let mut seen = Vec::new();
+    let mut found = Vec::new();
+    for (path, kind) in candidates(&home) {
+        let steamapps = path.join("steamapps");
+        if !steamapps.is_dir() {
+            continue;
+        }
+        let real = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if seen.contains(&real) {
+            continue;
+        }
+        seen.push(real);
+
+        let library_count = vdf::library_folders(&path).iter().filter(|lib| lib.steamapps().is_dir()).count();
+        let last_modified = modified_secs(&steamapps.join("libraryfolders.vdf"))
+            .or_else(|| modified_secs(&steamapps))
+            .unwrap_or(0);
+        eprintln!("[steam] Found {} Steam at {:?} ({} libraries)", kind, path, library_count);
+        found.push(SteamInstallation { path: path.to_string_lossy().to_string(), kind, library_count, last_modified });
+    }
+
+    found.sort_by_key(|install| std::cmp::Reverse(install.last_modified));
+    found
+}
@@ -0,0 +1,156 @@
+//! Crash-safe writes and rolling backups for the app's JSON state files.
+//!
+//! Writes go to a temp file in the same directory, are fsynced and then renamed
+//! over the target, so a crash leaves either the old or the new file, never a
+//! truncated one. Before each overwrite the previous version is copied into
+//! `<app_data>/backups/` as `<stem>-<timestamp>.json`, keeping the newest
+//! `MAX_BACKUPS` per file.
+
+use serde::Serialize;
+use std::{fs, io::Write, path::{Path, PathBuf}};
+use tauri::{AppHandle, Manager};
+
+/// How many backups are kept per state file
+pub const MAX_BACKUPS: usize = 10;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub path: String,
+    /// Modification time, seconds since the Unix epoch
+    pub created: u64,
+    pub size: u64,
+    /// Whether the backup still parses as a valid state file
+    pub valid: bool,
+}
+
+/// Seconds since the Unix epoch
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn backups_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join("backups"))
+}
+
+/// Write `data` to `path` through a temp file + fsync + rename
+pub fn atomic_write(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(parent)?;
+
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("state");
+    let tmp_path = parent.join(format!(".{}.tmp", file_name));
+
+    {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    // Persist the rename itself (directory entries are only fsyncable on unix)
+    #[cfg(unix)]
+    {
+        if let Ok(dir) = fs::File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
+
+fn backup_stem(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("state")
+        .to_string()
+}
+
+/// List the backups of `path`, newest first
+pub fn list_backups(backup_dir: &Path, path: &Path, is_valid: impl Fn(&str) -> bool) -> Vec<BackupInfo> {
+    let prefix = format!("{}-", backup_stem(path));
+    let mut backups: Vec<BackupInfo> = fs::read_dir(backup_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| {
+                    let file_name = e.file_name().to_string_lossy().to_string();
+                    if !file_name.starts_with(&prefix) || !file_name.ends_with(".json") {
+                        return None;
+                    }
+                    let meta = e.metadata().ok()?;
+                    let created = meta.modified().ok()
+                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0);
+                    let valid = fs::read_to_string(e.path()).map(|c| is_valid(&c)).unwrap_or(false);
+                    Some(BackupInfo {
+                        file_name,
+                        path: e.path().to_string_lossy().to_string(),
+                        created,
+                        size: meta.len(),
+                        valid,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Timestamps in the file name sort chronologically
+    backups.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    backups
+}
+
+/// Copy the current version of `path` into `backup_dir` and prune old backups
+fn backup_current(backup_dir: &Path, path: &Path) -> std::io::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let current = fs::read(path)?;
+    fs::create_dir_all(backup_dir)?;
+
+    let existing = list_backups(backup_dir, path, |_| true);
+
+    // Don't fill the rotation with identical copies
+    let unchanged = existing.first()
+        .and_then(|newest| fs::read(&newest.path).ok())
+        .map(|newest| newest == current)
+        .unwrap_or(false);
+
+    if !unchanged {
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S%.3f");
+        let backup_path = backup_dir.join(format!("{}-{}.json", backup_stem(path), timestamp));
+        atomic_write(&backup_path, &current)?;
+    }
+
+    for old in list_backups(backup_dir, path, |_| true).iter().skip(MAX_BACKUPS) {
+        eprintln!("[storage] Pruning old backup: {}", old.file_name);
+        let _ = fs::remove_file(&old.path);
+    }
+
+    Ok(())
+}
+
+/// Back up the current file, then atomically replace it with `data`
+pub fn write_with_backup(backup_dir: &Path, path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Err(e) = backup_current(backup_dir, path) {
+        // A failed backup must not block saving the user's changes
+        eprintln!("[storage] Failed to back up {:?}: {}", path, e);
+    }
+    atomic_write(path, data).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+/// Contents of the newest backup of `path` that passes `is_valid`
+pub fn newest_valid_backup(backup_dir: &Path, path: &Path, is_valid: impl Fn(&str) -> bool) -> Option<(BackupInfo, String)> {
+    list_backups(backup_dir, path, &is_valid)
+        .into_iter()
+        .filter(|b| b.valid)
+        .find_map(|b| fs::read_to_string(&b.path).ok().map(|content| (b, content)))
+}
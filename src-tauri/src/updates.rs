@@ -0,0 +1,439 @@
+//! Checking a profile's mods for newer versions and switching them over.
+//!
+//! Updates are staged first: every new archive is downloaded and extracted
+//! into `profiles/<id>/.update-staging/<batch>/` before anything installed is
+//! touched. Only when all of them made it are the old files swapped out for
+//! the new ones (in the game folder if the profile is deployed there, and in
+//! the legacy profile cache if the mod is cached). The old files are moved
+//! aside while swapping, so a failure puts everything back. Mods installed
+//! before install records were kept are found by their folder and the
+//! deployment ledger; if neither knows their files the switch is refused. New
+//! archives are checked against the profile's lockfile and recorded in it
+//! once swapped.
+
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tauri::AppHandle;
+
+use crate::{
+    deployment,
+    downloads::{self, DownloadManager, ItemState},
+    install_rules::{self, InstallRecord, PlacedFile},
+    lockfile::{self, LockedPackage},
+    mod_loader,
+    package_index::{compare_versions, split_versioned_name, PackageIndex},
+    profile_store::{self, Profile},
+};
+
+const STAGING_DIR: &str = ".update-staging";
+const BACKUP_DIR: &str = ".update-backup";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DependencyChange {
+    pub package: String,
+    /// None if the dependency is new
+    pub from: Option<String>,
+    /// None if the dependency was dropped
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ModUpdate {
+    /// "Author-Name"
+    pub package: String,
+    pub current_version: String,
+    /// None if the package is no longer listed
+    pub latest_version: Option<String>,
+    /// A newer version exists and the mod isn't pinned
+    pub update_available: bool,
+    pub pinned: bool,
+    pub enabled: bool,
+    /// How the latest version's dependencies differ from the current one's
+    pub dependency_changes: Vec<DependencyChange>,
+}
+
+/// Dependencies of a version as "Author-Name" -> version
+fn dependency_map(version: Option<&serde_json::Value>) -> HashMap<String, String> {
+    version.map(PackageIndex::dependencies).unwrap_or_default()
+        .into_iter()
+        .map(|dep| {
+            let (name, version) = split_versioned_name(dep);
+            (name.to_string(), version.unwrap_or("").to_string())
+        })
+        .collect()
+}
+
+pub fn dependency_changes(from: Option<&serde_json::Value>, to: Option<&serde_json::Value>) -> Vec<DependencyChange> {
+    let (before, after) = (dependency_map(from), dependency_map(to));
+    let mut changes: Vec<DependencyChange> = after.iter()
+        .filter(|(name, version)| before.get(*name) != Some(version))
+        .map(|(name, version)| DependencyChange { package: name.clone(), from: before.get(name).cloned(), to: Some(version.clone()) })
+        .chain(before.iter()
+            .filter(|(name, _)| !after.contains_key(*name))
+            .map(|(name, version)| DependencyChange { package: name.clone(), from: Some(version.clone()), to: None }))
+        .collect();
+    changes.sort_by(|a, b| a.package.cmp(&b.package));
+    changes
+}
+
+/// Current vs latest version of every mod in the profile
+pub fn check(index: &PackageIndex, profile: &Profile) -> Vec<ModUpdate> {
+    profile.mods.iter()
+        .map(|m| {
+            let package = index.get(m.package_name());
+            let latest = package.and_then(PackageIndex::latest);
+            let latest_version = latest.and_then(|v| v["version_number"].as_str()).map(str::to_string);
+            let is_newer = latest_version.as_deref()
+                .map(|v| compare_versions(v, &m.version_number).is_gt())
+                .unwrap_or(false);
+            let current = package.and_then(|p| PackageIndex::version(p, &m.version_number));
+            ModUpdate {
+                package: m.package_name().to_string(),
+                current_version: m.version_number.clone(),
+                latest_version,
+                update_available: is_newer && !m.pinned,
+                pinned: m.pinned,
+                enabled: m.enabled,
+                dependency_changes: if is_newer { dependency_changes(current, latest) } else { Vec::new() },
+            }
+        })
+        .collect()
+}
+
+/// A mod to switch to another version
+#[derive(Debug, Clone)]
+pub struct Target {
+    /// "Author-Name"
+    pub package: String,
+    /// Full name of the installed version
+    pub from: String,
+    pub version: String,
+    pub download_url: String,
+    pub file_size: Option<u64>,
+}
+
+impl Target {
+    /// From the package listing; None if the version isn't listed
+    pub fn new(package: &serde_json::Value, installed_full_name: &str, version: &serde_json::Value) -> Option<Self> {
+        Some(Target {
+            package: package["full_name"].as_str()?.to_string(),
+            from: installed_full_name.to_string(),
+            version: version["version_number"].as_str()?.to_string(),
+            download_url: version["download_url"].as_str()?.to_string(),
+            file_size: version["file_size"].as_u64(),
+        })
+    }
+
+    fn full_name(&self) -> String {
+        format!("{}-{}", self.package, self.version)
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SwitchedMod {
+    pub package: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SkippedMod {
+    pub package: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct UpdateOutcome {
+    /// Download results; nothing is switched unless all of them are done
+    pub results: Vec<downloads::ItemResult>,
+    pub updated: Vec<SwitchedMod>,
+    pub skipped: Vec<SkippedMod>,
+    /// The profile after the switch, None if nothing changed
+    pub profile: Option<Profile>,
+}
+
+/// `rename`, or copy + delete across filesystems
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
+
+/// File moves that can be undone until committed
+struct Swap {
+    backup_dir: PathBuf,
+    /// (original location, backup location)
+    moved_aside: Vec<(PathBuf, PathBuf)>,
+    placed: Vec<PathBuf>,
+}
+
+impl Swap {
+    fn new(backup_dir: PathBuf) -> Self {
+        Self { backup_dir, moved_aside: Vec::new(), placed: Vec::new() }
+    }
+
+    fn remove(&mut self, path: &Path) -> io::Result<()> {
+        if !path.is_file() {
+            return Ok(());
+        }
+        let backup = self.backup_dir.join(self.moved_aside.len().to_string());
+        move_file(path, &backup)?;
+        self.moved_aside.push((path.to_path_buf(), backup));
+        Ok(())
+    }
+
+    fn place(&mut self, from: &Path, to: &Path) -> io::Result<()> {
+        self.remove(to)?;
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(from, to)?;
+        self.placed.push(to.to_path_buf());
+        Ok(())
+    }
+
+    fn rollback(self) {
+        for path in self.placed.iter().rev() {
+            let _ = fs::remove_file(path);
+        }
+        for (original, backup) in self.moved_aside.iter().rev() {
+            if let Err(e) = move_file(backup, original) {
+                eprintln!("[updates] Failed to restore {:?}: {}", original, e);
+            }
+        }
+        let _ = fs::remove_dir_all(&self.backup_dir);
+    }
+
+    /// Drop the backups and the folders the old files leave empty
+    fn commit(self, roots: &[PathBuf]) {
+        for (original, _) in &self.moved_aside {
+            let mut dir = original.parent();
+            while let Some(d) = dir {
+                if roots.iter().any(|root| root == d) || fs::remove_dir(d).is_err() {
+                    break;
+                }
+                dir = d.parent();
+            }
+        }
+        let _ = fs::remove_dir_all(&self.backup_dir);
+    }
+}
+
+/// Record for a mod installed before install records were kept: the files in
+/// the folders named after its package under the loader's mods folder of each
+/// root, plus what the ledger says it placed in the deployed game. None if
+/// neither finds anything.
+fn legacy_record(app: &AppHandle, profile: &Profile, roots: &[PathBuf], deployed_root: Option<&Path>, target: &Target) -> Option<InstallRecord> {
+    let mods_dir = mod_loader::for_game(&profile.game_identifier).mods_dir();
+    let mut paths: BTreeSet<String> = BTreeSet::new();
+    for root in roots {
+        let Ok(entries) = fs::read_dir(root.join(mods_dir)) else { continue };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !split_versioned_name(&name).0.eq_ignore_ascii_case(&target.package) {
+                continue;
+            }
+            let folder = entry.path();
+            paths.extend(walkdir::WalkDir::new(&folder).into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .filter_map(|e| {
+                    let inner = e.path().strip_prefix(&folder).ok()?.to_string_lossy().replace('\\', "/");
+                    Some(format!("{}/{}/{}", mods_dir, name, inner))
+                }));
+        }
+    }
+    if let Some(game_root) = deployed_root {
+        let ledger = deployment::load_ledger(app, &profile.game_identifier, game_root);
+        paths.extend(ledger.entries.iter()
+            .filter(|(_, entry)| entry.kind == deployment::EntryKind::File && entry.package.as_deref() == Some(target.from.as_str()))
+            .map(|(relative, _)| relative.clone()));
+    }
+    if paths.is_empty() {
+        return None;
+    }
+    eprintln!("[updates] No install record for {}, found {} files of it by folder and ledger", target.from, paths.len());
+    Some(InstallRecord {
+        package: target.from.clone(),
+        files: paths.into_iter().map(|path| PlacedFile { path, rule: "legacy".to_string() }).collect(),
+    })
+}
+
+fn restore_ledger(app: &AppHandle, game_identifier: &str, ledger: Option<deployment::Ledger>) {
+    if let Some(ledger) = ledger {
+        if let Err(e) = deployment::restore_ledger(app, game_identifier, ledger) {
+            eprintln!("[updates] Failed to restore the deployment ledger: {}", e);
+        }
+    }
+}
+
+/// Whether the legacy profile cache holds this install's files
+fn is_cached(profile_dir: &Path, record: Option<&InstallRecord>) -> bool {
+    record.map(|r| !r.files.is_empty() && r.files.iter().any(|f| profile_dir.join(&f.path).is_file())).unwrap_or(false)
+}
+
+/// Download `targets`, then swap each mod's installed files and profile entry
+/// to the target version, all or nothing
+pub async fn switch_versions(app: &AppHandle, manager: &DownloadManager, batch_id: String, profile_id: &str, targets: Vec<Target>) -> Result<UpdateOutcome, String> {
+    let profile = profile_store::find_profile(app, profile_id)?;
+    let profile_dir = profile_store::profile_dir(app, profile_id)?;
+    let staging = profile_dir.join(STAGING_DIR).join(&batch_id);
+    let deployed_root = deployment::get(app, &profile.game_identifier)
+        .filter(|d| d.profile_id == profile_id)
+        .map(|d| PathBuf::from(d.game_path));
+    let mut roots: Vec<PathBuf> = deployed_root.iter().cloned().collect();
+    roots.push(profile_dir.clone());
+
+    // The installed files of every mod must be known, or the new version
+    // would end up next to the old one
+    let mut old_records: HashMap<String, InstallRecord> = HashMap::new();
+    for target in &targets {
+        let record = install_rules::load_record(&profile_dir, &target.from)
+            .or_else(|| legacy_record(app, &profile, &roots, deployed_root.as_deref(), target))
+            .ok_or_else(|| format!("Can't tell which files belong to {}; reinstall it instead of switching versions", target.from))?;
+        old_records.insert(target.from.clone(), record);
+    }
+    let _ = fs::remove_dir_all(&staging);
+
+    // 1. Download and extract everything into staging
+    let items: Vec<downloads::DownloadItem> = targets.iter()
+        .map(|t| downloads::DownloadItem { mod_name: t.full_name(), download_url: t.download_url.clone(), expected_size: t.file_size })
+        .collect();
+    let staged: Arc<Mutex<HashMap<String, InstallRecord>>> = Arc::default();
+    let locked: Arc<Mutex<Vec<(String, LockedPackage)>>> = Arc::default();
+    let (stage_records, stage_locks, stage_root, lock_dir, game_identifier) =
+        (staged.clone(), locked.clone(), staging.clone(), profile_dir.clone(), profile.game_identifier.clone());
+    let results = downloads::run_batch(app.clone(), manager, batch_id, items, downloads::DEFAULT_CONCURRENCY, move |item, archive| {
+        let lock = lockfile::verify(&lock_dir, &item.mod_name, &item.download_url, archive)?;
+        let (record, _) = crate::extract_package(&game_identifier, &item.mod_name, archive, &stage_root.join(&item.mod_name))?;
+        stage_records.lock().unwrap_or_else(|e| e.into_inner()).insert(item.mod_name.clone(), record);
+        stage_locks.lock().unwrap_or_else(|e| e.into_inner()).push(lock);
+        Ok(())
+    }).await?;
+
+    if results.iter().any(|r| r.state != ItemState::Done) {
+        eprintln!("[updates] Not all downloads finished, leaving profile {} untouched", profile_id);
+        let _ = fs::remove_dir_all(&staging);
+        return Ok(UpdateOutcome { results, ..Default::default() });
+    }
+    let staged = std::mem::take(&mut *staged.lock().unwrap_or_else(|e| e.into_inner()));
+
+    // 2. Swap the files where the old versions live, keeping the game's
+    // ledger in step so it can be put back with them
+    let ledger_before = deployed_root.as_ref().map(|root| deployment::load_ledger(app, &profile.game_identifier, root));
+    let mut swap = Swap::new(profile_dir.join(BACKUP_DIR));
+
+    let swapped: Result<(), String> = targets.iter().try_for_each(|target| {
+        let full_name = target.full_name();
+        let new_record = staged.get(&full_name).ok_or_else(|| format!("{} was not staged", full_name))?;
+        let old_record = old_records.get(&target.from);
+        let enabled = profile.mods.iter().any(|m| m.full_name == target.from && m.enabled);
+
+        let mut destinations = Vec::new();
+        if enabled {
+            destinations.extend(deployed_root.iter().cloned());
+        }
+        if is_cached(&profile_dir, old_record) {
+            destinations.push(profile_dir.clone());
+        }
+        for root in &destinations {
+            let mut removed = Vec::new();
+            let mut placed = Vec::new();
+            // Configs the player already has are theirs: the old version's stay
+            // put and the new version's defaults don't overwrite them
+            for file in old_record.iter().flat_map(|r| r.files.iter()).filter(|f| f.rule != "config") {
+                swap.remove(&root.join(&file.path)).map_err(|e| format!("Failed to remove {}: {}", file.path, e))?;
+                removed.push(file.path.as_str());
+            }
+            for file in &new_record.files {
+                let dest = root.join(&file.path);
+                if file.rule == "config" && dest.exists() {
+                    continue;
+                }
+                swap.place(&staging.join(&full_name).join(&file.path), &dest)
+                    .map_err(|e| format!("Failed to install {}: {}", file.path, e))?;
+                placed.push(file.path.as_str());
+            }
+            if deployed_root.as_ref() == Some(root) {
+                deployment::disown(app, &profile.game_identifier, root, removed)?;
+                deployment::claim(app, &profile.game_identifier, profile_id, root, Some(&full_name), placed)?;
+            }
+        }
+        Ok(())
+    });
+    if let Err(e) = swapped {
+        eprintln!("[updates] Swap failed, rolling back: {}", e);
+        swap.rollback();
+        restore_ledger(app, &profile.game_identifier, ledger_before);
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    // 3. Records, lockfile and profile entries follow the files. Old records
+    // are only dropped once everything is saved; a failure puts it all back.
+    let original_profiles = profile_store::load_profiles(app)?;
+    let original_lockfile = lockfile::load(&profile_dir);
+    let locks = std::mem::take(&mut *locked.lock().unwrap_or_else(|e| e.into_inner()));
+    let saved = (|| -> Result<(Profile, Vec<SwitchedMod>), String> {
+        let mut profiles = original_profiles.clone();
+        let entry = profiles.iter_mut().find(|p| p.id == profile_id).ok_or("Profile not found")?;
+        let mut updated = Vec::new();
+        for target in &targets {
+            let full_name = target.full_name();
+            if let Some(record) = staged.get(&full_name) {
+                install_rules::save_record(&profile_dir, record)?;
+            }
+            for m in entry.mods.iter_mut().filter(|m| m.full_name == target.from) {
+                updated.push(SwitchedMod { package: target.package.clone(), from: m.version_number.clone(), to: target.version.clone() });
+                m.full_name = full_name.clone();
+                m.version_number = target.version.clone();
+            }
+        }
+        for (name, package) in locks {
+            lockfile::record(&profile_dir, name, package)?;
+        }
+        let switched = entry.clone();
+        profile_store::save_profiles(app, &profiles)?;
+        if let Some(game_root) = &deployed_root {
+            deployment::record(app, &switched.game_identifier, &switched, &game_root.to_string_lossy())?;
+        }
+        Ok((switched, updated))
+    })();
+
+    let (switched, updated) = match saved {
+        Ok(saved) => saved,
+        Err(e) => {
+            eprintln!("[updates] Saving the switch failed, rolling back: {}", e);
+            for target in &targets {
+                install_rules::remove_record(&profile_dir, &target.full_name());
+            }
+            let _ = lockfile::save(&profile_dir, &original_lockfile);
+            let _ = profile_store::save_profiles(app, &original_profiles);
+            swap.rollback();
+            restore_ledger(app, &profile.game_identifier, ledger_before);
+            if let Some(game_root) = &deployed_root {
+                let _ = deployment::record(app, &profile.game_identifier, &profile, &game_root.to_string_lossy());
+            }
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+    for target in &targets {
+        install_rules::remove_record(&profile_dir, &target.from);
+    }
+
+    swap.commit(&roots);
+    let _ = fs::remove_dir_all(profile_dir.join(STAGING_DIR));
+    eprintln!("[updates] Switched {} mods in profile {}", updated.len(), profile_id);
+    Ok(UpdateOutcome { results, updated, skipped: Vec::new(), profile: Some(switched) })
+}
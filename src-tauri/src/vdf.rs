@@ -0,0 +1,356 @@
+//! Steam's text KeyValues format (VDF/ACF).
+//!
+//! `parse` turns a file into nested `KeyValues`; on top of that,
+//! `library_folders` reads `steamapps/libraryfolders.vdf` (including the
+//! `apps` map of which library holds which app) and `read_app_manifest` reads
+//! `appmanifest_<appid>.acf`. Keys are matched case-insensitively, as Steam
+//! itself writes both `StateFlags` and `stateflags` depending on the version.
+
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    iter::Peekable,
+    path::{Path, PathBuf},
+    str::Chars,
+};
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    String(String),
+    Object(KeyValues),
+}
+
+/// Entries of one `{ ... }` block, in file order
+#[derive(Debug, Clone, Default)]
+pub struct KeyValues {
+    entries: Vec<(String, Value)>,
+}
+
+impl KeyValues {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            Value::String(s) => Some(s),
+            Value::Object(_) => None,
+        }
+    }
+
+    pub fn get_object(&self, key: &str) -> Option<&KeyValues> {
+        match self.get(key)? {
+            Value::Object(o) => Some(o),
+            Value::String(_) => None,
+        }
+    }
+
+    fn get_num<T: std::str::FromStr>(&self, key: &str) -> Option<T> {
+        self.get_str(key)?.trim().parse().ok()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VDF parse error on line {}: {}", self.line, self.message)
+    }
+}
+
+impl From<ParseError> for String {
+    fn from(e: ParseError) -> Self {
+        e.to_string()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Open,
+    Close,
+    /// `[$WIN32]`-style platform conditional, ignored
+    Conditional,
+}
+
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { chars: text.chars().peekable(), line: 1 }
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError { line: self.line, message: message.into() }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn skip_trivia(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() {
+                self.bump();
+            } else if c == '/' {
+                // "//" comment to end of line (a lone '/' starts a bare string)
+                let mut ahead = self.chars.clone();
+                ahead.next();
+                if ahead.peek() != Some(&'/') {
+                    return;
+                }
+                while let Some(c) = self.bump() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, ParseError> {
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => return Ok(s),
+                Some('\\') => match self.bump() {
+                    Some('\\') => s.push('\\'),
+                    Some('"') => s.push('"'),
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some(c) => {
+                        s.push('\\');
+                        s.push(c);
+                    }
+                    None => return Err(self.error("unterminated string")),
+                },
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>, ParseError> {
+        self.skip_trivia();
+        let Some(&c) = self.chars.peek() else { return Ok(None) };
+        match c {
+            '{' => {
+                self.bump();
+                Ok(Some(Token::Open))
+            }
+            '}' => {
+                self.bump();
+                Ok(Some(Token::Close))
+            }
+            '"' => {
+                self.bump();
+                self.quoted().map(|s| Some(Token::Str(s)))
+            }
+            '[' => {
+                while let Some(c) = self.bump() {
+                    if c == ']' {
+                        return Ok(Some(Token::Conditional));
+                    }
+                }
+                Err(self.error("unterminated conditional"))
+            }
+            _ => {
+                let mut s = String::new();
+                while let Some(&c) = self.chars.peek() {
+                    if c.is_whitespace() || matches!(c, '{' | '}' | '"') {
+                        break;
+                    }
+                    s.push(c);
+                    self.bump();
+                }
+                Ok(Some(Token::Str(s)))
+            }
+        }
+    }
+
+    /// Next token that isn't a conditional
+    fn next_significant(&mut self) -> Result<Option<Token>, ParseError> {
+        loop {
+            match self.next_token()? {
+                Some(Token::Conditional) => continue,
+                other => return Ok(other),
+            }
+        }
+    }
+}
+
+fn parse_block(lexer: &mut Lexer, nested: bool) -> Result<KeyValues, ParseError> {
+    let mut kv = KeyValues::default();
+    loop {
+        let key = match lexer.next_significant()? {
+            Some(Token::Str(key)) => key,
+            Some(Token::Close) if nested => return Ok(kv),
+            None if !nested => return Ok(kv),
+            None => return Err(lexer.error("missing '}'")),
+            Some(_) => return Err(lexer.error("expected a key")),
+        };
+        let value = match lexer.next_significant()? {
+            Some(Token::Str(value)) => Value::String(value),
+            Some(Token::Open) => Value::Object(parse_block(lexer, true)?),
+            _ => return Err(lexer.error(format!("missing value for '{}'", key))),
+        };
+        kv.entries.push((key, value));
+    }
+}
+
+pub fn parse(text: &str) -> Result<KeyValues, ParseError> {
+    parse_block(&mut Lexer::new(text.trim_start_matches('\u{feff}')), false)
+}
+
+/// A Steam library and the apps Steam says it holds
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryFolder {
+    pub path: PathBuf,
+    pub label: String,
+    /// App id -> size on disk in bytes (0 if the file doesn't say)
+    pub apps: HashMap<u32, u64>,
+}
+
+impl LibraryFolder {
+    pub fn steamapps(&self) -> PathBuf {
+        self.path.join("steamapps")
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppManifest {
+    pub app_id: u32,
+    pub name: String,
+    /// Folder under `steamapps/common`
+    pub install_dir: String,
+    pub build_id: u64,
+    pub state_flags: u32,
+    /// Unix timestamp of the last update
+    pub last_updated: u64,
+}
+
+/// StateFlags bit for a fully installed app
+const STATE_FULLY_INSTALLED: u32 = 4;
+
+impl AppManifest {
+    pub fn is_fully_installed(&self) -> bool {
+        self.state_flags & STATE_FULLY_INSTALLED != 0
+    }
+}
+
+fn read(path: &Path) -> Option<KeyValues> {
+    let text = fs::read_to_string(path).ok()?;
+    match parse(&text) {
+        Ok(kv) => Some(kv),
+        Err(e) => {
+            eprintln!("[vdf] {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Libraries listed in `libraryfolders.vdf`, with `steam_path` itself always first
+pub fn library_folders(steam_path: &Path) -> Vec<LibraryFolder> {
+    let mut folders = vec![LibraryFolder { path: steam_path.to_path_buf(), label: String::new(), apps: HashMap::new() }];
+
+    let root = read(&steam_path.join("steamapps").join("libraryfolders.vdf"));
+    let Some(list) = root.as_ref().and_then(|r| r.get_object("libraryfolders")) else {
+        return folders;
+    };
+
+    // Numbered entries are libraries; old files list just the path as a string
+    for (key, value) in list.iter().filter(|(k, _)| k.parse::<u32>().is_ok()) {
+        let folder = match value {
+            Value::String(path) => LibraryFolder { path: PathBuf::from(path), label: String::new(), apps: HashMap::new() },
+            Value::Object(entry) => {
+                let Some(path) = entry.get_str("path") else {
+                    eprintln!("[vdf] Library {} has no path", key);
+                    continue;
+                };
+                let apps = entry.get_object("apps")
+                    .map(|apps| {
+                        apps.iter()
+                            .filter_map(|(id, size)| {
+                                let size = match size {
+                                    Value::String(s) => s.parse().unwrap_or(0),
+                                    Value::Object(_) => 0,
+                                };
+                                Some((id.parse().ok()?, size))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                LibraryFolder { path: PathBuf::from(path), label: entry.get_str("label").unwrap_or("").to_string(), apps }
+            }
+        };
+
+        // The Steam folder is usually listed too; keep its app map
+        if let Some(existing) = folders.iter_mut().find(|f| same_path(&f.path, &folder.path)) {
+            *existing = folder;
+        } else {
+            folders.push(folder);
+        }
+    }
+    folders
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    a == b || matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+}
+
+pub fn read_app_manifest(steamapps: &Path, app_id: u32) -> Option<AppManifest> {
+    let root = read(&steamapps.join(format!("appmanifest_{}.acf", app_id)))?;
+    let state = root.get_object("AppState")?;
+    Some(AppManifest {
+        app_id: state.get_num("appid").unwrap_or(app_id),
+        name: state.get_str("name").unwrap_or("").to_string(),
+        install_dir: state.get_str("installdir")?.to_string(),
+        build_id: state.get_num("buildid").unwrap_or(0),
+        state_flags: state.get_num("StateFlags").unwrap_or(0),
+        last_updated: state.get_num("LastUpdated").unwrap_or(0),
+    })
+}
+
+/// Ids of the `appmanifest_<appid>.acf` files in `steamapps`
+pub fn manifest_ids(steamapps: &Path) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir(steamapps) else { return Vec::new() };
+    entries.filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix("appmanifest_")?.strip_suffix(".acf")?.parse().ok()
+        })
+        .collect()
+}
+
+/// The library holding `app_id` and its manifest. Libraries whose `apps` map
+/// lists the app are checked first; the others only by their manifest path.
+/// A fully installed copy beats a partial one (e.g. a stale second library).
+pub fn find_app(libraries: &[LibraryFolder], app_id: u32) -> Option<(&LibraryFolder, AppManifest)> {
+    let listed = libraries.iter().filter(|lib| lib.apps.contains_key(&app_id));
+    let unlisted = libraries.iter().filter(|lib| !lib.apps.contains_key(&app_id));
+    let mut found = listed.chain(unlisted)
+        .filter_map(|lib| read_app_manifest(&lib.steamapps(), app_id).map(|manifest| (lib, manifest)));
+    let first = found.next()?;
+    if first.1.is_fully_installed() {
+        return Some(first);
+    }
+    Some(found.find(|(_, manifest)| manifest.is_fully_installed()).unwrap_or(first))
+}
@@ -20,7 +20,7 @@ import { getVersion } from '@tauri-apps/api/app';
 import { listen } from '@tauri-apps/api/event';
 import { UpdateModal } from './components/UpdateModal';
 import PreferencesModal from './components/PreferencesModal';
-import type { UpdateInfo } from './types/electron';
+import type { DownloadItem, DownloadProgress, GameExited, Lockfile, OrphanPlan, UpdateInfo } from './types/electron';
 
 function App() {
   const [communities, setCommunities] = useState<Community[]>([])
@@ -46,7 +46,13 @@ function App() {
 
   const [selectedMod, setSelectedMod] = useState<Package | null>(null)
   const [gameSearchQuery, setGameSearchQuery] = useState('')
-  const [progressState, setProgressState] = useState({
+  const [progressState, setProgressState] = useState<{
+    isOpen: boolean;
+    title: string;
+    progress: number;
+    currentTask: string;
+    onCancel?: () => void;
+  }>({
     isOpen: false,
     title: '',
     progress: 0,
@@ -63,6 +69,7 @@ function App() {
     orphanDeps: { name: string; icon?: string }[];
     allInstalledDeps: string[];
     profileId: string | null;
+    plan?: OrphanPlan | null;
   }>({
     isOpen: false,
     pkg: null,
@@ -74,6 +81,7 @@ function App() {
   const [showUpdateModal, setShowUpdateModal] = useState(false)
   const [showPreferences, setShowPreferences] = useState(false)
   const [legacyInstallMode, setLegacyInstallMode] = useState(false)
+  const [archiveCacheQuotaMb, setArchiveCacheQuotaMb] = useState<number | null>(null)
   const [isBrowsingMode, setIsBrowsingMode] = useState(false)
 
   const {
@@ -93,14 +101,23 @@ function App() {
 
   useEffect(() => {
     loadData()
-    loadProfiles()
     checkForUpdates()
 
+    // Register before loading so a recovery during get_profiles is not missed
+    const unlistenRecovered = listen<{ backup: { file_name: string }; error: string }>('profiles-recovered', (event) => {
+      window.ipcRenderer.alert(
+        'Profiles Restored From Backup',
+        `profiles.json could not be read (${event.payload.error}).\n\nLoaded backup ${event.payload.backup.file_name} instead.`
+      );
+    });
+    unlistenRecovered.then(() => loadProfiles());
+
     // Load legacy mode setting
     window.ipcRenderer.getSettings().then((s: any) => {
       if (s.legacy_install_mode !== undefined) {
         setLegacyInstallMode(s.legacy_install_mode);
       }
+      setArchiveCacheQuotaMb(s.archive_cache_quota_mb ?? null);
     });
 
     // Listen for preferences menu event
@@ -110,6 +127,7 @@ function App() {
 
     return () => {
       unlisten.then(fn => fn());
+      unlistenRecovered.then(fn => fn());
     };
   }, [])
 
@@ -266,21 +284,41 @@ function App() {
     }
   }
 
-  const installModWithDependencies = async (
-    pkg: Package,
+  // Download and install `items` as one backend batch: bounded concurrency,
+  // aggregated progress and a Cancel button in the progress dialog
+  const runInstallBatch = async (profileId: string, gamePath: string, items: DownloadItem[], useProfileCache: boolean) => {
+    const batchId = crypto.randomUUID();
+    let finished = 0;
+    const unlistenProgress = listen<DownloadProgress>('download-progress', (event) => {
+      const p = event.payload;
+      if (p.batch_id !== batchId) return;
+      if (p.state === 'done' || p.state === 'failed' || p.state === 'cancelled') finished++;
+      setProgressState(prev => ({
+        ...prev,
+        progress: p.overall_total > 0 ? Math.min(100, (p.overall_downloaded / p.overall_total) * 100) : prev.progress,
+        currentTask: `Downloading ${finished}/${items.length}: ${p.mod_name}${p.state === 'retrying' ? ' (retrying)' : ''}`
+      }));
+    });
+    setProgressState(prev => ({ ...prev, onCancel: () => window.ipcRenderer.cancelDownloads(batchId) }));
+
+    try {
+      return await window.ipcRenderer.installMods(batchId, profileId, gamePath, items, useProfileCache);
+    } finally {
+      (await unlistenProgress)();
+      setProgressState(prev => ({ ...prev, onCancel: undefined }));
+    }
+  };
+
+  // Versions of `version` and every dependency not yet in the profile, dependencies first
+  const collectWithDependencies = async (
     version: PackageVersion,
-    installedCache: Set<string> = new Set(),
-    targetProfileId?: string,
-    progressCounter?: { installed: number; total: number },
-    gamePath?: string
+    profileIdToUse: string,
+    installedCache: Set<string>,
+    collected: PackageVersion[]
   ) => {
     if (installedCache.has(version.full_name)) return;
     installedCache.add(version.full_name);
 
-    const profileIdToUse = targetProfileId || activeProfileId;
-    if (!profileIdToUse) throw new Error("No profile selected");
-    if (!gamePath) throw new Error("Game path not provided");
-
     // 1. Collect all dependencies that need to be installed
     const depsToInstall: string[] = [];
     for (const depString of version.dependencies) {
@@ -317,16 +355,10 @@ function App() {
       try {
         const result = await window.ipcRenderer.lookupPackagesByNames(selectedCommunity, depsToInstall);
 
-        // Update total if we discovered more deps
-        if (progressCounter) {
-          progressCounter.total += result.found.length;
-        }
-
-        // Install each found dependency recursively
         for (const depPkg of result.found) {
           const depVersion = depPkg.versions[0];
           if (depVersion) {
-            await installModWithDependencies(depPkg, depVersion, installedCache, profileIdToUse, progressCounter, gamePath);
+            await collectWithDependencies(depVersion, profileIdToUse, installedCache, collected);
           }
         }
 
@@ -339,48 +371,50 @@ function App() {
       }
     }
 
-    // 3. Install the mod itself DIRECTLY to game folder
-    try {
-      // Update progress using shared counter if available
-      if (progressCounter) {
-        progressCounter.installed++;
-        const progress = Math.min(95, Math.round((progressCounter.installed / progressCounter.total) * 100));
-        setProgressState(prev => ({
-          ...prev,
-          progress,
-          currentTask: `Installing ${pkg.name}... (${progressCounter.installed}/${progressCounter.total})`
-        }));
-      } else {
-        setProgressState(prev => ({
-          ...prev,
-          currentTask: `Installing ${pkg.name}...`
-        }));
-      }
+    collected.push(version);
+  };
 
-      const result = await window.ipcRenderer.installMod(
-        profileIdToUse,
-        version.download_url,
-        version.full_name,
-        gamePath,
-        true  // useProfileCache - save to profile cache in legacy mode
-      );
+  const installModWithDependencies = async (
+    pkg: Package,
+    version: PackageVersion,
+    installedCache: Set<string> = new Set(),
+    targetProfileId?: string,
+    gamePath?: string
+  ) => {
+    const profileIdToUse = targetProfileId || activeProfileId;
+    if (!profileIdToUse) throw new Error("No profile selected");
+    if (!gamePath) throw new Error("Game path not provided");
 
-      if (result.success) {
-        const installedMod: InstalledMod = {
-          uuid4: version.uuid4,
-          fullName: version.full_name,
-          versionNumber: version.version_number,
-          iconUrl: version.icon,
-          enabled: true
-        };
-        addMod(profileIdToUse, installedMod);
-        console.log(`[Install] Successfully installed ${version.full_name}`);
-      } else {
-        throw new Error(result.error);
-      }
-    } catch (err: any) {
-      console.error(`Failed to install ${pkg.name}:`, err);
-      throw err;
+    const versions: PackageVersion[] = [];
+    await collectWithDependencies(version, profileIdToUse, installedCache, versions);
+    if (versions.length === 0) return;
+
+    // 3. Install the mod and its dependencies DIRECTLY to game folder, in one batch
+    setProgressState(prev => ({ ...prev, currentTask: `Installing ${pkg.name}...` }));
+    const results = await runInstallBatch(
+      profileIdToUse,
+      gamePath,
+      versions.map(v => ({ modName: v.full_name, downloadUrl: v.download_url, expectedSize: v.file_size })),
+      true  // useProfileCache - save to profile cache in legacy mode
+    );
+
+    for (const v of versions) {
+      if (results.find(r => r.mod_name === v.full_name)?.state !== 'done') continue;
+      const installedMod: InstalledMod = {
+        uuid4: v.uuid4,
+        fullName: v.full_name,
+        versionNumber: v.version_number,
+        iconUrl: v.icon,
+        enabled: true
+      };
+      addMod(profileIdToUse, installedMod);
+      console.log(`[Install] Successfully installed ${v.full_name}`);
+    }
+
+    const failed = results.filter(r => r.state !== 'done');
+    if (failed.length > 0) {
+      console.error(`Failed to install ${pkg.name}:`, failed);
+      throw new Error(failed.map(r => `${r.mod_name}: ${r.error ?? r.state}`).join('\n'));
     }
   };
 
@@ -416,8 +450,7 @@ function App() {
       });
 
       try {
-        const progressCounter = { installed: 0, total: 1 };
-        await installModWithDependencies(pkg, version, new Set(), profileIdToUse, progressCounter, gamePath);
+        await installModWithDependencies(pkg, version, new Set(), profileIdToUse, gamePath);
         setProgressState(prev => ({ ...prev, progress: 100, currentTask: 'Done!' }));
         setTimeout(() => setProgressState(prev => ({ ...prev, isOpen: false })), 500);
       } catch (err: any) {
@@ -530,66 +563,25 @@ function App() {
       return;
     }
 
-    // Get dependencies of this mod
-    // FILTER OUT BepInExPack IMMEDIATELY
-    const modDependencies = version.dependencies
-      .map(dep => {
-        const parts = dep.split('-');
-        return parts.length >= 2 ? `${parts[0]}-${parts[1]}` : dep;
-      })
-      .filter(dep => !dep.toLowerCase().includes('bepinexpack'));
-
-    // Find which dependencies are "orphan" (not used by other installed mods)
-    const orphanDepsDetails: { name: string; icon?: string }[] = [];
-
-    if (modDependencies.length > 0 && selectedCommunity) {
-      // Get dependencies of all OTHER installed mods
-      const otherMods = profile.mods.filter(m => !m.fullName.startsWith(pkg.full_name));
-      const otherModNames = otherMods.map(m => {
-        const parts = m.fullName.split('-');
-        return parts.length >= 2 ? `${parts[0]}-${parts[1]}` : m.fullName;
-      });
-
-      // Lookup other mods to get their dependencies
-      let otherModsDeps = new Set<string>();
-      if (otherModNames.length > 0) {
-        try {
-          const result = await window.ipcRenderer.lookupPackagesByNames(selectedCommunity, otherModNames);
-          for (const otherPkg of result.found) {
-            const otherVer = otherPkg.versions[0];
-            if (otherVer) {
-              for (const dep of otherVer.dependencies) {
-                const parts = dep.split('-');
-                const depName = parts.length >= 2 ? `${parts[0]}-${parts[1]}` : dep;
-                otherModsDeps.add(depName);
-              }
-            }
-          }
-        } catch (err) {
-          console.error('Failed to lookup other mods dependencies:', err);
-        }
-      }
-
-      // Find orphans: deps of mod being removed that are NOT in otherModsDeps AND are installed
-      for (const dep of modDependencies) {
-        if (!otherModsDeps.has(dep)) {
-          // Check if this dep is actually installed
-          const installedDep = profile.mods.find(m => m.fullName.startsWith(dep));
-          if (installedDep) {
-            orphanDepsDetails.push({
-              name: dep,
-              icon: installedDep.iconUrl
-            });
-          }
-        }
-      }
+    // Ask the backend which dependencies would become unreferenced
+    // (mod loaders such as BepInExPack are never offered as orphans)
+    let plan: OrphanPlan | null = null;
+    try {
+      plan = await window.ipcRenderer.computeOrphans(profileIdToUse, [pkg.full_name]);
+    } catch (err) {
+      console.error('Failed to compute orphaned dependencies:', err);
     }
 
-    // Get ALL installed dependencies (not just orphans)
-    // Also excluding BepInExPack (already filtered from modDependencies)
-    const allInstalledDeps = modDependencies.filter(dep =>
-      profile.mods.some(m => m.fullName.startsWith(dep))
-    );
+    const orphanDepsDetails: { name: string; icon?: string }[] = (plan?.orphans ?? []).map(orphan => ({
+      name: orphan.name,
+      icon: profile.mods.find(m => m.fullName.startsWith(orphan.name))?.iconUrl
+    }));
+
+    // Get ALL installed dependencies (not just orphans), excluding mod loaders
+    const allInstalledDeps = [
+      ...(plan?.orphans ?? []).map(o => o.name),
+      ...(plan?.retained ?? []).filter(r => r.needed_by.length > 0).map(r => r.name)
+    ];
 
     // If there are NO removable dependencies (or only BepInExPack which is filtered out),
     // skip the complex modal and show a simple confirmation dialog.
@@ -625,18 +617,16 @@ function App() {
       pkg,
       orphanDeps: orphanDepsDetails,
       allInstalledDeps,
-      profileId: profileIdToUse
+      profileId: profileIdToUse,
+      plan
     });
   };
 
   // Execute the actual uninstall with given deps to remove
   const executeUninstall = async (depsToRemove: string[]) => {
-    const { pkg, profileId } = uninstallModalState;
+    const { pkg, profileId, plan } = uninstallModalState;
     if (!pkg || !profileId) return;
 
-    const profile = profiles.find(p => p.id === profileId);
-    if (!profile) return;
-
     // Close modal
     setUninstallModalState(prev => ({ ...prev, isOpen: false }));
 
@@ -645,34 +635,19 @@ function App() {
       isOpen: true,
       title: `Uninstalling ${pkg.name}`,
       progress: 0,
-      currentTask: 'Removing mod...'
+      currentTask: depsToRemove.length > 0 ? `Removing mod and ${depsToRemove.length} dependencies...` : 'Removing mod...'
     });
 
     try {
-      // Remove the main mod
-      const installed = profile.mods.find(m => m.fullName.startsWith(pkg.full_name));
-      if (installed) {
-        await removeMod(profileId, installed.uuid4);
-      }
-
-      setProgressState(prev => ({ ...prev, progress: 30 }));
-
-      // Remove selected dependencies
-      if (depsToRemove.length > 0) {
-        const total = depsToRemove.length;
-        for (let i = 0; i < depsToRemove.length; i++) {
-          const depName = depsToRemove[i];
-          const depMod = profile.mods.find(m => m.fullName.startsWith(depName));
-          if (depMod) {
-            setProgressState(prev => ({
-              ...prev,
-              progress: 30 + Math.round((i / total) * 60),
-              currentTask: `Removing ${depName}... (${i + 1}/${total})`
-            }));
-            await removeMod(profileId, depMod.uuid4);
-          }
-        }
-      }
+      // Remove the mod and the selected dependencies in one backend operation
+      await window.ipcRenderer.removeModsWithPlan(profileId, {
+        profile_id: profileId,
+        remove: plan?.remove.length ? plan.remove : [pkg.full_name],
+        orphans: depsToRemove.map(name => ({ name, version: '', orphaned_by: [] })),
+        retained: [],
+        unresolved: []
+      });
+      await loadProfiles();
 
       setProgressState(prev => ({ ...prev, progress: 100, currentTask: 'Done!' }));
       setTimeout(() => setProgressState(prev => ({ ...prev, isOpen: false })), 500);
@@ -748,7 +723,7 @@ function App() {
   const handleExportFile = async () => {
     if (!activeProfileId) return;
     try {
-      const result = await window.ipcRenderer.exportProfile(activeProfileId);
+      const result = await window.ipcRenderer.exportProfile(activeProfileId, true);
       if (result.success) {
         alert(`Profile exported to: ${result.path}`);
       }
@@ -768,7 +743,7 @@ function App() {
     });
 
     try {
-      const code = await window.ipcRenderer.shareProfile(activeProfileId);
+      const code = await window.ipcRenderer.shareProfile(activeProfileId, true);
 
       setProgressState(prev => ({ ...prev, progress: 100, currentTask: 'Done!' }));
       setTimeout(() => {
@@ -831,6 +806,16 @@ function App() {
 
       const newProfileId = createProfile(profileName, selectedCommunity!);
 
+      // Seed the lockfile so installs get the exact archives the exporter had
+      const lockfile: Lockfile | null = result.lockfile ?? null;
+      if (lockfile) {
+        try {
+          await window.ipcRenderer.saveProfileLockfile(newProfileId, lockfile);
+        } catch (e) {
+          console.error('Failed to save imported lockfile', e);
+        }
+      }
+
       setProgressState({
         isOpen: true,
         title: 'Importing Profile',
@@ -846,57 +831,43 @@ function App() {
 
         let installedCount = 0;
         const totalMods = modsToInstall.length;
-        const BATCH_SIZE = 5;
         const failedMods: string[] = [];
 
-        // Process in batches
-        for (let i = 0; i < totalMods; i += BATCH_SIZE) {
-          const batch = modsToInstall.slice(i, i + BATCH_SIZE);
-
-          await Promise.all(batch.map(async (mod: any, batchIdx: number) => {
-            const globalIdx = i + batchIdx;
-
-            // Update progress
-            setProgressState(prev => ({
-              ...prev,
-              progress: Math.round(((globalIdx + 1) / totalMods) * 100),
-              currentTask: `Installing ${mod.name} (${globalIdx + 1}/${totalMods})...`
-            }));
-
-            try {
-              // Find package from lookup results
-              const pkg = lookup.found.find((p: Package) => p.full_name === mod.name);
-
-              if (pkg) {
-                const version = pkg.versions.find(v => v.version_number === mod.version) || pkg.versions[0];
-
-                const installResult = await window.ipcRenderer.installMod(
-                  newProfileId,
-                  version.download_url,
-                  version.full_name,
-                  gamePath,
-                  legacyInstallMode
-                );
-
-                if (installResult.success) {
-                  const installedMod: InstalledMod = {
-                    uuid4: version.uuid4,
-                    fullName: version.full_name,
-                    versionNumber: version.version_number,
-                    iconUrl: version.icon,
-                    enabled: mod.enabled
-                  };
-                  addMod(newProfileId, installedMod);
-                  installedCount++;
-                } else {
-                  throw new Error(installResult.error);
-                }
-              }
-            } catch (e) {
-              failedMods.push(mod.name);
-              console.error(`Error installing ${mod.name}`, e);
+        // Resolve every mod to its version, then download and install them as one batch
+        const toInstall: { version: PackageVersion; enabled: boolean }[] = [];
+        const downloads: DownloadItem[] = [];
+        for (const mod of modsToInstall) {
+          const pkg = lookup.found.find((p: Package) => p.full_name === mod.name);
+          if (!pkg) continue;
+          const version = pkg.versions.find(v => v.version_number === mod.version) || pkg.versions[0];
+          const locked = lockfile?.packages[mod.name];
+          const downloadUrl = locked && locked.version === version.version_number ? locked.downloadUrl : version.download_url;
+          toInstall.push({ version, enabled: mod.enabled });
+          downloads.push({ modName: version.full_name, downloadUrl, expectedSize: version.file_size });
+        }
+
+        try {
+          const results = await runInstallBatch(newProfileId, gamePath, downloads, legacyInstallMode);
+          for (const { version, enabled } of toInstall) {
+            const itemResult = results.find(r => r.mod_name === version.full_name);
+            if (itemResult?.state !== 'done') {
+              failedMods.push(version.full_name);
+              console.error(`Error installing ${version.full_name}`, itemResult?.error ?? itemResult?.state);
+              continue;
             }
-          }));
+            const installedMod: InstalledMod = {
+              uuid4: version.uuid4,
+              fullName: version.full_name,
+              versionNumber: version.version_number,
+              iconUrl: version.icon,
+              enabled
+            };
+            addMod(newProfileId, installedMod);
+            installedCount++;
+          }
+        } catch (e) {
+          console.error('Error installing imported mods', e);
+          failedMods.push(...toInstall.map(({ version }) => version.full_name));
         }
 
         setProgressState(prev => ({ ...prev, progress: 100, currentTask: 'Import Complete!' }));
@@ -1109,7 +1080,7 @@ function App() {
                   currentTask: `Installing missing requirement: ${bepInExPkg.name}...`
                 }));
 
-                await installModWithDependencies(bepInExPkg, version, new Set(), activeProfile.id, undefined, gamePath);
+                await installModWithDependencies(bepInExPkg, version, new Set(), activeProfile.id, gamePath);
 
                 console.log("[Auto-Install] BepInExPack installed successfully.");
               } else {
@@ -1137,6 +1108,7 @@ function App() {
               });
 
               let installed = 0;
+              const downloads: DownloadItem[] = [];
               for (const modKey of syncResult.to_install) {
                 // Find mod in profile
                 const modInProfile = activeProfile.mods.find(m => {
@@ -1154,26 +1126,31 @@ function App() {
                       installed++;
                       setProgressState(prev => ({
                         ...prev,
-                        progress: Math.round((installed / syncResult.to_install.length) * 100),
                         currentTask: `Copied from cache ${installed}/${syncResult.to_install.length}: ${modKey}`
                       }));
                       continue; // Skip download
                     }
                   }
 
-                  // Fallback: Download from Thunderstore
+                  // Fallback: Queue download from Thunderstore
                   const pkg = await window.ipcRenderer.fetchPackageByName(modInProfile.fullName, currentCommunity.identifier);
                   if (pkg) {
                     const version = pkg.versions.find((v: any) => v.version_number === modInProfile.versionNumber) || pkg.versions[0];
-                    await window.ipcRenderer.installMod(activeProfile.id, version.download_url, version.full_name, gamePath, legacyInstallMode);
+                    downloads.push({ modName: version.full_name, downloadUrl: version.download_url, expectedSize: version.file_size });
                   }
                 }
-                installed++;
-                setProgressState(prev => ({
-                  ...prev,
-                  progress: Math.round((installed / syncResult.to_install.length) * 100),
-                  currentTask: `Installed ${installed}/${syncResult.to_install.length}: ${modKey}`
-                }));
+              }
+
+              if (downloads.length > 0) {
+                const results = await runInstallBatch(activeProfile.id, gamePath, downloads, legacyInstallMode);
+
+                const failed = results.filter(r => r.state === 'failed');
+                if (failed.length > 0) {
+                  await window.ipcRenderer.alert(
+                    'Some mods failed to install',
+                    failed.map(r => `${r.mod_name}: ${r.error}`).join('\n')
+                  );
+                }
               }
               setProgressState(prev => ({ ...prev, isOpen: false }));
             }
@@ -1194,6 +1171,19 @@ function App() {
               message = `Sync complete! ${parts.join(', ')}.`;
             }
 
+            const configSync = syncResult.config_sync;
+            if (configSync && configSync.copied.length > 0) {
+              message += `\n${configSync.copied.length} config file(s) changed in-game were saved to the previous profile.`;
+            }
+            if (configSync && configSync.conflicts.length > 0) {
+              message += `\nConfig conflicts (kept the profile's version, the game's copy was saved aside):\n${configSync.conflicts.map(c => c.path).join('\n')}`;
+            }
+
+            const foreign = syncResult.cleanup.foreign;
+            if (foreign.length > 0) {
+              message += `\n${foreign.length} file(s) in the game folder were not installed by r2modmac and were left alone:\n${foreign.slice(0, 10).join('\n')}${foreign.length > 10 ? '\n...' : ''}`;
+            }
+
             await window.ipcRenderer.alert('Success', message);
             setShowCrossOverGuide(true);
           } catch (e: any) {
@@ -1202,6 +1192,34 @@ function App() {
             alert('Error syncing: ' + e);
           }
         }}
+        onLaunchGame={async () => {
+          if (!activeProfile || !currentCommunity) return;
+          try {
+            await window.ipcRenderer.launchGame(currentCommunity.identifier, activeProfile.id);
+            const unlisten = await listen<GameExited>('game-exited', async (event) => {
+              if (event.payload.profile_id !== activeProfile.id) return;
+              unlisten();
+              const problems: string[] = [];
+              if (event.payload.exit_code !== null && event.payload.exit_code !== 0) {
+                problems.push(`The game exited with code ${event.payload.exit_code}.`);
+              }
+              try {
+                const summary = await window.ipcRenderer.getLastSessionSummary(event.payload.game_identifier);
+                for (const report of summary.reports) {
+                  const what = report.load_failed ? 'failed to load' : `${report.errors} error(s), ${report.exceptions} exception(s)`;
+                  problems.push(`${report.package ?? report.plugin}: ${what}${report.first_error ? ` - ${report.first_error}` : ''}`);
+                }
+              } catch (e) {
+                console.warn('No BepInEx log summary:', e);
+              }
+              if (problems.length > 0) {
+                await window.ipcRenderer.alert('Game Exited', problems.join('\n'));
+              }
+            });
+          } catch (e: any) {
+            await window.ipcRenderer.alert('Cannot Launch Game', String(e));
+          }
+        }}
         onExportProfile={() => setShowExportModal(true)}
         onOpenSettings={() => setShowSettings(true)}
       />
@@ -1353,6 +1371,7 @@ function App() {
         title={progressState.title}
         progress={progressState.progress}
         currentTask={progressState.currentTask}
+        onCancel={progressState.onCancel}
       />
 
       <UninstallModal
@@ -1431,14 +1450,16 @@ function App() {
       <PreferencesModal
         isOpen={showPreferences}
         onClose={() => setShowPreferences(false)}
-        settings={{ legacy_install_mode: legacyInstallMode }}
+        settings={{ legacy_install_mode: legacyInstallMode, archive_cache_quota_mb: archiveCacheQuotaMb }}
         onSave={async (newSettings) => {
           setLegacyInstallMode(newSettings.legacy_install_mode);
+          setArchiveCacheQuotaMb(newSettings.archive_cache_quota_mb);
           // Save to backend
           const currentSettings = await window.ipcRenderer.getSettings();
           await window.ipcRenderer.saveSettings({
             ...currentSettings,
-            legacy_install_mode: newSettings.legacy_install_mode
+            legacy_install_mode: newSettings.legacy_install_mode,
+            archive_cache_quota_mb: newSettings.archive_cache_quota_mb
           });
         }}
       />
@@ -1,25 +1,37 @@
 import { useState, useEffect } from 'react';
+import type { ArchiveCacheStats } from '../types/electron';
 
 interface PreferencesModalProps {
     isOpen: boolean;
     onClose: () => void;
     settings: {
         legacy_install_mode: boolean;
+        archive_cache_quota_mb: number | null;
     };
-    onSave: (settings: { legacy_install_mode: boolean }) => void;
+    onSave: (settings: { legacy_install_mode: boolean; archive_cache_quota_mb: number | null }) => void;
 }
 
 export default function PreferencesModal({ isOpen, onClose, settings, onSave }: PreferencesModalProps) {
     const [legacyMode, setLegacyMode] = useState(settings.legacy_install_mode);
+    const [quotaMb, setQuotaMb] = useState(settings.archive_cache_quota_mb?.toString() ?? '');
+    const [cacheStats, setCacheStats] = useState<ArchiveCacheStats | null>(null);
 
     useEffect(() => {
         setLegacyMode(settings.legacy_install_mode);
+        setQuotaMb(settings.archive_cache_quota_mb?.toString() ?? '');
     }, [settings]);
 
+    useEffect(() => {
+        if (isOpen) {
+            window.ipcRenderer.getArchiveCacheStats().then(setCacheStats).catch(() => setCacheStats(null));
+        }
+    }, [isOpen]);
+
     if (!isOpen) return null;
 
     const handleSave = () => {
-        onSave({ legacy_install_mode: legacyMode });
+        const quota = parseInt(quotaMb, 10);
+        onSave({ legacy_install_mode: legacyMode, archive_cache_quota_mb: quota > 0 ? quota : null });
         onClose();
     };
 
@@ -84,6 +96,44 @@ export default function PreferencesModal({ isOpen, onClose, settings, onSave }:
                         </div>
                     </div>
 
+                    {/* Download Cache Section */}
+                    <div className="p-4 rounded-lg bg-gray-800/50 border border-gray-700 space-y-3">
+                        <div className="flex items-center justify-between gap-4">
+                            <div className="flex-1">
+                                <h4 className="text-white font-medium text-sm mb-1">Download Cache</h4>
+                                <p className="text-gray-400 text-xs">
+                                    {cacheStats
+                                        ? `${cacheStats.entry_count} archive(s), ${(cacheStats.total_bytes / 1024 / 1024).toFixed(1)} MB shared by all profiles.`
+                                        : 'Downloaded mod archives shared by all profiles.'}
+                                </p>
+                            </div>
+                            <button
+                                onClick={async () => {
+                                    const result = await window.ipcRenderer.pruneArchiveCache(30);
+                                    const sizeMB = (result.bytes_freed / 1024 / 1024).toFixed(1);
+                                    await window.ipcRenderer.alert(
+                                        'Cache Pruned',
+                                        `Removed ${result.removed.length} archive(s) unused for 30 days, freed ${sizeMB} MB.`
+                                    );
+                                    setCacheStats(await window.ipcRenderer.getArchiveCacheStats());
+                                }}
+                                className="px-3 py-1.5 rounded-lg bg-gray-700 hover:bg-gray-600 text-gray-200 text-sm font-medium transition-colors flex-shrink-0"
+                            >
+                                Prune Unused
+                            </button>
+                        </div>
+                        <label className="flex items-center justify-between gap-4 text-sm text-gray-300">
+                            Size limit (MB, empty = unlimited)
+                            <input
+                                type="number"
+                                min={0}
+                                value={quotaMb}
+                                onChange={(e) => setQuotaMb(e.target.value)}
+                                className="w-28 px-2 py-1 rounded-lg bg-gray-900 border border-gray-700 text-white text-sm"
+                            />
+                        </label>
+                    </div>
+
                     {/* Clear Cache Section */}
                     <div className="p-4 rounded-lg bg-red-900/20 border border-red-800">
                         <div className="flex items-center justify-between gap-4">
@@ -13,6 +13,7 @@ interface ProfileSidebarProps {
     onOpenModFolder: (profileId: string, modName: string) => void;
     onUninstallMod: (mod: InstalledMod) => void;
     onInstallToGame: () => void;
+    onLaunchGame?: () => void;
     onResolvePackage: (mod: InstalledMod) => Promise<Package | null>;
     onExportProfile: () => void;
     onOpenSettings: () => void;
@@ -29,6 +30,7 @@ export const ProfileSidebar: React.FC<ProfileSidebarProps> = ({
     onOpenModFolder,
     onUninstallMod,
     onInstallToGame,
+    onLaunchGame,
     onResolvePackage,
     onExportProfile,
     onOpenSettings
@@ -100,7 +102,7 @@ export const ProfileSidebar: React.FC<ProfileSidebarProps> = ({
                     const modNameWithoutVersion = mod.fullName.replace(/-\d+\.\d+\.\d+$/, '');
                     const pkg = packages.find(p => p.full_name === modNameWithoutVersion);
                     const latestVersion = pkg?.versions[0].version_number;
-                    const hasUpdate = latestVersion && latestVersion !== mod.versionNumber;
+                    const hasUpdate = !mod.pinned && latestVersion && latestVersion !== mod.versionNumber;
 
                     return (
                         <div
@@ -237,6 +239,19 @@ export const ProfileSidebar: React.FC<ProfileSidebarProps> = ({
                     </button>
                 )}
 
+                {activeProfile && onLaunchGame && (
+                    <button
+                        onClick={onLaunchGame}
+                        className="w-full flex items-center justify-center gap-2 px-4 py-2.5 rounded-xl bg-gray-800 hover:bg-gray-700 text-gray-200 hover:text-white border border-gray-700 hover:border-gray-600 transition-colors"
+                    >
+                        <svg xmlns="http://www.w3.org/2000/svg" className="h-5 w-5" fill="none" viewBox="0 0 24 24" stroke="currentColor">
+                            <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M14.752 11.168l-3.197-2.132A1 1 0 0010 9.87v4.263a1 1 0 001.555.832l3.197-2.132a1 1 0 000-1.664z" />
+                            <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M21 12a9 9 0 11-18 0 9 9 0 0118 0z" />
+                        </svg>
+                        <span className="font-bold text-sm tracking-wide">Launch Game</span>
+                    </button>
+                )}
+
                 {/* Secondary Actions */}
                 <div className="grid grid-cols-2 gap-2">
                     {activeProfile ? (
@@ -3,9 +3,10 @@ interface ProgressModalProps {
     title: string;
     progress: number; // 0 to 100
     currentTask: string;
+    onCancel?: () => void;
 }
 
-export function ProgressModal({ isOpen, title, progress, currentTask }: ProgressModalProps) {
+export function ProgressModal({ isOpen, title, progress, currentTask, onCancel }: ProgressModalProps) {
     if (!isOpen) return null;
 
     return (
@@ -31,6 +32,17 @@ export function ProgressModal({ isOpen, title, progress, currentTask }: Progress
                         <path className="opacity-75" fill="currentColor" d="M4 12a8 8 0 018-8V0C5.373 0 0 5.373 0 12h4zm2 5.291A7.962 7.962 0 014 12H0c0 3.042 1.135 5.824 3 7.938l3-2.647z"></path>
                     </svg>
                 </div>
+
+                {onCancel && (
+                    <div className="flex justify-center mt-4">
+                        <button
+                            onClick={onCancel}
+                            className="px-4 py-2 rounded-lg bg-gray-700 text-gray-300 hover:bg-gray-600 text-sm transition-colors"
+                        >
+                            Cancel
+                        </button>
+                    </div>
+                )}
             </div>
         </div>
     );
@@ -1,4 +1,13 @@
 import { useState, useEffect } from 'react';
+import type { GameLocation, ProtonStatus, SteamInstallation, WineLaunch } from '../types/electron';
+
+const SOURCE_LABELS: Record<GameLocation['source'], string> = {
+    manual: 'Set manually',
+    steam: 'Steam',
+    epic: 'Epic (Heroic/Legendary)',
+    gog: 'GOG (Heroic)',
+    lutris: 'Lutris',
+};
 
 interface SettingsModalProps {
     isOpen: boolean;
@@ -9,12 +18,16 @@ interface SettingsModalProps {
 export function SettingsModal({ isOpen, onClose, selectedGame }: SettingsModalProps) {
     const [steamPath, setSteamPath] = useState<string>('');
     const [loading, setLoading] = useState(false);
-    const [gamePath, setGamePath] = useState<string | null>(null);
+    const [gameLocation, setGameLocation] = useState<GameLocation | null>(null);
     const [checkingGamePath, setCheckingGamePath] = useState(false);
+    const [detectedSteam, setDetectedSteam] = useState<SteamInstallation[]>([]);
+    const [protonStatus, setProtonStatus] = useState<ProtonStatus | null>(null);
+    const [wineLaunch, setWineLaunch] = useState<WineLaunch | null>(null);
 
     useEffect(() => {
         if (isOpen) {
             loadSettings();
+            window.ipcRenderer.detectSteamInstallations().then(setDetectedSteam).catch(() => setDetectedSteam([]));
             if (selectedGame) {
                 checkGamePath();
             }
@@ -25,6 +38,7 @@ export function SettingsModal({ isOpen, onClose, selectedGame }: SettingsModalPr
         try {
             const settings = await window.ipcRenderer.getSettings();
             setSteamPath(settings.steam_path || '');
+            setWineLaunch((selectedGame && settings.wine_launch?.[selectedGame]) || null);
         } catch (e) {
             console.error("Failed to load settings", e);
         }
@@ -35,11 +49,11 @@ export function SettingsModal({ isOpen, onClose, selectedGame }: SettingsModalPr
 
         setCheckingGamePath(true);
         try {
-            const path = await window.ipcRenderer.getGamePath(selectedGame);
-            setGamePath(path);
+            setGameLocation(await window.ipcRenderer.getGameLocation(selectedGame));
+            setProtonStatus(await window.ipcRenderer.getProtonStatus(selectedGame).catch(() => null));
         } catch (e) {
             console.error("Failed to get game path", e);
-            setGamePath(null);
+            setGameLocation(null);
         }
         setCheckingGamePath(false);
     };
@@ -85,6 +99,12 @@ export function SettingsModal({ isOpen, onClose, selectedGame }: SettingsModalPr
         }
     };
 
+    const updateWineLaunch = async (config: WineLaunch | null) => {
+        if (!selectedGame) return;
+        await window.ipcRenderer.setWineLaunch(selectedGame, config);
+        setWineLaunch(config);
+    };
+
     const handleManualGamePath = async () => {
         if (!selectedGame) return;
         try {
@@ -127,6 +147,27 @@ export function SettingsModal({ isOpen, onClose, selectedGame }: SettingsModalPr
                     <p className="text-xs text-gray-500 mt-2">
                         Select the Steam folder inside your bottle (e.g., drive_c/Program Files (x86)/Steam).
                     </p>
+                    {detectedSteam.length > 0 && (
+                        <div className="mt-3 space-y-1">
+                            <p className="text-xs text-gray-400">Detected Steam installations:</p>
+                            {detectedSteam.map((install) => (
+                                <button
+                                    key={install.path}
+                                    onClick={() => setSteamPath(install.path)}
+                                    className={`w-full text-left px-3 py-2 rounded-lg border text-xs transition-colors ${steamPath === install.path
+                                        ? 'bg-blue-900/30 border-blue-700 text-blue-200'
+                                        : 'bg-gray-800 border-gray-700 text-gray-300 hover:bg-gray-700'
+                                        }`}
+                                >
+                                    <span className="block break-all">{install.path}</span>
+                                    <span className="text-gray-500">
+                                        {install.kind} · {install.library_count} {install.library_count === 1 ? 'library' : 'libraries'}
+                                        {install.last_modified > 0 && ` · updated ${new Date(install.last_modified * 1000).toLocaleDateString()}`}
+                                    </span>
+                                </button>
+                            ))}
+                        </div>
+                    )}
                 </div>
 
                 {selectedGame && (
@@ -143,7 +184,7 @@ export function SettingsModal({ isOpen, onClose, selectedGame }: SettingsModalPr
                                 </svg>
                                 Checking...
                             </div>
-                        ) : gamePath ? (
+                        ) : gameLocation ? (
                             <div className="space-y-2">
                                 <div className="flex gap-2">
                                     <button
@@ -169,7 +210,10 @@ export function SettingsModal({ isOpen, onClose, selectedGame }: SettingsModalPr
                                     <svg className="h-3.5 w-3.5 text-green-500 mt-0.5 flex-shrink-0" fill="currentColor" viewBox="0 0 20 20">
                                         <path fillRule="evenodd" d="M10 18a8 8 0 100-16 8 8 0 000 16zm3.707-9.293a1 1 0 00-1.414-1.414L9 10.586 7.707 9.293a1 1 0 00-1.414 1.414l2 2a1 1 0 001.414 0l4-4z" clipRule="evenodd" />
                                     </svg>
-                                    <span className="text-gray-400 break-all">{gamePath}</span>
+                                    <span className="text-gray-400 break-all">
+                                        {gameLocation.path}
+                                        <span className="text-gray-500"> ({SOURCE_LABELS[gameLocation.source]})</span>
+                                    </span>
                                 </div>
                             </div>
                         ) : steamPath ? (
@@ -195,6 +239,60 @@ export function SettingsModal({ isOpen, onClose, selectedGame }: SettingsModalPr
                                 Configure Steam directory to detect game location
                             </div>
                         )}
+
+                        {protonStatus?.uses_proton && (
+                            <div className="mt-3 text-xs bg-blue-900/20 border border-blue-800 rounded-lg p-3 space-y-2">
+                                <p className="text-blue-200">
+                                    Runs through Proton{protonStatus.compat_tool ? ` (${protonStatus.compat_tool})` : ''}. Set these Steam launch options so mods load:
+                                </p>
+                                <div className="flex gap-2">
+                                    <code className="flex-1 bg-gray-900 border border-gray-700 rounded px-2 py-1 text-gray-200 break-all">{protonStatus.launch_options}</code>
+                                    <button
+                                        onClick={() => navigator.clipboard.writeText(protonStatus.launch_options)}
+                                        className="bg-gray-700 hover:bg-gray-600 text-white px-2 py-1 rounded transition-colors"
+                                    >
+                                        Copy
+                                    </button>
+                                </div>
+                            </div>
+                        )}
+
+                        <div className="mt-3 text-xs space-y-2">
+                            <p className="text-gray-400">Wine/Proton binary for launching the Windows build directly (optional)</p>
+                            <div className="flex gap-2">
+                                <span className="flex-1 bg-gray-800 border border-gray-700 rounded-lg px-3 py-2 text-gray-300 break-all">
+                                    {wineLaunch ? `${wineLaunch.binary}${wineLaunch.prefix ? ` (prefix: ${wineLaunch.prefix})` : ''}` : 'Not set'}
+                                </span>
+                                <button
+                                    onClick={async () => {
+                                        const binary = await window.ipcRenderer.selectFile();
+                                        if (binary) await updateWineLaunch({ binary, prefix: wineLaunch?.prefix ?? null });
+                                    }}
+                                    className="bg-gray-700 hover:bg-gray-600 text-white px-3 py-2 rounded-lg transition-colors"
+                                >
+                                    Binary
+                                </button>
+                                {wineLaunch && (
+                                    <>
+                                        <button
+                                            onClick={async () => {
+                                                const prefix = await window.ipcRenderer.selectFolder();
+                                                if (prefix) await updateWineLaunch({ ...wineLaunch, prefix });
+                                            }}
+                                            className="bg-gray-700 hover:bg-gray-600 text-white px-3 py-2 rounded-lg transition-colors"
+                                        >
+                                            Prefix
+                                        </button>
+                                        <button
+                                            onClick={() => updateWineLaunch(null)}
+                                            className="bg-gray-800 hover:bg-gray-700 border border-gray-700 text-gray-400 hover:text-white px-3 py-2 rounded-lg transition-colors"
+                                        >
+                                            Clear
+                                        </button>
+                                    </>
+                                )}
+                            </div>
+                        </div>
                     </div>
                 )}
 
@@ -33,7 +33,7 @@ export function VirtualizedModGrid({ packages, installedMods, onInstall, onUnins
         if (!installed) return 'not_installed';
 
         // Compare versions
-        if (installed.versionNumber !== pkg.versions[0].version_number) {
+        if (!installed.pinned && installed.versionNumber !== pkg.versions[0].version_number) {
             return 'update_available';
         }
 
@@ -49,7 +49,10 @@ export const useProfileStore = create<ProfileState>((set) => ({
         // First delete from disk, THEN update state
         // This ensures if there's an error, we don't lose state
         try {
-            await window.ipcRenderer.deleteProfileFolder(profileId, gameIdentifier);
+            const result = await window.ipcRenderer.deleteProfileFolder(profileId, gameIdentifier);
+            if (result.cleanup && result.cleanup.foreign.length > 0) {
+                console.warn("Files not placed by the manager were left in the game folder:", result.cleanup.foreign);
+            }
         } catch (e) {
             console.error("Failed to delete profile folder:", e);
             // Continue anyway to clean up state
@@ -145,9 +148,10 @@ export const useProfileStore = create<ProfileState>((set) => ({
 
         const newEnabled = !mod.enabled;
 
-        // Extract mod name from fullName (format: "Author-ModName-Version")
-        const parts = mod.fullName.split('-');
-        const modName = parts.length >= 2 ? parts[1] : mod.fullName;
+        // "Author-ModName" from fullName (format: "Author-ModName-Version")
+        const modName = mod.fullName.endsWith(`-${mod.versionNumber}`)
+            ? mod.fullName.slice(0, -(mod.versionNumber.length + 1))
+            : mod.fullName;
 
         try {
             // Call backend to actually rename the DLL files and sync to game if applicable
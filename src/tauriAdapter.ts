@@ -1,18 +1,23 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { IElectronAPI } from './types/electron';
+import type { ArchiveCacheStats, ArchiveCachePruneResult, DownloadItemResult, IElectronAPI } from './types/electron';
 import type { Profile } from './types/profile';
 import type { Community, Package } from './types/thunderstore';
 
 export const tauriAPI: IElectronAPI = {
     getProfiles: () => invoke<Profile[]>('get_profiles'),
     saveProfiles: (profiles) => invoke('save_profiles', { profiles }),
+    listProfilesBackups: () => invoke('list_profiles_backups'),
+    restoreProfilesBackup: (fileName) => invoke<Profile[]>('restore_profiles_backup', { fileName }),
+    listProfileSnapshots: (profileId) => invoke('list_profile_snapshots', { profileId }),
+    diffProfileSnapshots: (profileId, from, to) => invoke('diff_profile_snapshots', { profileId, from, to }),
+    restoreProfileSnapshot: (profileId, snapshotId) => invoke<Profile>('restore_profile_snapshot', { profileId, snapshotId }),
 
     // Placeholder implementations for now
     selectFolder: async () => invoke<string | null>('select_folder'),
     selectFile: async (filters) => invoke<string | null>('select_file', { filters }),
-    installMod: async (profileId, downloadUrl, modName, gamePath, useProfileCache) => {
+    installMod: async (profileId, downloadUrl, modName, gamePath, useProfileCache, expectedSize) => {
         try {
-            await invoke('install_mod', { profileId, downloadUrl, modName, gamePath, useProfileCache: useProfileCache ?? false });
+            await invoke('install_mod', { profileId, downloadUrl, modName, gamePath, useProfileCache: useProfileCache ?? false, expectedSize });
             return { success: true };
         } catch (e) {
             return { success: false, error: String(e) };
@@ -58,27 +63,55 @@ export const tauriAPI: IElectronAPI = {
         return await invoke('lookup_packages_by_names', { gameId, names });
     },
     fetchPackageByName: async (name: string, gameId?: string | null) => invoke<Package | null>('fetch_package_by_name', { name, gameId }),
+    resolveDependencies: async (gameId, pkg, profileId) => invoke('resolve_dependencies', { gameId, package: pkg, profileId }),
+    checkProfileUpdates: async (profileId) => invoke('check_profile_updates', { profileId }),
+    updateProfileMods: async (batchId, profileId, packages) => invoke('update_profile_mods', { batchId, profileId, packages }),
+    listPackageVersions: async (gameId, pkg, profileId) => invoke('list_package_versions', { gameId, package: pkg, profileId }),
+    installModVersion: async (batchId, profileId, pkg, version) => invoke('install_mod_version', { batchId, profileId, package: pkg, version }),
+    setModPinned: async (profileId, pkg, pinned) => invoke('set_mod_pinned', { profileId, package: pkg, pinned }),
+    getProfileLockfile: async (profileId) => invoke('get_profile_lockfile', { profileId }),
+    saveProfileLockfile: async (profileId, lockfile) => invoke('save_profile_lockfile', { profileId, lockfile }),
     importProfile: async (code) => invoke<any>('import_profile', { code }),
     importProfileFromFile: async (path) => invoke<any>('import_profile_from_file', { path }),
-    shareProfile: async (profileId) => invoke<string>('share_profile', { profileId }),
+    shareProfile: async (profileId, includeLockfile) => invoke<string>('share_profile', { profileId, includeLockfile }),
     openModFolder: async (profileId, modName, gameIdentifier) => invoke('open_mod_folder', { profileId, modName, gameIdentifier }),
-    exportProfile: async (profileId) => {
+    exportProfile: async (profileId, includeLockfile) => {
         try {
-            return await invoke<any>('export_profile', { profileId });
+            return await invoke<any>('export_profile', { profileId, includeLockfile });
         } catch (e) {
             console.error("Export failed", e);
             throw e;
         }
     },
-    deleteProfileFolder: async (profileId, gameIdentifier?) => invoke<boolean>('delete_profile_folder', { profileId, gameIdentifier }),
+    deleteProfileFolder: async (profileId, gameIdentifier?) => invoke('delete_profile_folder', { profileId, gameIdentifier }),
     getSettings: async () => invoke('get_settings'),
     saveSettings: async (settings) => invoke('save_settings', { settings }),
+    detectSteamInstallations: async () => invoke('detect_steam_installations'),
     getGamePath: async (gameIdentifier) => invoke('get_game_path', { gameIdentifier }),
+    getGameLocation: async (gameIdentifier) => invoke('get_game_location', { gameIdentifier }),
     setGamePath: async (gameIdentifier, path) => invoke('set_game_path', { gameIdentifier, path }),
     openGameFolder: async (gameIdentifier) => invoke('open_game_folder', { gameIdentifier }),
+    findGameExecutable: async (gamePath, gameIdentifier?) => invoke('find_game_executable', { gamePath, gameIdentifier }),
+    launchGame: async (gameIdentifier, profileId, mode?) => invoke('launch_game', { gameIdentifier, profileId, mode }),
+    getProtonStatus: async (gameIdentifier) => invoke('get_proton_status', { gameIdentifier }),
+    startLogTail: async (gameIdentifier) => invoke('start_log_tail', { gameIdentifier }),
+    stopLogTail: async (gameIdentifier) => invoke('stop_log_tail', { gameIdentifier }),
+    getLastSessionSummary: async (gameIdentifier) => invoke('get_last_session_summary', { gameIdentifier }),
+    listConfigFiles: async (location) => invoke('list_config_files', { location }),
+    readConfigFile: async (location, path) => invoke('read_config_file', { location, path }),
+    setConfigValue: async (location, path, section, key, value) => invoke('set_config_value', { location, path, section, key, value }),
+    resetConfigEntry: async (location, path, section, key) => invoke('reset_config_entry', { location, path, section, key }),
+    resetConfigFile: async (location, path) => invoke('reset_config_file', { location, path }),
+    captureGameConfig: async (gameIdentifier) => invoke('capture_game_config', { gameIdentifier }),
+    setWineLaunch: async (gameIdentifier, config) => invoke('set_wine_launch', { gameIdentifier, config }),
+    getGameDefinition: async (gameIdentifier) => invoke('get_game_definition', { gameIdentifier }),
+    updateGameDefinitions: async (path) => invoke('update_game_definitions', { path }),
+    getSteamAppManifest: async (gameIdentifier) => invoke('get_steam_app_manifest', { gameIdentifier }),
     removeMod: async (profileId: string, modName: string) => {
         await invoke('remove_mod', { profileId, modName });
     },
+    computeOrphans: async (profileId, modsToRemove) => invoke('compute_orphans', { profileId, modsToRemove }),
+    removeModsWithPlan: async (profileId, plan) => invoke('remove_mod', { profileId, plan }),
     toggleMod: async (profileId: string, modName: string, enabled: boolean, gameIdentifier?: string) => {
         await invoke('toggle_mod', { profileId, modName, enabled, gameIdentifier });
     },
@@ -112,5 +145,10 @@ export const tauriAPI: IElectronAPI = {
     },
     clearProfileCache: async () => {
         return await invoke<{ cleared: number; bytes_freed: number }>('clear_profile_cache', {});
-    }
+    },
+    installMods: (batchId, profileId, gamePath, items, useProfileCache) =>
+        invoke<DownloadItemResult[]>('install_mods', { batchId, profileId, gamePath, items, useProfileCache: useProfileCache ?? false }),
+    cancelDownloads: (batchId, modName) => invoke<boolean>('cancel_downloads', { batchId, modName }),
+    getArchiveCacheStats: () => invoke<ArchiveCacheStats>('get_archive_cache_stats'),
+    pruneArchiveCache: (unusedForDays, maxMb) => invoke<ArchiveCachePruneResult>('prune_archive_cache', { unusedForDays, maxMb })
 };
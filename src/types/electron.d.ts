@@ -4,9 +4,14 @@ import type { Community, Package } from './thunderstore';
 export interface IElectronAPI {
     getProfiles: () => Promise<Profile[]>;
     saveProfiles: (profiles: Profile[]) => Promise<boolean>;
+    listProfilesBackups: () => Promise<BackupInfo[]>;
+    restoreProfilesBackup: (fileName: string) => Promise<Profile[]>;
+    listProfileSnapshots: (profileId: string) => Promise<SnapshotSummary[]>;
+    diffProfileSnapshots: (profileId: string, from: string, to?: string) => Promise<SnapshotDiff>;
+    restoreProfileSnapshot: (profileId: string, snapshotId: string) => Promise<Profile>;
     selectFolder: () => Promise<string | null>;
     selectFile: (filters?: { name: string; extensions: string[] }[]) => Promise<string | null>;
-    installMod: (profileId: string, downloadUrl: string, modName: string, gamePath: string, useProfileCache?: boolean) => Promise<{ success: boolean; error?: string }>;
+    installMod: (profileId: string, downloadUrl: string, modName: string, gamePath: string, useProfileCache?: boolean, expectedSize?: number) => Promise<{ success: boolean; error?: string }>;
     checkDirectoryExists: (dirPath: string) => Promise<boolean>;
     fetchCommunities: () => Promise<Community[]>;
     fetchCommunityImages: () => Promise<Record<string, string>>;
@@ -27,30 +32,206 @@ export interface IElectronAPI {
     ): Promise<any[]>;
     lookupPackagesByNames: (gameId: string, names: string[]) => Promise<{ found: Package[]; unknown: string[] }>;
     fetchPackageByName: (name: string, gameId?: string | null) => Promise<Package | null>;
+    resolveDependencies: (gameId: string, pkg?: string | null, profileId?: string | null) => Promise<Resolution>;
+    checkProfileUpdates: (profileId: string) => Promise<ModUpdate[]>;
+    updateProfileMods: (batchId: string, profileId: string, packages: string[]) => Promise<UpdateOutcome>;
+    listPackageVersions: (gameId: string, pkg: string, profileId?: string | null) => Promise<PackageVersion[]>;
+    installModVersion: (batchId: string, profileId: string, pkg: string, version: string) => Promise<UpdateOutcome>;
+    setModPinned: (profileId: string, pkg: string, pinned: boolean) => Promise<Profile>;
+    getProfileLockfile: (profileId: string) => Promise<Lockfile>;
+    saveProfileLockfile: (profileId: string, lockfile: Lockfile) => Promise<void>;
     importProfile: (code: string) => Promise<any>;
     importProfileFromFile: (path: string) => Promise<any>;
-    shareProfile: (profileId: string) => Promise<string>;
+    shareProfile: (profileId: string, includeLockfile?: boolean) => Promise<string>;
     openModFolder: (profileId: string, modName: string, gameIdentifier: string) => Promise<void>;
-    exportProfile: (profileId: string) => Promise<any>;
-    deleteProfileFolder: (profileId: string, gameIdentifier?: string) => Promise<boolean>;
-    getSettings: () => Promise<{ steam_path: string | null; favorite_games: string[]; game_paths: Record<string, string>; legacy_install_mode?: boolean }>;
-    saveSettings: (settings: { steam_path: string | null; favorite_games: string[]; game_paths: Record<string, string>; legacy_install_mode?: boolean }) => Promise<void>;
+    exportProfile: (profileId: string, includeLockfile?: boolean) => Promise<any>;
+    deleteProfileFolder: (profileId: string, gameIdentifier?: string) => Promise<{ deleted: boolean; cleanup: CleanupReport | null }>;
+    getSettings: () => Promise<{ steam_path: string | null; favorite_games: string[]; game_paths: Record<string, string>; legacy_install_mode?: boolean; archive_cache_quota_mb?: number | null; wine_launch?: Record<string, WineLaunch> }>;
+    saveSettings: (settings: { steam_path: string | null; favorite_games: string[]; game_paths: Record<string, string>; legacy_install_mode?: boolean; archive_cache_quota_mb?: number | null; wine_launch?: Record<string, WineLaunch> }) => Promise<void>;
+    detectSteamInstallations: () => Promise<SteamInstallation[]>;
     getGamePath: (gameIdentifier: string) => Promise<string | null>;
+    getGameLocation: (gameIdentifier: string) => Promise<GameLocation | null>;
     setGamePath: (gameIdentifier: string, path: string) => Promise<void>;
     openGameFolder: (gameIdentifier: string) => Promise<void>;
+    findGameExecutable: (gamePath: string, gameIdentifier?: string) => Promise<string | null>;
+    launchGame: (gameIdentifier: string, profileId: string, mode?: LaunchMode) => Promise<LaunchMode>;
+    getProtonStatus: (gameIdentifier: string) => Promise<ProtonStatus | null>;
+    startLogTail: (gameIdentifier: string) => Promise<void>;
+    stopLogTail: (gameIdentifier: string) => Promise<void>;
+    getLastSessionSummary: (gameIdentifier: string) => Promise<SessionSummary>;
+    listConfigFiles: (location: ConfigLocation) => Promise<ConfigFileInfo[]>;
+    readConfigFile: (location: ConfigLocation, path: string) => Promise<ConfigFile>;
+    setConfigValue: (location: ConfigLocation, path: string, section: string, key: string, value: string) => Promise<ConfigEntry>;
+    resetConfigEntry: (location: ConfigLocation, path: string, section: string, key: string) => Promise<ConfigEntry>;
+    resetConfigFile: (location: ConfigLocation, path: string) => Promise<ConfigFile>;
+    captureGameConfig: (gameIdentifier: string) => Promise<ReverseSync | null>;
+    setWineLaunch: (gameIdentifier: string, config: WineLaunch | null) => Promise<void>;
+    getGameDefinition: (gameIdentifier: string) => Promise<GameDefinition | null>;
+    updateGameDefinitions: (path: string) => Promise<number>;
+    getSteamAppManifest: (gameIdentifier: string) => Promise<SteamAppManifest | null>;
     removeMod: (profileId: string, modName: string) => Promise<void>;
+    computeOrphans: (profileId: string, modsToRemove: string[]) => Promise<OrphanPlan>;
+    removeModsWithPlan: (profileId: string, plan: OrphanPlan) => Promise<{ removed: boolean; removed_mods: string[]; failed: string[]; profile: Profile | null; game_cleanup: CleanupReport | null }>;
     toggleMod: (profileId: string, modName: string, enabled: boolean, gameIdentifier?: string) => Promise<void>;
     confirm: (title: string, message: string) => Promise<boolean>;
     alert: (title: string, message: string) => Promise<void>;
     readImage: (path: string) => Promise<string | null>;
-    installToGame: (gameIdentifier: string, profileId: string, disabledMods: string[]) => Promise<void>;
+    installToGame: (gameIdentifier: string, profileId: string, disabledMods: string[]) => Promise<CleanupReport>;
     fetchTextContent: (url: string) => Promise<string>;
     checkUpdate: (currentVersion: string) => Promise<UpdateInfo>;
     installUpdate: (downloadUrl: string) => Promise<void>;
     lookupPackagesByNames: (gameId: string, names: string[]) => Promise<any>;
-    syncProfileToGame: (profileId: string, gameIdentifier: string, useLegacyCache?: boolean) => Promise<{ removed: number; to_install: string[]; already_installed: number; cached: number }>;
-    copyModFromCache: (profileId: string, modName: string, gamePath: string) => Promise<{ success: boolean; copied: boolean }>;
+    syncProfileToGame: (profileId: string, gameIdentifier: string, useLegacyCache?: boolean) => Promise<{ removed: number; to_install: string[]; already_installed: number; cached: number; config_sync: ReverseSync | null; cleanup: CleanupReport }>;
+    copyModFromCache: (profileId: string, modName: string, gamePath: string) => Promise<{ success: boolean; copied: boolean; cleanup?: CleanupReport }>;
     clearProfileCache: () => Promise<{ cleared: number; bytes_freed: number }>;
+    installMods: (batchId: string, profileId: string, gamePath: string, items: DownloadItem[], useProfileCache?: boolean) => Promise<DownloadItemResult[]>;
+    cancelDownloads: (batchId: string, modName?: string) => Promise<boolean>;
+    getArchiveCacheStats: () => Promise<ArchiveCacheStats>;
+    pruneArchiveCache: (unusedForDays?: number, maxMb?: number) => Promise<ArchiveCachePruneResult>;
+}
+
+export interface DownloadItem {
+    modName: string;
+    downloadUrl: string;
+    expectedSize?: number;
+}
+
+export type DownloadState = 'queued' | 'downloading' | 'retrying' | 'installing' | 'done' | 'failed' | 'cancelled';
+
+export interface DownloadProgress {
+    batch_id: string;
+    mod_name: string;
+    state: DownloadState;
+    downloaded: number;
+    total: number;
+    overall_downloaded: number;
+    overall_total: number;
+    attempt: number;
+    error: string | null;
+}
+
+export interface DownloadItemResult {
+    mod_name: string;
+    state: DownloadState;
+    error: string | null;
+}
+
+export interface ArchiveCacheStats {
+    path: string;
+    entry_count: number;
+    total_bytes: number;
+    quota_bytes: number | null;
+}
+
+export interface ArchiveCachePruneResult {
+    removed: string[];
+    bytes_freed: number;
+}
+
+export interface BackupInfo {
+    file_name: string;
+    path: string;
+    created: number;
+    size: number;
+    valid: boolean;
+}
+
+export interface SnapshotSummary {
+    id: string;
+    created: number;
+    summary: string;
+    mod_count: number;
+    has_config: boolean;
+}
+
+export interface SnapshotDiff {
+    added: { name: string; version: string; enabled: boolean }[];
+    removed: { name: string; version: string; enabled: boolean }[];
+    version_changes: { name: string; from: string; to: string }[];
+    toggled: { name: string; enabled: boolean }[];
+    config_changes: string[];
+}
+
+export interface ResolvedPackage {
+    name: string;
+    version: string;
+    full_name: string;
+    download_url: string | null;
+    file_size: number;
+    dependencies: string[];
+    required_by: string[];
+    installed_version: string | null;
+    action: 'install' | 'keep' | 'upgrade' | 'downgrade';
+}
+
+export interface Resolution {
+    packages: ResolvedPackage[];
+    conflicts: { name: string; chosen: string; requests: { version: string; required_by: string }[] }[];
+    missing: { name: string; version: string | null; required_by: string[]; reason: string }[];
+}
+
+export interface DependencyChange {
+    package: string;
+    from: string | null;
+    to: string | null;
+}
+
+export interface ModUpdate {
+    package: string;
+    current_version: string;
+    latest_version: string | null;
+    update_available: boolean;
+    pinned: boolean;
+    enabled: boolean;
+    dependency_changes: DependencyChange[];
+}
+
+export interface PackageVersion {
+    version_number: string;
+    full_name: string;
+    date_created: string | null;
+    downloads: number;
+    file_size: number | null;
+    download_url: string;
+    dependencies: string[];
+    installed: boolean;
+}
+
+/** Game folder cleanup: only files the manager placed are removed */
+export interface CleanupReport {
+    removed: string[];
+    /** Placed by the manager but changed since, left alone */
+    modified: string[];
+    /** Not placed by the manager, left alone */
+    foreign: string[];
+}
+
+export interface LockedPackage {
+    version: string;
+    downloadUrl: string;
+    sha256: string;
+    dependencies: string[];
+}
+
+export interface Lockfile {
+    lockfileVersion: number;
+    /** By "Author-Name" */
+    packages: Record<string, LockedPackage>;
+}
+
+export interface UpdateOutcome {
+    results: DownloadItemResult[];
+    updated: { package: string; from: string; to: string }[];
+    skipped: { package: string; reason: string }[];
+    profile: Profile | null;
+}
+
+export interface OrphanPlan {
+    profile_id: string;
+    remove: string[];
+    orphans: { name: string; version: string; orphaned_by: string[] }[];
+    retained: { name: string; version: string; needed_by: string[]; reason: string }[];
+    unresolved: string[];
 }
 
 export interface UpdateInfo {
@@ -60,6 +241,145 @@ export interface UpdateInfo {
     download_url?: string;
 }
 
+export interface GameDefinition {
+    name: string;
+    steamAppId: number | null;
+    epicAppName: string | null;
+    gogId: string | null;
+    installFolders: string[];
+    executables: string[];
+    dataFolder: string | null;
+    loader: 'bepinex' | 'melonloader';
+    quirks: { windowsOnly: boolean; launchArgs: string[] };
+}
+
+export type LaunchMode = 'steam' | 'direct' | 'wine';
+
+export interface WineLaunch {
+    binary: string;
+    prefix: string | null;
+}
+
+export interface ProtonStatus {
+    uses_proton: boolean;
+    compat_tool: string | null;
+    compat_data: string | null;
+    launch_options: string;
+}
+
+export interface GameStarted {
+    game_identifier: string;
+    profile_id: string;
+    mode: LaunchMode;
+    pid: number | null;
+}
+
+export interface GameExited {
+    game_identifier: string;
+    profile_id: string;
+    exit_code: number | null;
+}
+
+export interface LogEntry {
+    level: 'fatal' | 'error' | 'warning' | 'message' | 'info' | 'debug';
+    source: string;
+    message: string;
+    kind: 'loaded' | 'loadFailed' | 'exception' | 'message';
+    plugin: string | null;
+    plugin_guid: string | null;
+    package: string | null;
+}
+
+export interface GameLogEvent {
+    game_identifier: string;
+    entries: LogEntry[];
+}
+
+export interface PluginReport {
+    plugin: string;
+    plugin_guid: string | null;
+    package: string | null;
+    errors: number;
+    exceptions: number;
+    warnings: number;
+    load_failed: boolean;
+    first_error: string | null;
+}
+
+export interface SessionSummary {
+    log_path: string;
+    modified: number;
+    plugins_loaded: number;
+    reports: PluginReport[];
+    unattributed_errors: number;
+}
+
+export type ConfigLocation = { profile: string } | { game: string };
+
+export interface ConfigConflict {
+    path: string;
+    game_copy: string;
+}
+
+export interface ReverseSync {
+    profile_id: string;
+    copied: string[];
+    removed: string[];
+    conflicts: ConfigConflict[];
+}
+
+export interface ConfigFileInfo {
+    path: string;
+    size: number;
+    modified: number;
+}
+
+export interface ConfigEntry {
+    key: string;
+    value: string;
+    description: string;
+    value_type: string | null;
+    default_value: string | null;
+    acceptable_values: string[];
+    acceptable_range: [string, string] | null;
+    is_flags: boolean;
+    line: number;
+}
+
+export interface ConfigSection {
+    name: string;
+    entries: ConfigEntry[];
+}
+
+export interface ConfigFile {
+    path: string;
+    plugin_name: string | null;
+    plugin_version: string | null;
+    plugin_guid: string | null;
+    sections: ConfigSection[];
+}
+
+export interface SteamInstallation {
+    path: string;
+    kind: 'native' | 'flatpak' | 'snap' | 'macos';
+    library_count: number;
+    last_modified: number;
+}
+
+export interface GameLocation {
+    path: string;
+    source: 'manual' | 'steam' | 'epic' | 'gog' | 'lutris';
+}
+
+export interface SteamAppManifest {
+    app_id: number;
+    name: string;
+    install_dir: string;
+    build_id: number;
+    state_flags: number;
+    last_updated: number;
+}
+
 declare global {
     interface Window {
         ipcRenderer: IElectronAPI;
@@ -4,6 +4,7 @@ export interface InstalledMod {
     versionNumber: string;
     iconUrl?: string;
     enabled: boolean;
+    pinned?: boolean; // left out of update checks
 }
 
 export interface Profile {